@@ -0,0 +1,659 @@
+// SPDX-License-Identifier: MPL-2.0
+//! A compact, versioned binary encoding of [`PageAnalysis`], for caching analyses.
+//!
+//! Unlike the `serde` implementation, which works with any serde format but carries no version
+//! marker, every record written by [`PageAnalysis::write_binary`] starts with a header:
+//!
+//! | Field | Encoding |
+//! | --- | --- |
+//! | magic number | the 4 bytes [`MAGIC`] |
+//! | schema version | `u16`, little endian ([`SCHEMA_VERSION`] when written by this crate) |
+//! | crate version | one length byte and the UTF-8 version of the crate that wrote the record |
+//! | payload length | `u64`, little endian |
+//!
+//! followed by the payload: the analysis as LEB128 varints and length-prefixed strings. The
+//! same analysis always encodes to the same bytes and the payload has no padding, so records
+//! compress well, e.g. with zstd. Records can be stored back to back in one stream;
+//! [`BinaryHeader::read`] tells which schema and crate version wrote a record without
+//! decoding it.
+//!
+//! # Schema versions
+//!
+//! [`PageAnalysis::read_binary`] reads every schema version since 1 and converts older
+//! versions to the current analysis structure, so an existing cache keeps working after an
+//! update of the crate. Writing the analysis again with [`PageAnalysis::write_binary`]
+//! migrates the record to the current schema version. Records of newer schema versions
+//! than this crate knows are rejected with [`BinaryFormatError::UnsupportedVersion`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use wikiwho::algorithm::{binary::BinaryHeader, PageAnalysis};
+//! # use wikiwho::dump_parser::{Contributor, Revision, Text};
+//! # let revision = Revision {
+//! #     id: 1,
+//! #     timestamp: chrono::DateTime::from_timestamp_nanos(1_700_000_000_000_000_000),
+//! #     contributor: Contributor { id: Some(1), username: "User".into(), deleted: false },
+//! #     text: Text::Normal("Hello world.".to_string()),
+//! #     sha1: None,
+//! #     comment: None,
+//! #     minor: false,
+//! #     model: None,
+//! #     format: None,
+//! #     slots: Vec::new(),
+//! # };
+//! let analysis = PageAnalysis::analyse_page(&[revision]).unwrap();
+//!
+//! let mut bytes = Vec::new();
+//! analysis.write_binary(&mut bytes).unwrap();
+//!
+//! let header = BinaryHeader::read(&mut bytes.as_slice()).unwrap();
+//! assert_eq!(header.crate_version, env!("CARGO_PKG_VERSION"));
+//! let restored = PageAnalysis::read_binary(bytes.as_slice()).unwrap();
+//! assert_eq!(restored.words.len(), analysis.words.len());
+//! ```
+use std::{
+    io::{self, Read, Write},
+    ops::Range,
+    sync::Arc,
+};
+
+use super::{
+    serialized::{
+        SerializedArcSubstring, SerializedPageAnalysis, SerializedParagraphAnalysis,
+        SerializedRevisionAnalysis, SerializedRevisionImmutables, SerializedSentenceAnalysis,
+        SerializedWordAnalysis,
+    },
+    PageAnalysis,
+};
+
+/// The magic number every record starts with.
+pub const MAGIC: [u8; 4] = *b"WWPA";
+
+/// The schema version written by [`PageAnalysis::write_binary`].
+pub const SCHEMA_VERSION: u16 = 1;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum BinaryFormatError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    /// The data doesn't start with [`MAGIC`].
+    #[error("not a binary page analysis (wrong magic number)")]
+    InvalidMagic,
+    /// The record was written with a newer schema version than this crate supports.
+    #[error("unsupported schema version {0} (this crate reads versions 1 to {SCHEMA_VERSION})")]
+    UnsupportedVersion(u16),
+    /// The payload can't be decoded into a valid analysis.
+    #[error("malformed payload: {0}")]
+    Malformed(String),
+}
+
+/// The header of a record, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryHeader {
+    pub schema_version: u16,
+    /// Version of the crate that wrote the record.
+    pub crate_version: String,
+    /// Length of the payload following the header, in bytes.
+    pub payload_len: u64,
+}
+
+impl BinaryHeader {
+    /// Reads the header of a record and leaves `reader` at the start of the payload.
+    ///
+    /// Fails if the magic number doesn't match, but accepts any schema version.
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, BinaryFormatError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(BinaryFormatError::InvalidMagic);
+        }
+        let mut schema_version = [0; 2];
+        reader.read_exact(&mut schema_version)?;
+        let mut crate_version_len = [0; 1];
+        reader.read_exact(&mut crate_version_len)?;
+        let mut crate_version = vec![0; crate_version_len[0] as usize];
+        reader.read_exact(&mut crate_version)?;
+        let mut payload_len = [0; 8];
+        reader.read_exact(&mut payload_len)?;
+
+        Ok(Self {
+            schema_version: u16::from_le_bytes(schema_version),
+            crate_version: String::from_utf8(crate_version).map_err(|_| {
+                BinaryFormatError::Malformed("crate version is not UTF-8".to_string())
+            })?,
+            payload_len: u64::from_le_bytes(payload_len),
+        })
+    }
+
+    fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&self.schema_version.to_le_bytes())?;
+        let crate_version = &self.crate_version.as_bytes()[..self.crate_version.len().min(255)];
+        writer.write_all(&[crate_version.len() as u8])?;
+        writer.write_all(crate_version)?;
+        writer.write_all(&self.payload_len.to_le_bytes())
+    }
+}
+
+impl PageAnalysis {
+    /// Writes the analysis as one record of the binary format, see [`binary`](super::binary).
+    pub fn write_binary<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut payload = Vec::new();
+        encode(&mut payload, &SerializedPageAnalysis::from_analysis(self));
+        let header = BinaryHeader {
+            schema_version: SCHEMA_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            payload_len: payload.len() as u64,
+        };
+        header.write(&mut writer)?;
+        writer.write_all(&payload)
+    }
+
+    /// Reads one record of the binary format, see [`binary`](super::binary).
+    ///
+    /// Exactly one record is consumed from `reader`, pass `&mut reader` to read several
+    /// records stored back to back. Records of older schema versions are migrated to the
+    /// current analysis structure.
+    pub fn read_binary<R: Read>(mut reader: R) -> Result<Self, BinaryFormatError> {
+        let header = BinaryHeader::read(&mut reader)?;
+        if header.schema_version == 0 || header.schema_version > SCHEMA_VERSION {
+            return Err(BinaryFormatError::UnsupportedVersion(header.schema_version));
+        }
+
+        let mut payload = Vec::new();
+        reader.take(header.payload_len).read_to_end(&mut payload)?;
+        if payload.len() as u64 != header.payload_len {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+
+        let serialized = match header.schema_version {
+            1 => decode_v1(&payload)?,
+            // When the payload changes, bump `SCHEMA_VERSION`, keep the decoder of the old
+            // version and convert its result to the current `SerializedPageAnalysis` here.
+            // Also check in a record of the new version next to `tests/fixtures/binary/v1.bin`.
+            version => return Err(BinaryFormatError::UnsupportedVersion(version)),
+        };
+        serialized
+            .into_analysis()
+            .map_err(BinaryFormatError::Malformed)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Encoding (current schema version)
+// ---------------------------------------------------------------------------
+
+fn encode(out: &mut Vec<u8>, analysis: &SerializedPageAnalysis) {
+    write_len(out, analysis.source_strings.len());
+    for string in &analysis.source_strings {
+        write_str(out, string);
+    }
+    write_len(out, analysis.revision_immutables.len());
+    for rev in &analysis.revision_immutables {
+        write_i32(out, rev.id);
+        write_usize(out, rev.length_lowercase);
+        write_substr(out, &rev.text_lowercase);
+    }
+    for substrings in [
+        &analysis.paragraph_immutables,
+        &analysis.sentence_immutables,
+        &analysis.word_immutables,
+    ] {
+        write_len(out, substrings.len());
+        for substr in substrings {
+            write_substr(out, substr);
+        }
+    }
+
+    write_len(out, analysis.revisions.len());
+    for rev in &analysis.revisions {
+        write_indices(out, &rev.paragraphs_ordered);
+        write_usize(out, rev.original_adds);
+    }
+    write_len(out, analysis.paragraphs.len());
+    for paragraph in &analysis.paragraphs {
+        write_indices(out, &paragraph.sentences_ordered);
+    }
+    write_len(out, analysis.sentences.len());
+    for sentence in &analysis.sentences {
+        write_indices(out, &sentence.words_ordered);
+    }
+    write_len(out, analysis.word_analyses.len());
+    for word in &analysis.word_analyses {
+        write_usize(out, word.origin_revision);
+        write_usize(out, word.latest_revision);
+        write_indices(out, &word.inbound);
+        write_indices(out, &word.outbound);
+    }
+
+    write_len(out, analysis.spam_ids.len());
+    for &id in &analysis.spam_ids {
+        write_i32(out, id);
+    }
+    // sorted, so that the same analysis always has the same encoding
+    let mut revisions_by_id: Vec<_> = analysis.revisions_by_id.iter().collect();
+    revisions_by_id.sort_unstable();
+    write_len(out, revisions_by_id.len());
+    for (&id, &index) in revisions_by_id {
+        write_i32(out, id);
+        write_usize(out, index);
+    }
+    write_indices(out, &analysis.ordered_revisions);
+    write_indices(out, &analysis.words);
+    write_usize(out, analysis.current_revision);
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_usize(out: &mut Vec<u8>, value: usize) {
+    write_varint(out, value as u64);
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    write_usize(out, len);
+}
+
+/// Zigzag encoded, so that small negative values stay short.
+fn write_i32(out: &mut Vec<u8>, value: i32) {
+    write_varint(out, ((value << 1) ^ (value >> 31)) as u32 as u64);
+}
+
+fn write_str(out: &mut Vec<u8>, value: &str) {
+    write_len(out, value.len());
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn write_indices(out: &mut Vec<u8>, indices: &[usize]) {
+    write_len(out, indices.len());
+    for &index in indices {
+        write_usize(out, index);
+    }
+}
+
+/// Source index, start and length of the range.
+fn write_substr(out: &mut Vec<u8>, substr: &SerializedArcSubstring) {
+    write_usize(out, substr.source_index);
+    write_usize(out, substr.source_range.start);
+    write_usize(out, substr.source_range.len());
+}
+
+// ---------------------------------------------------------------------------
+// Decoding
+// ---------------------------------------------------------------------------
+
+fn malformed(message: &str) -> BinaryFormatError {
+    BinaryFormatError::Malformed(message.to_string())
+}
+
+fn decode_v1(payload: &[u8]) -> Result<SerializedPageAnalysis, BinaryFormatError> {
+    let mut d = Decoder { data: payload };
+
+    let source_strings = d.list(|d| d.string().map(Arc::new))?;
+    let revision_immutables = d.list(|d| {
+        Ok(SerializedRevisionImmutables {
+            id: d.i32()?,
+            length_lowercase: d.usize()?,
+            text_lowercase: d.substr()?,
+        })
+    })?;
+    let paragraph_immutables = d.list(Decoder::substr)?;
+    let sentence_immutables = d.list(Decoder::substr)?;
+    let word_immutables = d.list(Decoder::substr)?;
+
+    let revisions = d.list(|d| {
+        Ok(SerializedRevisionAnalysis {
+            paragraphs_ordered: d.indices()?,
+            original_adds: d.usize()?,
+        })
+    })?;
+    let paragraphs = d.list(|d| {
+        Ok(SerializedParagraphAnalysis {
+            sentences_ordered: d.indices()?,
+        })
+    })?;
+    let sentences = d.list(|d| {
+        Ok(SerializedSentenceAnalysis {
+            words_ordered: d.indices()?,
+        })
+    })?;
+    let word_analyses = d.list(|d| {
+        Ok(SerializedWordAnalysis {
+            origin_revision: d.usize()?,
+            latest_revision: d.usize()?,
+            inbound: d.indices()?,
+            outbound: d.indices()?,
+        })
+    })?;
+
+    let spam_ids = d.list(Decoder::i32)?;
+    let revisions_by_id = d
+        .list(|d| Ok((d.i32()?, d.usize()?)))?
+        .into_iter()
+        .collect();
+    let ordered_revisions = d.indices()?;
+    let words = d.indices()?;
+    let current_revision = d.usize()?;
+
+    if !d.data.is_empty() {
+        return Err(malformed("trailing bytes after the analysis"));
+    }
+    Ok(SerializedPageAnalysis {
+        source_strings,
+        revision_immutables,
+        paragraph_immutables,
+        sentence_immutables,
+        word_immutables,
+        revisions,
+        paragraphs,
+        sentences,
+        word_analyses,
+        spam_ids,
+        revisions_by_id,
+        ordered_revisions,
+        words,
+        current_revision,
+    })
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], BinaryFormatError> {
+        if len > self.data.len() {
+            return Err(malformed("unexpected end of payload"));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> Result<u64, BinaryFormatError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.bytes(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(malformed("varint too long"))
+    }
+
+    fn usize(&mut self) -> Result<usize, BinaryFormatError> {
+        usize::try_from(self.varint()?).map_err(|_| malformed("integer too large"))
+    }
+
+    fn i32(&mut self) -> Result<i32, BinaryFormatError> {
+        let zigzag = u32::try_from(self.varint()?).map_err(|_| malformed("integer too large"))?;
+        Ok((zigzag >> 1) as i32 ^ -((zigzag & 1) as i32))
+    }
+
+    fn string(&mut self) -> Result<String, BinaryFormatError> {
+        let len = self.usize()?;
+        let bytes = self.bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| malformed("string is not UTF-8"))
+    }
+
+    fn substr(&mut self) -> Result<SerializedArcSubstring, BinaryFormatError> {
+        let source_index = self.usize()?;
+        let start = self.usize()?;
+        let len = self.usize()?;
+        let end = start
+            .checked_add(len)
+            .ok_or_else(|| malformed("substring range overflows"))?;
+        Ok(SerializedArcSubstring {
+            source_index,
+            source_range: Range { start, end },
+        })
+    }
+
+    fn list<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, BinaryFormatError>,
+    ) -> Result<Vec<T>, BinaryFormatError> {
+        let len = self.usize()?;
+        // every item takes at least one byte, don't trust larger lengths for the allocation
+        let mut items = Vec::with_capacity(len.min(self.data.len()));
+        for _ in 0..len {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    fn indices(&mut self) -> Result<Vec<usize>, BinaryFormatError> {
+        self.list(Decoder::usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dump_parser::{Contributor, Revision, Text};
+    use crate::utils::iterate_revision_tokens;
+
+    fn make_revision(id: i32, text: &str) -> Revision {
+        Revision {
+            id,
+            timestamp: chrono::DateTime::from_timestamp_nanos(1_700_000_000_000_000_000),
+            contributor: Contributor {
+                id: Some(id),
+                username: format!("User{id}").into(),
+                deleted: false,
+            },
+            text: Text::Normal(text.to_string()),
+            sha1: None,
+            comment: None,
+            minor: false,
+            model: None,
+            format: None,
+            slots: Vec::new(),
+        }
+    }
+
+    fn analysis() -> PageAnalysis {
+        let revisions = vec![
+            make_revision(1, "Hello world. This is a test."),
+            make_revision(
+                2,
+                "Hello world. This is a modified test. New sentence added.",
+            ),
+            make_revision(3, "Hello world. New sentence added."),
+            make_revision(4, "Hello world. This is a test. New sentence added."),
+        ];
+        PageAnalysis::analyse_page(&revisions).unwrap()
+    }
+
+    /// The tokens of the current revision with origin, inbound and outbound revision ids.
+    fn tokens(analysis: &PageAnalysis) -> Vec<(String, i32, Vec<i32>, Vec<i32>)> {
+        iterate_revision_tokens(analysis, &analysis.current_revision)
+            .map(|word| {
+                let word_analysis = &analysis[word];
+                (
+                    word.value.to_string(),
+                    word_analysis.origin_revision.id,
+                    word_analysis.inbound.iter().map(|r| r.id).collect(),
+                    word_analysis.outbound.iter().map(|r| r.id).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn encoded(analysis: &PageAnalysis) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        analysis.write_binary(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let analysis = analysis();
+        let bytes = encoded(&analysis);
+        let restored = PageAnalysis::read_binary(bytes.as_slice()).unwrap();
+
+        assert_eq!(tokens(&restored), tokens(&analysis));
+        assert_eq!(restored.words.len(), analysis.words.len());
+        assert_eq!(restored.spam_ids, analysis.spam_ids);
+        assert_eq!(restored.current_revision.id, analysis.current_revision.id);
+        let ids = |a: &PageAnalysis| a.ordered_revisions.iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(&restored), ids(&analysis));
+        assert_eq!(
+            restored.revisions_by_id.len(),
+            analysis.revisions_by_id.len()
+        );
+        // deterministic encoding
+        assert_eq!(encoded(&restored), bytes);
+    }
+
+    /// A record written by the crate at schema version 1, decoded by every later version.
+    const V1_RECORD: &[u8] = include_bytes!("../../tests/fixtures/binary/v1.bin");
+
+    #[test]
+    fn test_read_v1_record() {
+        let mut reader = V1_RECORD;
+        let header = BinaryHeader::read(&mut reader).unwrap();
+        assert_eq!(header.schema_version, 1);
+        assert_eq!(header.payload_len, reader.len() as u64);
+
+        let restored = PageAnalysis::read_binary(V1_RECORD).unwrap();
+        assert_eq!(restored.current_revision.id, 4);
+        let expected = [
+            ("hello", 1, &[][..], &[][..]),
+            ("world", 1, &[], &[]),
+            (".", 1, &[], &[]),
+            ("this", 1, &[4], &[3]),
+            ("is", 1, &[4], &[3]),
+            ("a", 1, &[4], &[3]),
+            ("test", 1, &[4], &[3]),
+            (".", 1, &[4], &[3]),
+            ("new", 2, &[], &[]),
+            ("sentence", 2, &[], &[]),
+            ("added", 2, &[], &[]),
+            (".", 2, &[], &[]),
+        ]
+        .map(|(value, origin, inbound, outbound)| {
+            (
+                value.to_string(),
+                origin,
+                inbound.to_vec(),
+                outbound.to_vec(),
+            )
+        });
+        assert_eq!(tokens(&restored), expected);
+    }
+
+    /// The payload written at the current schema version must not change without bumping
+    /// [`SCHEMA_VERSION`].
+    #[test]
+    fn test_v1_payload_unchanged() {
+        if SCHEMA_VERSION != 1 {
+            return;
+        }
+        let mut fixture = V1_RECORD;
+        BinaryHeader::read(&mut fixture).unwrap();
+        let bytes = encoded(&analysis());
+        let mut current = bytes.as_slice();
+        BinaryHeader::read(&mut current).unwrap();
+        assert_eq!(current, fixture, "the payload changed, bump SCHEMA_VERSION");
+    }
+
+    #[test]
+    fn test_header() {
+        let bytes = encoded(&analysis());
+        let mut reader = bytes.as_slice();
+        let header = BinaryHeader::read(&mut reader).unwrap();
+        assert_eq!(header.schema_version, SCHEMA_VERSION);
+        assert_eq!(header.crate_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(header.payload_len, reader.len() as u64);
+    }
+
+    #[test]
+    fn test_records_back_to_back() {
+        let analysis = analysis();
+        let mut bytes = encoded(&analysis);
+        bytes.extend(encoded(&analysis));
+
+        let mut reader = bytes.as_slice();
+        for _ in 0..2 {
+            let restored = PageAnalysis::read_binary(&mut reader).unwrap();
+            assert_eq!(tokens(&restored), tokens(&analysis));
+        }
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_error_invalid_magic() {
+        let mut bytes = encoded(&analysis());
+        bytes[0] = b'X';
+        assert!(matches!(
+            PageAnalysis::read_binary(bytes.as_slice()),
+            Err(BinaryFormatError::InvalidMagic)
+        ));
+    }
+
+    #[test]
+    fn test_error_unsupported_version() {
+        let mut bytes = encoded(&analysis());
+        bytes[4..6].copy_from_slice(&(SCHEMA_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            PageAnalysis::read_binary(bytes.as_slice()),
+            Err(BinaryFormatError::UnsupportedVersion(v)) if v == SCHEMA_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn test_error_truncated() {
+        let bytes = encoded(&analysis());
+        assert!(matches!(
+            PageAnalysis::read_binary(&bytes[..bytes.len() - 1]),
+            Err(BinaryFormatError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof
+        ));
+    }
+
+    #[test]
+    fn test_error_malformed_payload() {
+        let analysis = analysis();
+        let mut payload = Vec::new();
+        let mut serialized = SerializedPageAnalysis::from_analysis(&analysis);
+        serialized.current_revision = 999;
+        encode(&mut payload, &serialized);
+        let header = BinaryHeader {
+            schema_version: SCHEMA_VERSION,
+            crate_version: String::new(),
+            payload_len: payload.len() as u64,
+        };
+        let mut bytes = Vec::new();
+        header.write(&mut bytes).unwrap();
+        bytes.extend(payload);
+
+        match PageAnalysis::read_binary(bytes.as_slice()) {
+            Err(BinaryFormatError::Malformed(message)) => {
+                assert!(message.contains("revision index"), "{message}")
+            }
+            other => panic!("expected a malformed payload error, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_varints() {
+        for value in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut out = Vec::new();
+            write_varint(&mut out, value);
+            assert_eq!(Decoder { data: &out }.varint().unwrap(), value);
+        }
+        for value in [0, 1, -1, 42, i32::MIN, i32::MAX] {
+            let mut out = Vec::new();
+            write_i32(&mut out, value);
+            assert_eq!(Decoder { data: &out }.i32().unwrap(), value);
+        }
+    }
+}
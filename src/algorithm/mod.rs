@@ -7,17 +7,20 @@ use std::{
 
 pub use types::*;
 
+pub mod binary;
 #[cfg(feature = "serde")]
 mod serde_impl;
+mod serialized;
 
+use compact_str::CompactString;
 use imara_diff::Interner;
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::{
-    dump_parser::{Revision, Text},
+    dump_parser::{Revision, Sha1Hash, Text, MAIN_SLOT},
     utils::{
         self, compute_avg_word_freq, split_into_paragraphs, split_into_sentences,
-        split_into_tokens, trim_in_place, ChangeTag, RevisionHash,
+        split_into_tokens, trim_in_place, ChangeTag,
     },
 };
 
@@ -50,7 +53,7 @@ pub(crate) struct PageAnalysisInternals {
 
     paragraphs_ht: FxHashMap<blake3::Hash, Vec<ParagraphPointer>>, // Hash table of paragraphs of all revisions
     sentences_ht: FxHashMap<blake3::Hash, Vec<SentencePointer>>, // Hash table of sentences of all revisions
-    spam_hashes: FxHashSet<RevisionHash>, // Hashes of spam revisions; RevisionHash can be a SHA1 hash or a BLAKE3 hash but we expect all hashes in this revision to be of the same type
+    spam_hashes: FxHashSet<Sha1Hash>,                            // Hashes of spam revisions
 
     revision_prev: Option<RevisionPointer>,
     // text_curr: String, /* pass text_curr as parameter instead */
@@ -335,9 +338,13 @@ impl ParasentPointer for SentencePointer {
     }
 }
 
-#[derive(Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct PageAnalysisOptions {
+    /// Role of the content slot to analyse, [`MAIN_SLOT`] by default.
+    ///
+    /// Revisions without this slot are skipped like revisions with deleted text.
+    pub slot: CompactString,
     /// Use optimized lowercasing algorithm that is faster than default for inputs with <= 90% ASCII content.
     #[cfg(feature = "optimized-lowercase")]
     pub optimize_non_ascii: bool,
@@ -353,6 +360,7 @@ pub struct PageAnalysisOptions {
 impl PageAnalysisOptions {
     pub const fn new() -> Self {
         Self {
+            slot: CompactString::const_new(MAIN_SLOT),
             #[cfg(feature = "optimized-lowercase")]
             optimize_non_ascii: false,
             #[cfg(feature = "python-diff")]
@@ -360,6 +368,13 @@ impl PageAnalysisOptions {
         }
     }
 
+    /// Analyses the content slot with the given role instead of the main slot,
+    /// e.g. `mediainfo` for the structured data of files on Wikimedia Commons.
+    pub fn slot(mut self, role: impl Into<CompactString>) -> Self {
+        self.slot = role.into();
+        self
+    }
+
     #[cfg(feature = "optimized-lowercase")]
     pub const fn optimize_non_ascii(mut self) -> Self {
         self.optimize_non_ascii = true;
@@ -373,6 +388,27 @@ impl PageAnalysisOptions {
     }
 }
 
+/// Returns the text and hash of the slot with the given role.
+fn revision_slot<'a>(revision: &'a Revision, role: &str) -> Option<(&'a Text, Option<Sha1Hash>)> {
+    if role == MAIN_SLOT {
+        // the hash of a revision with several slots combines the hashes of all slots
+        let sha1 = revision.sha1.filter(|_| revision.slots.is_empty());
+        Some((&revision.text, sha1))
+    } else {
+        revision
+            .slots
+            .iter()
+            .find(|slot| slot.role == role)
+            .map(|slot| (&slot.text, slot.sha1))
+    }
+}
+
+impl Default for PageAnalysisOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PageAnalysis {
     /// Runs the WikiWho authorship analysis on an ordered sequence of revisions.
     ///
@@ -382,11 +418,17 @@ impl PageAnalysis {
     ///
     /// `xml_revisions` must be in chronological order (oldest first), as returned
     /// by [`DumpParser::parse_page`](crate::dump_parser::DumpParser::parse_page).
+    /// [`Page::normalize_revisions`](crate::dump_parser::Page::normalize_revisions) sorts
+    /// and deduplicates revisions from other sources.
     ///
     /// # Errors
     ///
     /// Returns [`AnalysisError::NoValidRevisions`] if every revision in the input
     /// is classified as spam or has empty/deleted text.
+    ///
+    /// Returns [`AnalysisError::RevisionsOutOfOrder`] if a revision is older than the
+    /// preceding one by both timestamp and id, and [`AnalysisError::DuplicateRevisionId`]
+    /// if a revision id appears twice.
     pub fn analyse_page<I, R>(xml_revisions: I) -> Result<Self, AnalysisError>
     where
         R: Borrow<Revision>,
@@ -407,32 +449,47 @@ impl PageAnalysis {
         // which is not ideal but simplifies the implementation and data model significantly.
         let initial_revision = (RevisionAnalysis::default(), RevisionImmutables::dummy()); /* will be overwritten before being read */
         let mut analysis = PageAnalysis::new(initial_revision);
-        analysis.internals.options = analysis_options;
+        analysis.internals.options = analysis_options.clone();
 
         let mut at_least_one = false;
+        let mut previous_revision = None;
+        let mut revision_ids = FxHashSet::default();
 
         // Iterate over revisions of the article.
         // Analysis begins at the oldest revision and progresses to the newest.
         for xml_revision_source in xml_revisions {
             let xml_revision = xml_revision_source.borrow();
 
-            // Extract text of the revision
-            let text = match xml_revision.text {
-                Text::Normal(ref t) => t,
-                Text::Deleted => {
-                    // Skip revisions with deleted text
-                    continue;
+            // Reject input that is out of order by both timestamp and id. Dumps are ordered by id,
+            // in which timestamps can go backwards (e.g. after history merges), the API orders by
+            // timestamp, so either order alone is accepted.
+            if !revision_ids.insert(xml_revision.id) {
+                return Err(AnalysisError::DuplicateRevisionId(xml_revision.id));
+            }
+            if let Some((previous_timestamp, previous_id)) = previous_revision {
+                if xml_revision.timestamp < previous_timestamp && xml_revision.id < previous_id {
+                    return Err(AnalysisError::RevisionsOutOfOrder {
+                        revision_id: xml_revision.id,
+                        previous_revision_id: previous_id,
+                    });
                 }
-            };
+            }
+            previous_revision = Some((xml_revision.timestamp, xml_revision.id));
 
-            // Use pre-calculated SHA1 hash if available, otherwise calculate BLAKE3 hash
-            let rev_hash = match xml_revision.sha1 {
-                Some(sha1_hash) => RevisionHash::Sha1(sha1_hash),
-                None => RevisionHash::Blake3(blake3::hash(text.as_bytes())),
+            // Extract text of the revision
+            // Skip revisions with deleted or missing text or without the analysed slot
+            let Some((text, sha1)) = revision_slot(xml_revision, &analysis_options.slot)
+                .and_then(|(text, sha1)| Some((text.content()?, sha1)))
+            else {
+                continue;
             };
 
+            // Use the SHA1 hash from the dump if available, otherwise calculate it the same way
+            // so that revisions with and without hash in the dump are comparable
+            let rev_hash = sha1.unwrap_or_else(|| Sha1Hash::compute(text));
+
             let revision_data =
-                RevisionImmutables::from_revision_with_options(xml_revision, analysis_options);
+                RevisionImmutables::from_revision_with_options(xml_revision, &analysis_options);
             let mut vandalism = false;
 
             if analysis.internals.spam_hashes.contains(&rev_hash) {
@@ -442,7 +499,12 @@ impl PageAnalysis {
 
             // Spam detection: Deletion
             // On initial revision this resolves to a no-op, since length_lowercase is 0
-            if !(vandalism || xml_revision.comment.is_some() && xml_revision.minor) {
+            // A deleted comment counts as no comment (mwxml reports it as `None`)
+            let has_comment = xml_revision
+                .comment
+                .as_ref()
+                .is_some_and(|comment| comment.as_visible().is_some());
+            if !(vandalism || has_comment && xml_revision.minor) {
                 let revision_prev = &analysis.current_revision; /* !! since we have not yet updated current_revision, this is the previous revision */
                 let change_percentage = (revision_data.length_lowercase as f64
                     - revision_prev.length_lowercase as f64)
@@ -1,437 +1,40 @@
 // SPDX-License-Identifier: MPL-2.0
-use std::{collections::HashMap, ops::Range, sync::Arc};
-
-use rustc_hash::FxHashMap;
-
-use crate::algorithm::{ArcSubstring, ParagraphPointer};
-
-use super::{
-    PageAnalysis, PageAnalysisInternals, ParagraphAnalysis, ParagraphImmutables, RevisionAnalysis,
-    RevisionImmutables, RevisionPointer, SentenceAnalysis, SentenceImmutables, SentencePointer,
-    WordAnalysis, WordImmutables, WordPointer,
-};
-
-// ---------------------------------------------------------------------------
-// Intermediate serialized types — not exported, only used for serde conversion
-// ---------------------------------------------------------------------------
-
-#[derive(serde::Serialize, serde::Deserialize)]
-struct SerializedArcSubstring {
-    source_index: usize,
-    source_range: Range<usize>,
-}
-
-#[derive(serde::Serialize, serde::Deserialize)]
-struct SerializedRevisionImmutables {
-    id: i32,
-    length_lowercase: usize,
-    text_lowercase: SerializedArcSubstring,
-}
-
-#[derive(serde::Serialize, serde::Deserialize)]
-struct SerializedWordAnalysis {
-    origin_revision: usize,
-    latest_revision: usize,
-    inbound: Vec<usize>,
-    outbound: Vec<usize>,
-}
-
-#[derive(serde::Serialize, serde::Deserialize)]
-struct SerializedSentenceAnalysis {
-    words_ordered: Vec<usize>,
-}
-
-#[derive(serde::Serialize, serde::Deserialize)]
-struct SerializedParagraphAnalysis {
-    sentences_ordered: Vec<usize>,
-}
-
-#[derive(serde::Serialize, serde::Deserialize)]
-struct SerializedRevisionAnalysis {
-    paragraphs_ordered: Vec<usize>,
-    original_adds: usize,
-}
-
-#[derive(serde::Serialize, serde::Deserialize)]
-struct SerializedPageAnalysis {
-    #[serde(with = "vec_arc_string")]
-    source_strings: Vec<Arc<String>>,
-    // Immutables arrays first — serialised as plain values, not Arc wrappers
-    revision_immutables: Vec<SerializedRevisionImmutables>,
-    paragraph_immutables: Vec<SerializedArcSubstring>,
-    sentence_immutables: Vec<SerializedArcSubstring>,
-    word_immutables: Vec<SerializedArcSubstring>,
-    // Analysis arrays with usize pointer indices
-    revisions: Vec<SerializedRevisionAnalysis>,
-    paragraphs: Vec<SerializedParagraphAnalysis>,
-    sentences: Vec<SerializedSentenceAnalysis>,
-    word_analyses: Vec<SerializedWordAnalysis>,
-    // Public pointer fields as indices
-    spam_ids: Vec<i32>,
-    revisions_by_id: HashMap<i32, usize>,
-    ordered_revisions: Vec<usize>,
-    words: Vec<usize>,
-    current_revision: usize,
-}
-
-mod vec_arc_string {
-    use std::sync::Arc;
-
-    pub fn serialize<S: serde::Serializer>(
-        // this is a special case since we are matching the `source_strings` field type
-        #[allow(clippy::ptr_arg)] obj: &Vec<Arc<String>>,
-        serializer: S,
-    ) -> Result<S::Ok, S::Error> {
-        serializer.collect_seq(obj.iter().map(|s| s.as_str()))
-    }
-
-    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
-        deserializer: D,
-    ) -> Result<Vec<Arc<String>>, D::Error> {
-        let strings: Vec<String> = serde::Deserialize::deserialize(deserializer)?;
-        Ok(strings.into_iter().map(Arc::new).collect())
-    }
-}
-
-// ---------------------------------------------------------------------------
-// Serialize
-// ---------------------------------------------------------------------------
+use super::{serialized::SerializedPageAnalysis, PageAnalysis};
 
 impl serde::Serialize for PageAnalysis {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let mut source_strings = Vec::new();
-
-        let mut arc_string_lookup = HashMap::new();
-        let mut arc_index = |base_arc: &Arc<String>| {
-            *arc_string_lookup
-                .entry(Arc::as_ptr(base_arc))
-                .or_insert_with(|| {
-                    let index = source_strings.len();
-                    source_strings.push(base_arc.clone());
-                    index
-                })
-        };
-        let mut serialize_arc_substr = |arc_substr: &ArcSubstring| {
-            let index = arc_index(arc_substr.base_string());
-
-            let base_bytestr = arc_substr.base_string().as_bytes();
-            let substr_bytestr = arc_substr.as_str().as_bytes();
-
-            let (substr_start, substr_end) = if substr_bytestr.is_empty() {
-                (0, 0)
-            } else {
-                let start = base_bytestr.element_offset(&substr_bytestr[0]).expect(
-                    "ArcSubstring::as_str to be a reference inside ArcSubstring::base_string",
-                );
-                (start, start + substr_bytestr.len())
-            };
-
-            SerializedArcSubstring {
-                source_index: index,
-                source_range: substr_start..substr_end,
-            }
-        };
-
-        let revision_immutables = self
-            .revision_immutables
-            .iter()
-            .map(|rev| SerializedRevisionImmutables {
-                id: rev.id,
-                length_lowercase: rev.length_lowercase,
-                text_lowercase: serialize_arc_substr(&rev.text_lowercase),
-            })
-            .collect();
-        let paragraph_immutables = self
-            .paragraph_immutables
-            .iter()
-            .map(|e| serialize_arc_substr(&e.value))
-            .collect();
-        let sentence_immutables = self
-            .sentence_immutables
-            .iter()
-            .map(|e| serialize_arc_substr(&e.value))
-            .collect();
-        let word_immutables = self
-            .word_immutables
-            .iter()
-            .map(|e| serialize_arc_substr(&e.value))
-            .collect();
-
-        let serialized = SerializedPageAnalysis {
-            source_strings,
-            revision_immutables,
-            paragraph_immutables,
-            sentence_immutables,
-            word_immutables,
-            revisions: self
-                .revisions
-                .iter()
-                .map(|r| SerializedRevisionAnalysis {
-                    paragraphs_ordered: r.paragraphs_ordered.iter().map(|p| p.0).collect(),
-                    original_adds: r.original_adds,
-                })
-                .collect(),
-            paragraphs: self
-                .paragraphs
-                .iter()
-                .map(|p| SerializedParagraphAnalysis {
-                    sentences_ordered: p.sentences_ordered.iter().map(|s| s.0).collect(),
-                })
-                .collect(),
-            sentences: self
-                .sentences
-                .iter()
-                .map(|s| SerializedSentenceAnalysis {
-                    words_ordered: s.words_ordered.iter().map(|w| w.0).collect(),
-                })
-                .collect(),
-            word_analyses: self
-                .word_analyses
-                .iter()
-                .map(|w| SerializedWordAnalysis {
-                    origin_revision: w.origin_revision.0,
-                    latest_revision: w.latest_revision.0,
-                    inbound: w.inbound.iter().map(|r| r.0).collect(),
-                    outbound: w.outbound.iter().map(|r| r.0).collect(),
-                })
-                .collect(),
-            spam_ids: self.spam_ids.clone(),
-            revisions_by_id: self
-                .revisions_by_id
-                .iter()
-                .map(|(&id, ptr)| (id, ptr.0))
-                .collect(),
-            ordered_revisions: self.ordered_revisions.iter().map(|r| r.0).collect(),
-            words: self.words.iter().map(|w| w.0).collect(),
-            current_revision: self.current_revision.0,
-        };
-        serialized.serialize(serializer)
+        SerializedPageAnalysis::from_analysis(self).serialize(serializer)
     }
 }
 
-// ---------------------------------------------------------------------------
-// Deserialize
-// ---------------------------------------------------------------------------
-
 impl<'de> serde::Deserialize<'de> for PageAnalysis {
     fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let s = SerializedPageAnalysis::deserialize(deserializer)?;
-
-        let source_strings = s.source_strings;
-        let deserialize_substr = |serial_substr: SerializedArcSubstring| {
-            if let Some(source_string) = source_strings.get(serial_substr.source_index) {
-                if let Some(substr) = source_string.get(serial_substr.source_range) {
-                    Ok(ArcSubstring::new_substr(source_string.clone(), substr))
-                } else {
-                    Err(serde::de::Error::custom(format!(
-                        "substring range {} out of bounds (source len={}) or not on character boundary",
-                        serial_substr.source_index,
-                        source_strings.len()
-                    )))
-                }
-            } else {
-                Err(serde::de::Error::custom(format!(
-                    "source string index {} out of bounds (len={})",
-                    serial_substr.source_index,
-                    source_strings.len()
-                )))
-            }
-        };
+        SerializedPageAnalysis::deserialize(deserializer)?
+            .into_analysis()
+            .map_err(serde::de::Error::custom)
+    }
+}
 
-        // Build Arc arrays from the deserialized plain values.
-        // ParagraphImmutables and SentenceImmutables have custom Deserialize impls
-        // that reconstruct blake3::Hash from `value`, so these Arcs are fully valid.
-        let revision_arcs: Vec<Arc<RevisionImmutables>> = s
-            .revision_immutables
-            .into_iter()
-            .map(|rev| {
-                Ok(Arc::new(RevisionImmutables {
-                    id: rev.id,
-                    length_lowercase: rev.length_lowercase,
-                    text_lowercase: deserialize_substr(rev.text_lowercase)?,
-                }))
-            })
-            .collect::<Result<Vec<_>, D::Error>>()?;
-        let paragraph_arcs: Vec<Arc<ParagraphImmutables>> = s
-            .paragraph_immutables
-            .into_iter()
-            .map(|s| Ok(Arc::new(ParagraphImmutables::new(deserialize_substr(s)?))))
-            .collect::<Result<Vec<_>, D::Error>>()?;
-        let sentence_arcs: Vec<Arc<SentenceImmutables>> = s
-            .sentence_immutables
-            .into_iter()
-            .map(|s| Ok(Arc::new(SentenceImmutables::new(deserialize_substr(s)?))))
-            .collect::<Result<Vec<_>, D::Error>>()?;
-        let word_arcs: Vec<Arc<WordImmutables>> = s
-            .word_immutables
-            .into_iter()
-            .map(|s| Ok(Arc::new(WordImmutables::new(deserialize_substr(s)?))))
-            .collect::<Result<Vec<_>, D::Error>>()?;
-
-        // Helper closures — validate index bounds and reconstruct pointers.
-        // All pointers for the same index share the single Arc heap allocation.
-        let rev_ptr = |idx: usize| -> Result<RevisionPointer, D::Error> {
-            revision_arcs
-                .get(idx)
-                .ok_or_else(|| {
-                    serde::de::Error::custom(format!(
-                        "revision index {idx} out of bounds (len={})",
-                        revision_arcs.len()
-                    ))
-                })
-                .map(|arc| RevisionPointer(idx, arc.clone()))
-        };
-        let par_ptr = |idx: usize| -> Result<ParagraphPointer, D::Error> {
-            paragraph_arcs
-                .get(idx)
-                .ok_or_else(|| {
-                    serde::de::Error::custom(format!(
-                        "paragraph index {idx} out of bounds (len={})",
-                        paragraph_arcs.len()
-                    ))
-                })
-                .map(|arc| ParagraphPointer(idx, arc.clone()))
-        };
-        let sent_ptr = |idx: usize| -> Result<SentencePointer, D::Error> {
-            sentence_arcs
-                .get(idx)
-                .ok_or_else(|| {
-                    serde::de::Error::custom(format!(
-                        "sentence index {idx} out of bounds (len={})",
-                        sentence_arcs.len()
-                    ))
-                })
-                .map(|arc| SentencePointer(idx, arc.clone()))
-        };
-        let word_ptr = |idx: usize| -> Result<WordPointer, D::Error> {
-            word_arcs
-                .get(idx)
-                .ok_or_else(|| {
-                    serde::de::Error::custom(format!(
-                        "word index {idx} out of bounds (len={})",
-                        word_arcs.len()
-                    ))
-                })
-                .map(|arc| WordPointer(idx, arc.clone()))
-        };
+/// The schema of the serialized form, for the `raw` output schema.
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for PageAnalysis {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "PageAnalysis".into()
+    }
 
-        // Reconstruct revisions; transient fields are zeroed.
-        let revisions = s
-            .revisions
-            .into_iter()
-            .map(|r| {
-                let paragraphs_ordered = r
-                    .paragraphs_ordered
-                    .into_iter()
-                    .map(&par_ptr)
-                    .collect::<Result<Vec<_>, _>>()?;
-                Ok(RevisionAnalysis {
-                    paragraphs_by_hash: FxHashMap::default(),
-                    paragraphs_ordered,
-                    original_adds: r.original_adds,
-                })
-            })
-            .collect::<Result<Vec<_>, D::Error>>()?;
-
-        // Reconstruct paragraphs; transient fields are zeroed.
-        let paragraphs = s
-            .paragraphs
-            .into_iter()
-            .map(|p| {
-                let sentences_ordered = p
-                    .sentences_ordered
-                    .into_iter()
-                    .map(&sent_ptr)
-                    .collect::<Result<Vec<_>, _>>()?;
-                Ok(ParagraphAnalysis {
-                    sentences_by_hash: FxHashMap::default(),
-                    sentences_ordered,
-                    matched_in_current: false,
-                })
-            })
-            .collect::<Result<Vec<_>, D::Error>>()?;
-
-        // Reconstruct sentences.
-        let sentences = s
-            .sentences
-            .into_iter()
-            .map(|s| {
-                let words_ordered = s
-                    .words_ordered
-                    .into_iter()
-                    .map(&word_ptr)
-                    .collect::<Result<Vec<_>, _>>()?;
-                Ok(SentenceAnalysis {
-                    words_ordered,
-                    matched_in_current: false,
-                })
-            })
-            .collect::<Result<Vec<_>, D::Error>>()?;
-
-        // Reconstruct words.
-        let word_analyses = s
-            .word_analyses
-            .into_iter()
-            .map(|w| {
-                Ok(WordAnalysis {
-                    origin_revision: rev_ptr(w.origin_revision)?,
-                    latest_revision: rev_ptr(w.latest_revision)?,
-                    matched_in_current: false,
-                    inbound: w
-                        .inbound
-                        .into_iter()
-                        .map(&rev_ptr)
-                        .collect::<Result<Vec<_>, _>>()?,
-                    outbound: w
-                        .outbound
-                        .into_iter()
-                        .map(&rev_ptr)
-                        .collect::<Result<Vec<_>, _>>()?,
-                })
-            })
-            .collect::<Result<Vec<_>, D::Error>>()?;
-
-        // Reconstruct public pointer fields.
-        let revisions_by_id = s
-            .revisions_by_id
-            .into_iter()
-            .map(|(id, idx)| rev_ptr(idx).map(|ptr| (id, ptr)))
-            .collect::<Result<HashMap<_, _>, _>>()?;
-        let ordered_revisions = s
-            .ordered_revisions
-            .into_iter()
-            .map(&rev_ptr)
-            .collect::<Result<Vec<_>, _>>()?;
-        let words = s
-            .words
-            .into_iter()
-            .map(word_ptr)
-            .collect::<Result<Vec<_>, _>>()?;
-        let current_revision = rev_ptr(s.current_revision)?;
-
-        Ok(PageAnalysis {
-            revisions,
-            revision_immutables: revision_arcs,
-            paragraphs,
-            paragraph_immutables: paragraph_arcs,
-            sentences,
-            sentence_immutables: sentence_arcs,
-            word_analyses,
-            word_immutables: word_arcs,
-            spam_ids: s.spam_ids,
-            revisions_by_id,
-            ordered_revisions,
-            words,
-            current_revision,
-            internals: PageAnalysisInternals::default(),
-        })
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        SerializedPageAnalysis::json_schema(generator)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::dump_parser::{Contributor, Revision, Text};
+    use crate::algorithm::{
+        ArcSubstring, ParagraphImmutables, RevisionAnalysis, RevisionImmutables,
+        SentenceImmutables, WordAnalysis, WordImmutables,
+    };
+    use crate::dump_parser::{Comment, Contributor, Revision, Text};
     use compact_str::CompactString;
     use std::sync::Arc;
 
@@ -452,11 +55,15 @@ mod tests {
             contributor: Contributor {
                 id: Some(id),
                 username: CompactString::from(format!("User{id}")),
+                deleted: false,
             },
             text: Text::Normal(text.to_string()),
             sha1: None,
             comment: None,
             minor: false,
+            model: None,
+            format: None,
+            slots: Vec::new(),
         }
     }
 
@@ -766,11 +373,15 @@ mod tests {
             contributor: Contributor {
                 id: Some(7),
                 username: CompactString::from("SpecificUser"),
+                deleted: false,
             },
             text: Text::Normal("Test text content".to_string()),
             sha1: Some(Sha1Hash(*b"abcdefghijklmnopqrstuvwxyz12345")),
-            comment: Some(CompactString::from("my edit comment")),
+            comment: Some(Comment::Normal(CompactString::from("my edit comment"))),
             minor: true,
+            model: None,
+            format: None,
+            slots: Vec::new(),
         };
 
         let mut pa = PageAnalysis::new((RevisionAnalysis::default(), RevisionImmutables::dummy()));
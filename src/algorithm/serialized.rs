@@ -0,0 +1,433 @@
+// SPDX-License-Identifier: MPL-2.0
+use std::{collections::HashMap, ops::Range, sync::Arc};
+
+use rustc_hash::FxHashMap;
+
+use crate::algorithm::{ArcSubstring, ParagraphPointer};
+
+use super::{
+    PageAnalysis, PageAnalysisInternals, ParagraphAnalysis, ParagraphImmutables, RevisionAnalysis,
+    RevisionImmutables, RevisionPointer, SentenceAnalysis, SentenceImmutables, SentencePointer,
+    WordAnalysis, WordImmutables, WordPointer,
+};
+
+// ---------------------------------------------------------------------------
+// Intermediate serialized types — not exported, shared by the serde impl and the
+// binary format
+// ---------------------------------------------------------------------------
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub(super) struct SerializedArcSubstring {
+    pub(super) source_index: usize,
+    pub(super) source_range: Range<usize>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub(super) struct SerializedRevisionImmutables {
+    pub(super) id: i32,
+    pub(super) length_lowercase: usize,
+    pub(super) text_lowercase: SerializedArcSubstring,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub(super) struct SerializedWordAnalysis {
+    pub(super) origin_revision: usize,
+    pub(super) latest_revision: usize,
+    pub(super) inbound: Vec<usize>,
+    pub(super) outbound: Vec<usize>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub(super) struct SerializedSentenceAnalysis {
+    pub(super) words_ordered: Vec<usize>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub(super) struct SerializedParagraphAnalysis {
+    pub(super) sentences_ordered: Vec<usize>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub(super) struct SerializedRevisionAnalysis {
+    pub(super) paragraphs_ordered: Vec<usize>,
+    pub(super) original_adds: usize,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub(super) struct SerializedPageAnalysis {
+    #[cfg_attr(feature = "serde", serde(with = "vec_arc_string"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<String>"))]
+    pub(super) source_strings: Vec<Arc<String>>,
+    // Immutables arrays first — serialised as plain values, not Arc wrappers
+    pub(super) revision_immutables: Vec<SerializedRevisionImmutables>,
+    pub(super) paragraph_immutables: Vec<SerializedArcSubstring>,
+    pub(super) sentence_immutables: Vec<SerializedArcSubstring>,
+    pub(super) word_immutables: Vec<SerializedArcSubstring>,
+    // Analysis arrays with usize pointer indices
+    pub(super) revisions: Vec<SerializedRevisionAnalysis>,
+    pub(super) paragraphs: Vec<SerializedParagraphAnalysis>,
+    pub(super) sentences: Vec<SerializedSentenceAnalysis>,
+    pub(super) word_analyses: Vec<SerializedWordAnalysis>,
+    // Public pointer fields as indices
+    pub(super) spam_ids: Vec<i32>,
+    pub(super) revisions_by_id: HashMap<i32, usize>,
+    pub(super) ordered_revisions: Vec<usize>,
+    pub(super) words: Vec<usize>,
+    pub(super) current_revision: usize,
+}
+
+#[cfg(feature = "serde")]
+mod vec_arc_string {
+    use std::sync::Arc;
+
+    pub fn serialize<S: serde::Serializer>(
+        // this is a special case since we are matching the `source_strings` field type
+        #[allow(clippy::ptr_arg)] obj: &Vec<Arc<String>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(obj.iter().map(|s| s.as_str()))
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Arc<String>>, D::Error> {
+        let strings: Vec<String> = serde::Deserialize::deserialize(deserializer)?;
+        Ok(strings.into_iter().map(Arc::new).collect())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PageAnalysis -> SerializedPageAnalysis
+// ---------------------------------------------------------------------------
+
+impl SerializedPageAnalysis {
+    pub(super) fn from_analysis(analysis: &PageAnalysis) -> Self {
+        let mut source_strings = Vec::new();
+
+        let mut arc_string_lookup = HashMap::new();
+        let mut arc_index = |base_arc: &Arc<String>| {
+            *arc_string_lookup
+                .entry(Arc::as_ptr(base_arc))
+                .or_insert_with(|| {
+                    let index = source_strings.len();
+                    source_strings.push(base_arc.clone());
+                    index
+                })
+        };
+        let mut serialize_arc_substr = |arc_substr: &ArcSubstring| {
+            let index = arc_index(arc_substr.base_string());
+
+            let base_bytestr = arc_substr.base_string().as_bytes();
+            let substr_bytestr = arc_substr.as_str().as_bytes();
+
+            let (substr_start, substr_end) = if substr_bytestr.is_empty() {
+                (0, 0)
+            } else {
+                let start = base_bytestr.element_offset(&substr_bytestr[0]).expect(
+                    "ArcSubstring::as_str to be a reference inside ArcSubstring::base_string",
+                );
+                (start, start + substr_bytestr.len())
+            };
+
+            SerializedArcSubstring {
+                source_index: index,
+                source_range: substr_start..substr_end,
+            }
+        };
+
+        let revision_immutables = analysis
+            .revision_immutables
+            .iter()
+            .map(|rev| SerializedRevisionImmutables {
+                id: rev.id,
+                length_lowercase: rev.length_lowercase,
+                text_lowercase: serialize_arc_substr(&rev.text_lowercase),
+            })
+            .collect();
+        let paragraph_immutables = analysis
+            .paragraph_immutables
+            .iter()
+            .map(|e| serialize_arc_substr(&e.value))
+            .collect();
+        let sentence_immutables = analysis
+            .sentence_immutables
+            .iter()
+            .map(|e| serialize_arc_substr(&e.value))
+            .collect();
+        let word_immutables = analysis
+            .word_immutables
+            .iter()
+            .map(|e| serialize_arc_substr(&e.value))
+            .collect();
+
+        SerializedPageAnalysis {
+            source_strings,
+            revision_immutables,
+            paragraph_immutables,
+            sentence_immutables,
+            word_immutables,
+            revisions: analysis
+                .revisions
+                .iter()
+                .map(|r| SerializedRevisionAnalysis {
+                    paragraphs_ordered: r.paragraphs_ordered.iter().map(|p| p.0).collect(),
+                    original_adds: r.original_adds,
+                })
+                .collect(),
+            paragraphs: analysis
+                .paragraphs
+                .iter()
+                .map(|p| SerializedParagraphAnalysis {
+                    sentences_ordered: p.sentences_ordered.iter().map(|s| s.0).collect(),
+                })
+                .collect(),
+            sentences: analysis
+                .sentences
+                .iter()
+                .map(|s| SerializedSentenceAnalysis {
+                    words_ordered: s.words_ordered.iter().map(|w| w.0).collect(),
+                })
+                .collect(),
+            word_analyses: analysis
+                .word_analyses
+                .iter()
+                .map(|w| SerializedWordAnalysis {
+                    origin_revision: w.origin_revision.0,
+                    latest_revision: w.latest_revision.0,
+                    inbound: w.inbound.iter().map(|r| r.0).collect(),
+                    outbound: w.outbound.iter().map(|r| r.0).collect(),
+                })
+                .collect(),
+            spam_ids: analysis.spam_ids.clone(),
+            revisions_by_id: analysis
+                .revisions_by_id
+                .iter()
+                .map(|(&id, ptr)| (id, ptr.0))
+                .collect(),
+            ordered_revisions: analysis.ordered_revisions.iter().map(|r| r.0).collect(),
+            words: analysis.words.iter().map(|w| w.0).collect(),
+            current_revision: analysis.current_revision.0,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SerializedPageAnalysis -> PageAnalysis
+// ---------------------------------------------------------------------------
+
+impl SerializedPageAnalysis {
+    /// Rebuilds the analysis, checking that all indices are in bounds.
+    pub(super) fn into_analysis(self) -> Result<PageAnalysis, String> {
+        let s = self;
+        let source_strings = s.source_strings;
+        let deserialize_substr = |serial_substr: SerializedArcSubstring| {
+            if let Some(source_string) = source_strings.get(serial_substr.source_index) {
+                if let Some(substr) = source_string.get(serial_substr.source_range) {
+                    Ok(ArcSubstring::new_substr(source_string.clone(), substr))
+                } else {
+                    Err(format!(
+                        "substring range {} out of bounds (source len={}) or not on character boundary",
+                        serial_substr.source_index,
+                        source_strings.len()
+                    ))
+                }
+            } else {
+                Err(format!(
+                    "source string index {} out of bounds (len={})",
+                    serial_substr.source_index,
+                    source_strings.len()
+                ))
+            }
+        };
+
+        // Build Arc arrays from the deserialized plain values.
+        // ParagraphImmutables and SentenceImmutables have custom Deserialize impls
+        // that reconstruct blake3::Hash from `value`, so these Arcs are fully valid.
+        let revision_arcs: Vec<Arc<RevisionImmutables>> = s
+            .revision_immutables
+            .into_iter()
+            .map(|rev| {
+                Ok(Arc::new(RevisionImmutables {
+                    id: rev.id,
+                    length_lowercase: rev.length_lowercase,
+                    text_lowercase: deserialize_substr(rev.text_lowercase)?,
+                }))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let paragraph_arcs: Vec<Arc<ParagraphImmutables>> = s
+            .paragraph_immutables
+            .into_iter()
+            .map(|s| Ok(Arc::new(ParagraphImmutables::new(deserialize_substr(s)?))))
+            .collect::<Result<Vec<_>, String>>()?;
+        let sentence_arcs: Vec<Arc<SentenceImmutables>> = s
+            .sentence_immutables
+            .into_iter()
+            .map(|s| Ok(Arc::new(SentenceImmutables::new(deserialize_substr(s)?))))
+            .collect::<Result<Vec<_>, String>>()?;
+        let word_arcs: Vec<Arc<WordImmutables>> = s
+            .word_immutables
+            .into_iter()
+            .map(|s| Ok(Arc::new(WordImmutables::new(deserialize_substr(s)?))))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        // Helper closures — validate index bounds and reconstruct pointers.
+        // All pointers for the same index share the single Arc heap allocation.
+        let rev_ptr = |idx: usize| -> Result<RevisionPointer, String> {
+            revision_arcs
+                .get(idx)
+                .ok_or_else(|| {
+                    format!(
+                        "revision index {idx} out of bounds (len={})",
+                        revision_arcs.len()
+                    )
+                })
+                .map(|arc| RevisionPointer(idx, arc.clone()))
+        };
+        let par_ptr = |idx: usize| -> Result<ParagraphPointer, String> {
+            paragraph_arcs
+                .get(idx)
+                .ok_or_else(|| {
+                    format!(
+                        "paragraph index {idx} out of bounds (len={})",
+                        paragraph_arcs.len()
+                    )
+                })
+                .map(|arc| ParagraphPointer(idx, arc.clone()))
+        };
+        let sent_ptr = |idx: usize| -> Result<SentencePointer, String> {
+            sentence_arcs
+                .get(idx)
+                .ok_or_else(|| {
+                    format!(
+                        "sentence index {idx} out of bounds (len={})",
+                        sentence_arcs.len()
+                    )
+                })
+                .map(|arc| SentencePointer(idx, arc.clone()))
+        };
+        let word_ptr = |idx: usize| -> Result<WordPointer, String> {
+            word_arcs
+                .get(idx)
+                .ok_or_else(|| format!("word index {idx} out of bounds (len={})", word_arcs.len()))
+                .map(|arc| WordPointer(idx, arc.clone()))
+        };
+
+        // Reconstruct revisions; transient fields are zeroed.
+        let revisions = s
+            .revisions
+            .into_iter()
+            .map(|r| {
+                let paragraphs_ordered = r
+                    .paragraphs_ordered
+                    .into_iter()
+                    .map(&par_ptr)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(RevisionAnalysis {
+                    paragraphs_by_hash: FxHashMap::default(),
+                    paragraphs_ordered,
+                    original_adds: r.original_adds,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        // Reconstruct paragraphs; transient fields are zeroed.
+        let paragraphs = s
+            .paragraphs
+            .into_iter()
+            .map(|p| {
+                let sentences_ordered = p
+                    .sentences_ordered
+                    .into_iter()
+                    .map(&sent_ptr)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(ParagraphAnalysis {
+                    sentences_by_hash: FxHashMap::default(),
+                    sentences_ordered,
+                    matched_in_current: false,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        // Reconstruct sentences.
+        let sentences = s
+            .sentences
+            .into_iter()
+            .map(|s| {
+                let words_ordered = s
+                    .words_ordered
+                    .into_iter()
+                    .map(&word_ptr)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(SentenceAnalysis {
+                    words_ordered,
+                    matched_in_current: false,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        // Reconstruct words.
+        let word_analyses = s
+            .word_analyses
+            .into_iter()
+            .map(|w| {
+                Ok(WordAnalysis {
+                    origin_revision: rev_ptr(w.origin_revision)?,
+                    latest_revision: rev_ptr(w.latest_revision)?,
+                    matched_in_current: false,
+                    inbound: w
+                        .inbound
+                        .into_iter()
+                        .map(&rev_ptr)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    outbound: w
+                        .outbound
+                        .into_iter()
+                        .map(&rev_ptr)
+                        .collect::<Result<Vec<_>, _>>()?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        // Reconstruct public pointer fields.
+        let revisions_by_id = s
+            .revisions_by_id
+            .into_iter()
+            .map(|(id, idx)| rev_ptr(idx).map(|ptr| (id, ptr)))
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        let ordered_revisions = s
+            .ordered_revisions
+            .into_iter()
+            .map(&rev_ptr)
+            .collect::<Result<Vec<_>, _>>()?;
+        let words = s
+            .words
+            .into_iter()
+            .map(word_ptr)
+            .collect::<Result<Vec<_>, _>>()?;
+        let current_revision = rev_ptr(s.current_revision)?;
+
+        Ok(PageAnalysis {
+            revisions,
+            revision_immutables: revision_arcs,
+            paragraphs,
+            paragraph_immutables: paragraph_arcs,
+            sentences,
+            sentence_immutables: sentence_arcs,
+            word_analyses,
+            word_immutables: word_arcs,
+            spam_ids: s.spam_ids,
+            revisions_by_id,
+            ordered_revisions,
+            words,
+            current_revision,
+            internals: PageAnalysisInternals::default(),
+        })
+    }
+}
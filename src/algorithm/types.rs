@@ -201,16 +201,19 @@ impl RevisionImmutables {
     }
 
     pub fn from_revision(revision: &Revision) -> Self {
-        Self::from_revision_with_options(revision, PageAnalysisOptions::default())
+        Self::from_revision_with_options(revision, &PageAnalysisOptions::default())
     }
 
     pub fn from_revision_with_options(
         revision: &Revision,
-        analysis_options: PageAnalysisOptions,
+        analysis_options: &PageAnalysisOptions,
     ) -> Self {
-        let (length_lowercase, text_lowercase) = match revision.text {
-            Text::Normal(ref t) => utils::to_lowercase(t, analysis_options),
-            Text::Deleted => (0, String::new()),
+        let (length_lowercase, text_lowercase) = match revision
+            .slot_text(&analysis_options.slot)
+            .and_then(Text::content)
+        {
+            Some(t) => utils::to_lowercase(t, analysis_options),
+            None => (0, String::new()),
         };
 
         let text_lowercase = ArcSubstring::new_source(Arc::new(text_lowercase));
@@ -450,6 +453,20 @@ impl<P: Pointer> IndexMut<&P> for PageAnalysis {
 pub enum AnalysisError {
     #[error("No valid revisions found")]
     NoValidRevisions,
+    /// A revision is older than the preceding one by both timestamp and id, the input
+    /// isn't in chronological order. See [`Page::normalize_revisions`](crate::dump_parser::Page::normalize_revisions).
+    ///
+    /// A timestamp that goes backwards while the ids increase is accepted, as in dumps after
+    /// history merges, and so are decreasing ids with increasing timestamps.
+    #[error("Revision {revision_id} comes after the newer revision {previous_revision_id}")]
+    RevisionsOutOfOrder {
+        revision_id: i32,
+        previous_revision_id: i32,
+    },
+    /// A revision id appears more than once in the input.
+    /// See [`Page::normalize_revisions`](crate::dump_parser::Page::normalize_revisions).
+    #[error("Duplicate revision id {0}")]
+    DuplicateRevisionId(i32),
 }
 
 // index is unique within a page
@@ -1,60 +1,192 @@
 // SPDX-License-Identifier: MPL-2.0
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{self, BufRead, BufReader, BufWriter, Write};
 use std::process::ExitCode;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use yoke::Yoke;
 
-use wikiwho::algorithm::PageAnalysis;
-use wikiwho::dump_parser::{Contributor, DumpParser, Namespace, Page, Revision};
-use wikiwho::utils::iterate_revision_tokens;
-
-/// Formats a `Contributor` directly into the serializer, avoiding an intermediate `String`
-/// allocation per editor field. Output: user id as string, or `"0|username"` for IP/anonymous.
-fn serialize_editor<S: serde::Serializer>(
-    contributor: &&Contributor,
-    serializer: S,
-) -> Result<S::Ok, S::Error> {
-    match contributor.id {
-        Some(id) if id != 0 => serializer.collect_str(&id),
-        _ => serializer.collect_str(&format_args!("0|{}", contributor.username)),
-    }
-}
+use wikiwho::algorithm::{PageAnalysis, PageAnalysisOptions};
+use wikiwho::dump_parser::{
+    decompress, open_decompressed, stats::DumpStats, DecompressedReader, DumpParser, Namespace,
+    Page, ParallelDumpReader, ParserOptions, ParsingError, Revision, SiteInfo, Text,
+};
+use wikiwho::output::jsonl::{PageOutput, RevisionFields};
+#[cfg(feature = "arrow")]
+use wikiwho::output::parquet::ParquetWriter;
+use wikiwho::output::raw::RawRecord;
+use wikiwho::output::schema::OutputSchema;
+#[cfg(feature = "sqlite")]
+use wikiwho::output::sqlite::SqliteWriter;
+use wikiwho::output::tsv::{TsvColumn, TsvWriter};
+use wikiwho::output::wikiwho_api::{AllContent, EditPersistence, RevContent, RevIds};
+use wikiwho::output::RevisionSelection;
 
 #[derive(Clone, Copy, PartialEq)]
 enum Format {
     Jsonl,
     Json,
     Raw,
+    /// One line per token, with a header line of the `--columns`.
+    Tsv,
+    /// One response of the WikiWho web API per line.
+    WikiwhoApi(ApiEndpoint),
+    /// `tokens` and `revisions` tables in an output directory.
+    #[cfg(feature = "arrow")]
+    Parquet,
+    /// `pages`, `revisions`, `tokens` and `token_events` tables in an SQLite database.
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+}
+
+/// The WikiWho API endpoint whose response schema `--format wikiwho-api:<endpoint>` writes.
+#[derive(Clone, Copy, PartialEq)]
+enum ApiEndpoint {
+    AllContent,
+    RevContent,
+    RevIds,
+    EditPersistence,
+}
+
+/// How pages are written, shared by all worker threads.
+struct OutputOptions {
+    format: Format,
+    revisions: RevisionSelection,
+    /// For `jsonl` and `json`, see `--revision-fields`.
+    revision_fields: RevisionFields,
+    /// For `edit_persistence`, see `--stopwords`.
+    stopwords: HashSet<String>,
+    /// Of the dump, for `raw` records.
+    dbname: String,
+}
+
+/// Parses the value of `--columns`.
+fn parse_columns(s: &str) -> Result<Vec<TsvColumn>, String> {
+    let columns = s
+        .split(',')
+        .map(|name| name.trim().parse::<TsvColumn>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("invalid --columns: {e}"))?;
+    if columns.is_empty() {
+        return Err("--columns must name at least one column".into());
+    }
+    Ok(columns)
+}
+
+/// Parses the value of `--revision-fields`.
+fn parse_revision_fields(s: &str) -> Result<RevisionFields, String> {
+    if s == "all" {
+        return Ok(RevisionFields::ALL);
+    }
+    let mut fields = RevisionFields::default();
+    for name in s.split(',').map(str::trim) {
+        let field = match name {
+            "comment" => &mut fields.comment,
+            "minor" => &mut fields.minor,
+            "size" => &mut fields.size,
+            "sha1" => &mut fields.sha1,
+            "token_count" => &mut fields.token_count,
+            "changes" => &mut fields.changes,
+            "spam" => &mut fields.spam,
+            other => {
+                return Err(format!(
+                    "invalid --revision-fields: unknown field `{other}`"
+                ))
+            }
+        };
+        *field = true;
+    }
+    Ok(fields)
+}
+
+/// Parses the value of `--revisions`.
+fn parse_revision_selection(s: &str) -> Result<RevisionSelection, String> {
+    match s {
+        "latest" => Ok(RevisionSelection::Latest),
+        "all" => Ok(RevisionSelection::All),
+        ids => ids
+            .split(',')
+            .map(|id| id.trim().parse::<i32>())
+            .collect::<Result<_, _>>()
+            .map(RevisionSelection::Ids)
+            .map_err(|e| format!("invalid --revisions value: {e}")),
+    }
 }
 
 fn print_usage(program: &str) {
     eprintln!(
         "Usage: {program} [OPTIONS] [INPUT]
+       {program} stats [OPTIONS] [INPUT]
+       {program} schema FORMAT
 
 Runs the WikiWho authorship attribution algorithm on a MediaWiki XML dump.
+`stats` summarises the dump without running the algorithm, see `{program} stats --help`.
+`schema` prints the JSON Schema of the records of a JSON output format.
 
 Arguments:
   INPUT                   Input XML dump file (omit or \"-\" for stdin)
-                          Compression auto-detected from extension (.bz2, .zst, .gz)
+                          Compression auto-detected from the content
+                          (bz2 incl. multistream, gz, zst, xz; 7z for files only)
 
 Options:
   -o, --output PATH       Output file (omit or \"-\" for stdout)
                           Compression auto-detected from extension (.bz2, .zst, .gz)
-  -f, --format FORMAT     Output format: jsonl (default), json, raw
+  -f, --format FORMAT     Output format: jsonl (default), json, raw, tsv, or
+                          wikiwho-api:ENDPOINT for the response schema of the WikiWho
+                          API endpoint all_content, rev_content, rev_ids or
+                          edit_persistence (one response per line), or parquet
+                          for tokens.parquet and revisions.parquet in the output
+                          directory (needs the arrow feature), or sqlite for
+                          an SQLite database at the output path (needs the
+                          sqlite feature)
   -j, --jobs N            Number of worker threads (default: number of CPUs)
+  -p, --parser-threads N  Number of XML parser threads (default: 1)
   -n, --namespace NS      Only process pages in this namespace (repeatable)
   -N, --limit N           Only process the first N pages
+      --slot ROLE         Analyse the content slot with this role (default: main)
+      --revisions REVS    Revisions to write the tokens of in jsonl, json, tsv and
+                          wikiwho-api:rev_content output: latest (default), all,
+                          or a comma-separated list of revision ids
+      --revision-fields FIELDS
+                          Comma-separated optional revision fields of jsonl and
+                          json output, or all: comment, minor, size, sha1,
+                          token_count, changes (added, removed, reintroduced),
+                          spam (also lists the revisions detected as spam)
+      --columns COLS      Comma-separated columns of tsv output (default: all):
+                          page_title, namespace, rev_id, position, token_id,
+                          str, o_rev_id, o_timestamp, editor, in_count, out_count
+      --stopwords PATH    File with one stopword per line, counted separately
+                          by wikiwho-api:edit_persistence
   -q, --quiet             Suppress progress messages on stderr
   -h, --help              Show this help message"
     );
 }
 
+fn print_stats_usage(program: &str) {
+    eprintln!(
+        "Usage: {program} stats [OPTIONS] [INPUT]
+
+Writes a JSON summary of a MediaWiki XML dump: pages, revisions and text bytes per
+namespace, revisions per page, the largest pages and the fraction of anonymous edits.
+The revision text is not decoded, text sizes come from the `bytes` attributes.
+
+Arguments:
+  INPUT                   Input XML dump file (omit or \"-\" for stdin)
+                          Compression auto-detected from the content
+
+Options:
+  -o, --output PATH       Output file (omit or \"-\" for stdout)
+  -n, --namespace NS      Only count pages in this namespace (repeatable)
+  -N, --limit N           Only count the first N pages
+      --top N             Number of largest pages to list (default: 10)
+  -h, --help              Show this help message"
+    );
+}
+
 fn main() -> ExitCode {
     match run() {
         Ok(()) => ExitCode::SUCCESS,
@@ -66,6 +198,14 @@ fn main() -> ExitCode {
 }
 
 fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("stats") {
+        return run_stats(&args[0], &args[2..]);
+    }
+    if args.get(1).map(String::as_str) == Some("schema") {
+        return run_schema(&args[0], &args[2..]);
+    }
+
     let mut opts = getopts::Options::new();
     opts.optopt(
         "o",
@@ -76,10 +216,11 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
     opts.optopt(
         "f",
         "format",
-        "Output format: jsonl (default), json, raw",
+        "Output format: jsonl (default), json, raw, tsv, wikiwho-api:ENDPOINT, parquet, sqlite",
         "FORMAT",
     );
     opts.optopt("j", "jobs", "Number of worker threads", "N");
+    opts.optopt("p", "parser-threads", "Number of XML parser threads", "N");
     opts.optmulti(
         "n",
         "namespace",
@@ -87,10 +228,39 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
         "NS",
     );
     opts.optopt("N", "limit", "Limit the number of pages to process", "N");
+    opts.optopt(
+        "",
+        "slot",
+        "Analyse the content slot with this role (default: main)",
+        "ROLE",
+    );
+    opts.optopt(
+        "",
+        "revisions",
+        "Revisions to write the tokens of: latest (default), all, or a list of ids",
+        "REVS",
+    );
+    opts.optopt(
+        "",
+        "revision-fields",
+        "Comma-separated optional revision fields of jsonl and json output, or all",
+        "FIELDS",
+    );
+    opts.optopt(
+        "",
+        "columns",
+        "Comma-separated columns of tsv output (default: all)",
+        "COLS",
+    );
+    opts.optopt(
+        "",
+        "stopwords",
+        "File with one stopword per line, for wikiwho-api:edit_persistence",
+        "PATH",
+    );
     opts.optflag("q", "quiet", "Suppress progress messages on stderr");
     opts.optflag("h", "help", "Show help");
 
-    let args: Vec<String> = std::env::args().collect();
     let matches = opts.parse(&args[1..]).map_err(|e| e.to_string())?;
 
     if matches.opt_present("h") {
@@ -102,6 +272,23 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
         None | Some("jsonl") => Format::Jsonl,
         Some("json") => Format::Json,
         Some("raw") => Format::Raw,
+        Some("tsv") => Format::Tsv,
+        Some("wikiwho-api:all_content") => Format::WikiwhoApi(ApiEndpoint::AllContent),
+        Some("wikiwho-api:rev_content") => Format::WikiwhoApi(ApiEndpoint::RevContent),
+        Some("wikiwho-api:rev_ids") => Format::WikiwhoApi(ApiEndpoint::RevIds),
+        Some("wikiwho-api:edit_persistence") => Format::WikiwhoApi(ApiEndpoint::EditPersistence),
+        #[cfg(feature = "arrow")]
+        Some("parquet") => Format::Parquet,
+        #[cfg(not(feature = "arrow"))]
+        Some("parquet") => {
+            return Err("parquet output needs wikiwho-cli built with the `arrow` feature".into())
+        }
+        #[cfg(feature = "sqlite")]
+        Some("sqlite") => Format::Sqlite,
+        #[cfg(not(feature = "sqlite"))]
+        Some("sqlite") => {
+            return Err("sqlite output needs wikiwho-cli built with the `sqlite` feature".into())
+        }
         Some(other) => return Err(format!("unknown format: {other}").into()),
     };
 
@@ -120,6 +307,19 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
             .unwrap_or(1),
     };
 
+    let parser_threads: usize = match matches.opt_str("p") {
+        Some(s) => {
+            let n = s
+                .parse::<usize>()
+                .map_err(|e| format!("invalid -p value: {e}"))?;
+            if n == 0 {
+                return Err("--parser-threads must be at least 1".into());
+            }
+            n
+        }
+        None => 1,
+    };
+
     let namespace_filter: Vec<i32> = matches
         .opt_strs("n")
         .iter()
@@ -129,33 +329,141 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
 
     let limit_pages: Option<u64> = matches.opt_get("N")?;
 
+    let revision_selection = match matches.opt_str("revisions") {
+        Some(_)
+            if !matches!(
+                format,
+                Format::Jsonl
+                    | Format::Json
+                    | Format::Tsv
+                    | Format::WikiwhoApi(ApiEndpoint::RevContent)
+            ) =>
+        {
+            return Err("--revisions is only supported with jsonl, json, tsv and \
+                        wikiwho-api:rev_content output"
+                .into())
+        }
+        Some(s) => parse_revision_selection(&s)?,
+        None => RevisionSelection::Latest,
+    };
+
+    let revision_fields = match matches.opt_str("revision-fields") {
+        Some(_) if !matches!(format, Format::Jsonl | Format::Json) => {
+            return Err("--revision-fields is only supported with jsonl and json output".into())
+        }
+        Some(s) => parse_revision_fields(&s)?,
+        None => RevisionFields::default(),
+    };
+
+    let columns = match matches.opt_str("columns") {
+        Some(_) if format != Format::Tsv => {
+            return Err("--columns is only supported with tsv output".into())
+        }
+        Some(s) => parse_columns(&s)?,
+        None => TsvColumn::ALL.to_vec(),
+    };
+
+    let stopwords: HashSet<String> = match matches.opt_str("stopwords") {
+        Some(path) => std::fs::read_to_string(&path)
+            .map_err(|e| format!("cannot read stopwords '{path}': {e}"))?
+            .lines()
+            .map(|line| line.trim().to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect(),
+        None => HashSet::new(),
+    };
+
     let quiet = matches.opt_present("q");
 
+    let mut analysis_options = PageAnalysisOptions::new();
+    if let Some(role) = matches.opt_str("slot") {
+        analysis_options = analysis_options.slot(role);
+    }
+
     let input_path = matches.free.first().map(|s| s.as_str());
     let output_path = matches.opt_str("o");
 
-    // Set up input reader with auto-decompression
-    let reader: Box<dyn BufRead + Send> = match input_path {
-        None | Some("-") => Box::new(BufReader::new(io::stdin())),
-        Some(path) => {
-            let file = std::fs::File::open(path)
-                .map_err(|e| format!("cannot open input '{path}': {e}"))?;
-            if path.ends_with(".bz2") {
-                Box::new(BufReader::new(bzip2::read::BzDecoder::new(file)))
-            } else if path.ends_with(".zst") || path.ends_with(".zstd") {
-                Box::new(BufReader::new(
-                    zstd::Decoder::new(file).map_err(|e| format!("zstd init: {e}"))?,
-                ))
-            } else if path.ends_with(".gz") {
-                Box::new(BufReader::new(flate2::read::GzDecoder::new(file)))
-            } else {
-                Box::new(BufReader::new(file))
-            }
+    let reader = open_input(input_path)?;
+    let output = match format {
+        #[cfg(feature = "arrow")]
+        Format::Parquet => {
+            let dir = output_path
+                .as_deref()
+                .filter(|path| *path != "-")
+                .ok_or("parquet output needs an output directory (-o DIR)")?;
+            let writer = ParquetWriter::create(dir)
+                .map_err(|e| format!("cannot create output '{dir}': {e}"))?;
+            Output::Parquet(Box::new(writer))
+        }
+        #[cfg(feature = "sqlite")]
+        Format::Sqlite => {
+            let path = output_path
+                .as_deref()
+                .filter(|path| *path != "-")
+                .ok_or("sqlite output needs an output file (-o PATH)")?;
+            let writer = SqliteWriter::create(path)
+                .map_err(|e| format!("cannot create output '{path}': {e}"))?;
+            Output::Sqlite(Box::new(writer))
         }
+        Format::Tsv => {
+            let writer = TsvWriter::new(create_output(output_path.as_deref())?, columns)?;
+            Output::Tsv(Box::new(writer), revision_selection.clone())
+        }
+        _ => Output::Stream(create_output(output_path.as_deref())?),
+    };
+
+    let source = if parser_threads == 1 {
+        DumpParser::new(reader).map(|parser| PageSource::Sequential(Box::new(parser)))
+    } else {
+        ParallelDumpReader::new(reader, parser_threads)
+            .map(|parser| PageSource::Parallel(Box::new(parser)))
+    }
+    .map_err(|e| format!("failed to initialize parser: {e:?}"))?;
+
+    let output_options = OutputOptions {
+        format,
+        revisions: revision_selection,
+        revision_fields,
+        stopwords,
+        dbname: source.site_info().dbname.to_string(),
     };
 
-    // Set up output writer with auto-compression
-    let writer: Box<dyn Write> = match output_path.as_deref() {
+    if num_threads == 1 {
+        process_single(
+            source,
+            output,
+            &output_options,
+            analysis_options,
+            &namespace_filter,
+            quiet,
+            limit_pages,
+        )
+    } else {
+        process_parallel(
+            source,
+            output,
+            &output_options,
+            analysis_options,
+            &namespace_filter,
+            quiet,
+            limit_pages,
+            num_threads,
+        )
+    }
+}
+
+/// Opens the input with auto-decompression.
+fn open_input(input_path: Option<&str>) -> Result<DecompressedReader, String> {
+    match input_path {
+        None | Some("-") => decompress(BufReader::new(io::stdin())),
+        Some(path) => open_decompressed(path),
+    }
+    .map_err(|e| format!("cannot open input '{}': {e}", input_path.unwrap_or("-")))
+}
+
+/// Creates the output with auto-compression by extension.
+fn create_output(output_path: Option<&str>) -> Result<Box<dyn Write>, String> {
+    Ok(match output_path {
         None | Some("-") => Box::new(BufWriter::new(io::stdout().lock())),
         Some(path) => {
             let file = std::fs::File::create(path)
@@ -178,28 +486,115 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
                 Box::new(BufWriter::new(file))
             }
         }
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Dump statistics
+// ---------------------------------------------------------------------------
+
+#[derive(serde::Serialize)]
+struct StatsOutput<'a> {
+    dbname: &'a str,
+    #[serde(flatten)]
+    stats: &'a DumpStats,
+    mean_revisions_per_page: f64,
+    anonymous_fraction: f64,
+}
+
+fn run_stats(program: &str, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut opts = getopts::Options::new();
+    opts.optopt(
+        "o",
+        "output",
+        "Output file (\"-\" or omit for stdout)",
+        "PATH",
+    );
+    opts.optmulti("n", "namespace", "Only count pages in this namespace", "NS");
+    opts.optopt("N", "limit", "Limit the number of pages to count", "N");
+    opts.optopt("", "top", "Number of largest pages to list", "N");
+    opts.optflag("h", "help", "Show help");
+
+    let matches = opts.parse(args).map_err(|e| e.to_string())?;
+    if matches.opt_present("h") {
+        print_stats_usage(program);
+        return Ok(());
+    }
+
+    let namespace_filter: Vec<i32> = matches
+        .opt_strs("n")
+        .iter()
+        .map(|s| s.parse::<i32>())
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("invalid namespace: {e}"))?;
+    let limit_pages: Option<u64> = matches.opt_get("N")?;
+    let top: usize = matches.opt_get_default("top", 10)?;
+
+    let reader = open_input(matches.free.first().map(|s| s.as_str()))?;
+    let mut writer = create_output(matches.opt_str("o").as_deref())?;
+
+    let options = ParserOptions::new().skip_text(true);
+    let mut parser = DumpParser::with_options(reader, options)
+        .map_err(|e| format!("failed to initialize parser: {e:?}"))?;
+
+    let mut stats = DumpStats::new(top);
+    let mut counted = 0;
+    while limit_pages.is_none_or(|limit| counted < limit) {
+        let Some(page) = parser.parse_page()? else {
+            break;
+        };
+        if !namespace_filter.is_empty() && !namespace_filter.contains(&page.namespace) {
+            continue;
+        }
+        stats.add_page(&page);
+        counted += 1;
+    }
+
+    let output = StatsOutput {
+        dbname: &parser.site_info().dbname,
+        stats: &stats,
+        mean_revisions_per_page: stats.mean_revisions_per_page(),
+        anonymous_fraction: stats.anonymous_fraction(),
     };
+    serde_json::to_writer_pretty(&mut writer, &output)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
 
-    if num_threads == 1 {
-        process_single(
-            reader,
-            writer,
-            format,
-            &namespace_filter,
-            quiet,
-            limit_pages,
-        )
-    } else {
-        process_parallel(
-            reader,
-            writer,
-            format,
-            &namespace_filter,
-            quiet,
-            limit_pages,
-            num_threads,
-        )
+fn print_schema_usage(program: &str) {
+    let formats: Vec<_> = OutputSchema::ALL.iter().map(|s| s.format()).collect();
+    eprintln!(
+        "Usage: {program} schema FORMAT
+
+Prints the JSON Schema of the records of a JSON output format: one line of jsonl, raw
+and wikiwho-api:ENDPOINT output, or the whole array of json output.
+
+Formats: {}",
+        formats.join(", ")
+    );
+}
+
+fn run_schema(program: &str, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let [format] = args else {
+        print_schema_usage(program);
+        return if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+            Ok(())
+        } else {
+            Err("schema takes exactly one FORMAT".into())
+        };
+    };
+    if format == "-h" || format == "--help" {
+        print_schema_usage(program);
+        return Ok(());
     }
+    let schema = OutputSchema::from_format(format)
+        .ok_or_else(|| format!("no JSON Schema for format: {format}"))?
+        .schema();
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, &schema)?;
+    writeln!(stdout)?;
+    Ok(())
 }
 
 // ---------------------------------------------------------------------------
@@ -311,9 +706,12 @@ impl ProgressReporter {
         let ns_name = self
             .namespaces
             .get(&page.namespace)
-            .map(|ns| match ns {
-                Namespace::Default => "[Default]".to_string(),
-                Namespace::Named(name) => format!("'{name}'"),
+            .map(|ns| {
+                if ns.is_default() {
+                    "[Default]".to_string()
+                } else {
+                    format!("'{}'", ns.name)
+                }
             })
             .unwrap_or_else(|| "[Unknown]".to_string());
 
@@ -430,6 +828,27 @@ impl<'a> Borrow<Revision> for RevisionIterHelper<'a> {
     }
 }
 
+/// Sizes of the analysed slot of each revision for `--revision-fields size`, taken before
+/// [`text_deleting_iterator`] drops the texts. Empty if the field is not written.
+fn revision_sizes(
+    page: &Page,
+    output_options: &OutputOptions,
+    analysis_options: &PageAnalysisOptions,
+) -> HashMap<i32, Option<u64>> {
+    if !output_options.revision_fields.size {
+        return HashMap::new();
+    }
+    page.revisions
+        .iter()
+        .map(|rev| {
+            let size = rev
+                .slot_text(&analysis_options.slot)
+                .and_then(Text::byte_len);
+            (rev.id, size)
+        })
+        .collect()
+}
+
 fn text_deleting_iterator(
     revisions: &mut [Revision],
 ) -> impl Iterator<Item = RevisionIterHelper<'_>> {
@@ -438,8 +857,11 @@ fn text_deleting_iterator(
         let last_rev = state.replace(this_rev.clone());
         if let Some(last_rev) = last_rev {
             if let Some(last_rev) = Rc::into_inner(last_rev) {
-                // we don't use the text again, so we might as well drop the original to save memory
-                last_rev.text = wikiwho::dump_parser::Text::Deleted;
+                // we don't use the texts again, so we might as well drop the originals to save memory
+                last_rev.text = Text::Deleted;
+                for slot in &mut last_rev.slots {
+                    slot.text = Text::Deleted;
+                }
             } else {
                 // this is just an optimization so if it fails that does not impact correctness
                 // but we want to be alerted to this during debugging
@@ -450,17 +872,39 @@ fn text_deleting_iterator(
     })
 }
 
+/// Where parsed pages come from: the sequential parser, or the multi-threaded one
+/// when more than one parser thread was requested.
+enum PageSource {
+    Sequential(Box<DumpParser<Box<dyn BufRead + Send>>>),
+    Parallel(Box<ParallelDumpReader>),
+}
+
+impl PageSource {
+    fn site_info(&self) -> &SiteInfo {
+        match self {
+            PageSource::Sequential(parser) => parser.site_info(),
+            PageSource::Parallel(parser) => parser.site_info(),
+        }
+    }
+
+    fn parse_page(&mut self) -> Result<Option<Page>, ParsingError> {
+        match self {
+            PageSource::Sequential(parser) => parser.parse_page(),
+            PageSource::Parallel(parser) => parser.parse_page(),
+        }
+    }
+}
+
 /// Single-threaded processing (original path, used when -j 1).
 fn process_single(
-    reader: Box<dyn BufRead + Send>,
-    mut writer: Box<dyn Write>,
-    format: Format,
+    mut parser: PageSource,
+    mut output: Output,
+    output_options: &OutputOptions,
+    analysis_options: PageAnalysisOptions,
     namespace_filter: &[i32],
     quiet: bool,
     page_limit: Option<u64>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut parser =
-        DumpParser::new(reader).map_err(|e| format!("failed to initialize parser: {e:?}"))?;
     let reporter = ProgressReporter::new(quiet, parser.site_info().namespaces.clone());
 
     if !quiet {
@@ -469,9 +913,7 @@ fn process_single(
 
     let mut page_count: u64 = 0;
 
-    if format == Format::Json {
-        write!(writer, "[")?;
-    }
+    output.begin(output_options.format)?;
 
     while let Some(mut page) = parser
         .parse_page()
@@ -487,8 +929,11 @@ fn process_single(
 
         reporter.page_parsed(&page);
 
-        let analysis = match PageAnalysis::analyse_page(text_deleting_iterator(&mut page.revisions))
-        {
+        let sizes = revision_sizes(&page, output_options, &analysis_options);
+        let analysis = match PageAnalysis::analyse_page_with_options(
+            text_deleting_iterator(&mut page.revisions),
+            analysis_options.clone(),
+        ) {
             Ok(a) => a,
             Err(e) => {
                 reporter.page_skipped(&page.title, &e.to_string());
@@ -498,18 +943,14 @@ fn process_single(
 
         reporter.page_analysed();
         let yoke = Yoke::attach_to_cart(Box::new((page, analysis)), |cart| {
-            build_page_output(&cart.0, &cart.1)
+            build_page_result(&cart.0, &cart.1, output_options, &analysis_options, &sizes)
         });
-        write_page_result(&mut writer, &yoke, format, page_count)?;
+        output.write_page(&yoke, output_options.format, page_count)?;
         reporter.page_written();
         page_count += 1;
     }
 
-    if format == Format::Json {
-        writeln!(writer, "]")?;
-    }
-
-    writer.flush()?;
+    output.finish(output_options.format)?;
     reporter.finish();
 
     Ok(())
@@ -517,41 +958,44 @@ fn process_single(
 
 /// Result from a worker thread: either a pre-built output or a skipped page.
 ///
-/// The `Ok` variant carries a [`PageOutput`] yoked to its backing `(Page, PageAnalysis)`.
-/// Workers build the `PageOutput` (resolving pointers, collecting token data) so that the
+/// The `Ok` variant carries a [`PageResult`] yoked to its backing `(Page, PageAnalysis)`.
+/// Workers build the `PageResult` (resolving pointers, collecting token data) so that the
 /// writer thread only needs to serialize — no construction work on the hot I/O path.
 enum AnalysisResult {
-    Ok(Yoke<PageOutput<'static>, Box<(Page, PageAnalysis)>>),
+    Ok(Yoke<PageResult<'static>, Box<(Page, PageAnalysis)>>),
     Skipped(String, String), // (title, error message)
 }
 
 /// Multi-threaded processing pipeline:
 ///
 /// ```text
-///   parser (1 thread) --> workers (N threads) --> writer (main thread)
+///   parser (1 thread*) --> workers (N threads) --> writer (main thread)
 ///          |    bounded channel    |    bounded channel    |
 /// ```
 ///
+/// \* With `--parser-threads` the parser thread drives a [`ParallelDumpReader`], which splits
+/// the XML stream at page boundaries and parses the pages on its own thread pool.
+///
 /// Both channels are bounded (`num_threads * 2`), which makes the pipeline self-balancing:
 /// if the writer is slow, the result channel fills → workers block → work channel fills →
 /// parser blocks. No stage can overwhelm another, and memory usage stays bounded.
 ///
-/// Workers build [`PageOutput`] (via [`build_page_output`]) before sending results, so the
+/// Workers build [`PageResult`] (via [`build_page_result`]) before sending results, so the
 /// writer thread only calls `serde_json::to_writer` — keeping I/O throughput high.
 ///
 /// Output order is non-deterministic (no reordering). This avoids head-of-line blocking
 /// where one slow large page would hold up all completed pages behind it.
+#[allow(clippy::too_many_arguments)]
 fn process_parallel(
-    reader: Box<dyn BufRead + Send>,
-    mut writer: Box<dyn Write>,
-    format: Format,
+    mut parser: PageSource,
+    output: Output,
+    output_options: &OutputOptions,
+    analysis_options: PageAnalysisOptions,
     namespace_filter: &[i32],
     quiet: bool,
     page_limit: Option<u64>,
     num_threads: usize,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut parser =
-        DumpParser::new(reader).map_err(|e| format!("failed to initialize parser: {e:?}"))?;
     let reporter = ProgressReporter::new(quiet, parser.site_info().namespaces.clone());
 
     if !quiet {
@@ -561,7 +1005,8 @@ fn process_parallel(
 
     // Bounded channel: parser -> workers (back-pressure to avoid unbounded memory growth)
     let (work_tx, work_rx) = std::sync::mpsc::sync_channel::<Page>(num_threads * 2);
-    let work_rx = Mutex::new(work_rx);
+    // Shared by the workers only, so that the parser stops once they are all gone
+    let work_rx = Arc::new(Mutex::new(work_rx));
 
     // Bounded channel: workers -> writer (back-pressure prevents memory growth)
     let (result_tx, result_rx) = std::sync::mpsc::sync_channel::<AnalysisResult>(num_threads * 2);
@@ -572,9 +1017,10 @@ fn process_parallel(
     std::thread::scope(|s| {
         // Spawn N worker threads that pull pages and analyze them
         for n in 0..num_threads {
-            let work_rx = &work_rx;
+            let work_rx = Arc::clone(&work_rx);
             let result_tx = result_tx.clone();
             let reporter = &reporter;
+            let analysis_options = &analysis_options;
             std::thread::Builder::new()
                 .name(format!("worker {n}"))
                 .spawn_scoped(s, move || {
@@ -585,14 +1031,22 @@ fn process_parallel(
                             Err(_) => break, // channel closed, no more work
                         };
 
-                        let result = match PageAnalysis::analyse_page(text_deleting_iterator(
-                            &mut page.revisions,
-                        )) {
+                        let sizes = revision_sizes(&page, output_options, analysis_options);
+                        let result = match PageAnalysis::analyse_page_with_options(
+                            text_deleting_iterator(&mut page.revisions),
+                            analysis_options.clone(),
+                        ) {
                             Ok(analysis) => {
                                 reporter.page_analysed();
                                 let yoke =
                                     Yoke::attach_to_cart(Box::new((page, analysis)), |cart| {
-                                        build_page_output(&cart.0, &cart.1)
+                                        build_page_result(
+                                            &cart.0,
+                                            &cart.1,
+                                            output_options,
+                                            analysis_options,
+                                            &sizes,
+                                        )
                                     });
                                 AnalysisResult::Ok(yoke)
                             }
@@ -611,6 +1065,7 @@ fn process_parallel(
 
         // Drop the original result_tx so that result_rx closes once all workers finish
         drop(result_tx);
+        drop(work_rx);
 
         // Parser thread: reads pages sequentially, applies namespace filter, sends to workers
         let parse_error_ref = &parse_error;
@@ -649,51 +1104,131 @@ fn process_parallel(
             .unwrap();
 
         // Main thread: collect results and write (no reordering)
-        if format == Format::Json {
-            write!(writer, "[").unwrap();
-        }
+        write_results(output, output_options.format, result_rx, reporter)
+    })?;
+
+    // Check if the parser hit an error
+    if let Some(err) = parse_error.into_inner().unwrap() {
+        return Err(err.into());
+    }
 
-        let mut page_count: u64 = 0;
+    Ok(())
+}
 
-        for result in &result_rx {
-            match result {
-                AnalysisResult::Skipped(title, err) => {
-                    reporter.page_skipped(&title, &err);
-                }
-                AnalysisResult::Ok(yoke) => {
-                    write_page_result(&mut writer, &yoke, format, page_count).unwrap();
-                    page_count += 1;
-                    reporter.page_written();
-                }
+/// Writes the results of the workers as they arrive.
+///
+/// Returning early drops `result_rx`, which stops the workers and, once they are gone, the
+/// parser.
+fn write_results(
+    mut output: Output,
+    format: Format,
+    result_rx: std::sync::mpsc::Receiver<AnalysisResult>,
+    reporter: &ProgressReporter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    output.begin(format)?;
+
+    let mut page_count: u64 = 0;
+
+    for result in result_rx {
+        match result {
+            AnalysisResult::Skipped(title, err) => {
+                reporter.page_skipped(&title, &err);
+            }
+            AnalysisResult::Ok(yoke) => {
+                output.write_page(&yoke, format, page_count)?;
+                page_count += 1;
+                reporter.page_written();
             }
-            // TODO: drop in separate thread
         }
+        // TODO: drop in separate thread
+    }
 
-        if format == Format::Json {
-            writeln!(writer, "]").unwrap();
-        }
+    output.finish(format)?;
+    reporter.finish();
+    Ok(())
+}
 
-        writer.flush().unwrap();
-        reporter.finish();
-    });
+/// Where the results are written.
+enum Output {
+    /// The output file or stdout, for the JSON formats.
+    Stream(Box<dyn Write>),
+    /// The output file or stdout, for `tsv`, and the revisions to write the tokens of.
+    Tsv(Box<TsvWriter<Box<dyn Write>>>, RevisionSelection),
+    #[cfg(feature = "arrow")]
+    Parquet(Box<ParquetWriter<std::fs::File>>),
+    #[cfg(feature = "sqlite")]
+    Sqlite(Box<SqliteWriter>),
+}
 
-    // Check if the parser hit an error
-    if let Some(err) = parse_error.into_inner().unwrap() {
-        return Err(err.into());
+impl Output {
+    fn begin(&mut self, format: Format) -> io::Result<()> {
+        match self {
+            Output::Stream(writer) if format == Format::Json => write!(writer, "["),
+            _ => Ok(()),
+        }
     }
 
-    Ok(())
+    fn write_page(
+        &mut self,
+        yoke: &Yoke<PageResult<'static>, Box<(Page, PageAnalysis)>>,
+        format: Format,
+        page_count: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            Output::Stream(writer) => write_page_result(writer, yoke, format, page_count),
+            Output::Tsv(writer, revisions) => {
+                let (page, analysis) = &**yoke.backing_cart();
+                writer.write_page(page, analysis, revisions)?;
+                Ok(())
+            }
+            #[cfg(feature = "arrow")]
+            Output::Parquet(writer) => {
+                let (page, analysis) = &**yoke.backing_cart();
+                writer.write_page(page, analysis)?;
+                Ok(())
+            }
+            #[cfg(feature = "sqlite")]
+            Output::Sqlite(writer) => {
+                let (page, analysis) = &**yoke.backing_cart();
+                writer.write_page(page, analysis)?;
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(self, format: Format) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            Output::Stream(mut writer) => {
+                if format == Format::Json {
+                    writeln!(writer, "]")?;
+                }
+                writer.flush()?;
+            }
+            Output::Tsv(writer, _) => {
+                writer.finish()?;
+            }
+            #[cfg(feature = "arrow")]
+            Output::Parquet(writer) => {
+                writer.finish()?;
+            }
+            #[cfg(feature = "sqlite")]
+            Output::Sqlite(writer) => {
+                writer.finish()?;
+            }
+        }
+        Ok(())
+    }
 }
 
 fn write_page_result(
     writer: &mut Box<dyn Write>,
-    yoke: &Yoke<PageOutput<'static>, Box<(Page, PageAnalysis)>>,
+    yoke: &Yoke<PageResult<'static>, Box<(Page, PageAnalysis)>>,
     format: Format,
     page_count: u64,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // TODO: look into speeding up serialization
     match format {
-        Format::Jsonl => {
+        Format::Jsonl | Format::Raw | Format::WikiwhoApi(_) => {
             serde_json::to_writer(&mut *writer, yoke.get())?;
             writeln!(writer)?;
         }
@@ -703,98 +1238,83 @@ fn write_page_result(
             }
             serde_json::to_writer(&mut *writer, yoke.get())?;
         }
-        Format::Raw => {
-            serde_json::to_writer(&mut *writer, &yoke.backing_cart().1)?;
-            writeln!(writer)?;
-        }
+        Format::Tsv => unreachable!("tsv is written to Output::Tsv"),
+        #[cfg(feature = "arrow")]
+        Format::Parquet => unreachable!("parquet is written to Output::Parquet"),
+        #[cfg(feature = "sqlite")]
+        Format::Sqlite => unreachable!("sqlite is written to Output::Sqlite"),
     }
     Ok(())
 }
 
-/// Serialization-ready view of a page's analysis results.
-///
-/// These structs derive `Serialize` so `serde_json` can stream fields directly to the writer
-/// without building an intermediate `serde_json::Value` tree — avoiding a full duplicate of
-/// all token strings, revision ids, and editor data in memory during serialization.
-///
-/// Fields borrow from `Page` and `PageAnalysis` (e.g. `&'a str` for token values,
-/// `&'a Contributor` for editors). The `Yokeable` derive allows these borrowing structs to
-/// be sent across threads: workers build a `PageOutput` and yoke it to a `Box<(Page,
-/// PageAnalysis)>` cart, which keeps the borrowed data alive.
-#[derive(serde::Serialize, yoke::Yokeable)]
-struct PageOutput<'a> {
-    article_title: &'a str,
-    namespace: i32,
-    revisions: Vec<RevisionOutput<'a>>,
-    spam_ids: &'a [i32],
-    all_tokens: Vec<TokenOutput<'a>>,
-}
-
+/// The output of a page in the selected format.
 #[derive(serde::Serialize, yoke::Yokeable)]
-struct RevisionOutput<'a> {
-    id: i32,
-    timestamp: String,
-    #[serde(serialize_with = "serialize_editor")]
-    editor: &'a Contributor,
+#[serde(untagged)]
+enum PageResult<'a> {
+    Cli(PageOutput<'a>),
+    AllContent(AllContent<'a>),
+    RevContent(RevContent<'a>),
+    RevIds(RevIds<'a>),
+    EditPersistence(EditPersistence),
+    Raw(RawRecord<'a>),
+    /// Written straight from the page and its analysis.
+    Cart,
 }
 
-#[derive(serde::Serialize, yoke::Yokeable)]
-struct TokenOutput<'a> {
-    token_id: usize,
-    #[serde(rename = "str")]
-    value: &'a str,
-    o_rev_id: i32,
-    #[serde(serialize_with = "serialize_editor")]
-    editor: &'a Contributor,
-    #[serde(rename = "in")]
-    inbound: Vec<i32>,
-    #[serde(rename = "out")]
-    outbound: Vec<i32>,
-}
-
-/// Builds a [`PageOutput`] that borrows from both the parsed `Page` and the `PageAnalysis`.
-///
-/// This resolves internal pointers (revision ids, token origins, in/out edges) into a flat
-/// structure ready for serialization. Called on worker threads so the writer thread doesn't
-/// need to do this work.
-fn build_page_output<'a>(page: &'a Page, analysis: &'a PageAnalysis) -> PageOutput<'a> {
-    let revisions_by_id: HashMap<i32, &Revision> =
-        page.revisions.iter().map(|rev| (rev.id, rev)).collect();
-
-    let revisions: Vec<RevisionOutput<'a>> = analysis
-        .ordered_revisions
-        .iter()
-        .map(|rev_ptr| {
-            let xml_revision = revisions_by_id[&rev_ptr.id];
-            RevisionOutput {
-                id: xml_revision.id,
-                timestamp: xml_revision.timestamp.to_rfc3339(),
-                editor: &xml_revision.contributor,
-            }
-        })
-        .collect();
-
-    let last_rev = &analysis.current_revision;
-    let all_tokens: Vec<TokenOutput<'a>> = iterate_revision_tokens(analysis, last_rev)
-        .map(|word_ptr| {
-            let word_analysis = &analysis[word_ptr];
-            let xml_origin_revision = revisions_by_id[&word_analysis.origin_revision.id];
-            TokenOutput {
-                token_id: word_ptr.unique_id(),
-                value: &word_ptr.value,
-                o_rev_id: xml_origin_revision.id,
-                editor: &xml_origin_revision.contributor,
-                inbound: word_analysis.inbound.iter().map(|r| r.id).collect(),
-                outbound: word_analysis.outbound.iter().map(|r| r.id).collect(),
+fn build_page_result<'a>(
+    page: &'a Page,
+    analysis: &'a PageAnalysis,
+    output_options: &OutputOptions,
+    analysis_options: &PageAnalysisOptions,
+    sizes: &HashMap<i32, Option<u64>>,
+) -> PageResult<'a> {
+    let revisions = &output_options.revisions;
+    match output_options.format {
+        Format::Jsonl | Format::Json => {
+            let mut record = PageOutput::with_revision_fields(
+                page,
+                analysis,
+                revisions,
+                output_options.revision_fields,
+            );
+            // the texts are dropped by the time the record is built
+            for revision in &mut record.revisions {
+                if let Some(size) = &mut revision.size {
+                    *size = sizes.get(&revision.id).copied().flatten();
+                }
             }
-        })
-        .collect();
-
-    PageOutput {
-        article_title: &page.title,
-        namespace: page.namespace,
-        revisions,
-        spam_ids: &analysis.spam_ids,
-        all_tokens,
+            PageResult::Cli(record)
+        }
+        Format::Raw => PageResult::Raw(RawRecord::new(
+            &output_options.dbname,
+            analysis_options,
+            page,
+            analysis,
+        )),
+        Format::Tsv => PageResult::Cart,
+        #[cfg(feature = "arrow")]
+        Format::Parquet => PageResult::Cart,
+        #[cfg(feature = "sqlite")]
+        Format::Sqlite => PageResult::Cart,
+        Format::WikiwhoApi(ApiEndpoint::AllContent) => {
+            PageResult::AllContent(AllContent::new(page, analysis))
+        }
+        Format::WikiwhoApi(ApiEndpoint::RevContent) => {
+            let selected: Vec<_> = match revisions {
+                RevisionSelection::Latest => vec![&analysis.current_revision],
+                _ => analysis
+                    .ordered_revisions
+                    .iter()
+                    .filter(|rev_ptr| revisions.contains(rev_ptr.id))
+                    .collect(),
+            };
+            PageResult::RevContent(RevContent::new(page, analysis, selected))
+        }
+        Format::WikiwhoApi(ApiEndpoint::RevIds) => PageResult::RevIds(RevIds::new(page, analysis)),
+        Format::WikiwhoApi(ApiEndpoint::EditPersistence) => {
+            PageResult::EditPersistence(EditPersistence::new(page, analysis, |word| {
+                output_options.stopwords.contains(word)
+            }))
+        }
     }
 }
@@ -0,0 +1,369 @@
+// SPDX-License-Identifier: MPL-2.0
+//! Reads page histories from saved [MediaWiki Action API](https://www.mediawiki.org/wiki/API:Revisions)
+//! responses instead of XML dumps.
+//!
+//! The responses are expected to come from a query like
+//! `action=query&prop=revisions&rvslots=main&rvprop=ids|timestamp|user|userid|sha1|comment|flags|content`,
+//! in either `formatversion=1` or `formatversion=2`. A history that needed several requests
+//! (continuation) is read by passing all responses to the same [`ApiJsonReader`], in any order.
+//!
+//! Revisions are converted the same way [`DumpParser`](super::DumpParser) converts the XML:
+//! - hidden users (`userhidden`) become [`Contributor::deleted`],
+//! - hidden text (`texthidden`) becomes [`Text::Deleted`],
+//! - hidden comments (`commenthidden`) become [`Comment::Deleted`] and empty comments `None`,
+//! - anonymous users (`anon`) get their IP address as user name and no id,
+//! - the hexadecimal `sha1` is converted to the base36 form used in dumps.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use wikiwho::dump_parser::api_json;
+//!
+//! let pages = api_json::read_files(["history-1.json", "history-2.json"]).unwrap();
+//! for page in &pages {
+//!     println!("{}: {} revisions", page.title, page.revisions.len());
+//! }
+//! ```
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+use compact_str::CompactString;
+use serde::Deserialize;
+
+use super::{
+    page_title, parse_timestamp, Comment, Contributor, Page, Revision, Sha1Hash, Slot, Text,
+    MAIN_SLOT,
+};
+
+/// Errors when reading API responses.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ApiJsonError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The response is an API error instead of a query result.
+    #[error("API error {code}: {info}")]
+    Api { code: String, info: String },
+    /// A revision lacks a property the analysis needs, e.g. because it was not requested in `rvprop`.
+    #[error("revision {revision_id} has no {field}")]
+    MissingField {
+        revision_id: i32,
+        field: &'static str,
+    },
+    #[error("revision {revision_id} has an invalid timestamp: {timestamp}")]
+    InvalidTimestamp { revision_id: i32, timestamp: String },
+    #[error("revision {revision_id} has an invalid sha1: {sha1}")]
+    InvalidSha1 { revision_id: i32, sha1: String },
+}
+
+/// Collects the pages and revisions of one or more API responses.
+///
+/// Revisions of the same page are merged across responses, duplicates (by revision id)
+/// are dropped. [`into_pages`](ApiJsonReader::into_pages) returns the pages in the order of
+/// their first appearance, with the revisions sorted oldest first like in dumps, regardless
+/// of the `rvdir` of the requests.
+#[derive(Debug, Default)]
+pub struct ApiJsonReader {
+    pages: Vec<Page>,
+    /// index into `pages` by namespace and full title
+    page_indices: HashMap<(i32, String), usize>,
+    revision_ids: Vec<HashSet<i32>>,
+}
+
+impl ApiJsonReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the API responses in `reader`.
+    ///
+    /// The input is a single response, a JSON array of responses or several responses
+    /// one after another (e.g. JSON Lines). If an error is returned, the responses read
+    /// before it have been added already.
+    pub fn read<R: Read>(&mut self, reader: R) -> Result<(), ApiJsonError> {
+        for responses in serde_json::Deserializer::from_reader(reader).into_iter::<Responses>() {
+            match responses? {
+                Responses::One(response) => self.add_response(response)?,
+                Responses::Many(responses) => {
+                    for response in responses {
+                        self.add_response(response)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the API responses in `json`, see [`read`](ApiJsonReader::read).
+    pub fn read_str(&mut self, json: &str) -> Result<(), ApiJsonError> {
+        self.read(json.as_bytes())
+    }
+
+    /// Returns the collected pages, with revisions sorted by timestamp and revision id.
+    pub fn into_pages(mut self) -> Vec<Page> {
+        for page in &mut self.pages {
+            page.revisions
+                .sort_by_key(|revision| (revision.timestamp, revision.id));
+        }
+        self.pages
+    }
+
+    fn add_response(&mut self, response: Response) -> Result<(), ApiJsonError> {
+        if let Some(error) = response.error {
+            return Err(ApiJsonError::Api {
+                code: error.code,
+                info: error.info,
+            });
+        }
+        let Some(query) = response.query else {
+            return Ok(());
+        };
+
+        let pages = match query.pages {
+            Pages::List(pages) => pages,
+            Pages::Map(pages) => {
+                let mut pages = pages.into_values().collect::<Vec<_>>();
+                pages.sort_by_key(|page| page.pageid);
+                pages
+            }
+        };
+
+        for api_page in pages {
+            let Some(ns) = api_page
+                .ns
+                .filter(|_| !api_page.missing.0 && !api_page.invalid.0)
+            else {
+                continue;
+            };
+
+            let key = (ns, api_page.title);
+            let index = match self.page_indices.get(&key) {
+                Some(index) => *index,
+                None => {
+                    self.pages.push(Page {
+                        id: api_page.pageid.and_then(|id| i32::try_from(id).ok()),
+                        title: page_title(&key.1),
+                        namespace: ns,
+                        revisions: Vec::new(),
+                    });
+                    self.revision_ids.push(HashSet::new());
+                    self.page_indices.insert(key, self.pages.len() - 1);
+                    self.pages.len() - 1
+                }
+            };
+
+            for api_revision in api_page.revisions {
+                if self.revision_ids[index].insert(api_revision.revid) {
+                    let revision = api_revision.into_revision()?;
+                    self.pages[index].revisions.push(revision);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the API responses in the given files, e.g. all continuation parts of a page history.
+pub fn read_files<P: AsRef<Path>>(
+    paths: impl IntoIterator<Item = P>,
+) -> Result<Vec<Page>, ApiJsonError> {
+    let mut reader = ApiJsonReader::new();
+    for path in paths {
+        reader.read(BufReader::new(File::open(path)?))?;
+    }
+    Ok(reader.into_pages())
+}
+
+/// A boolean property: `formatversion=1` marks it with an empty string, `formatversion=2` with `true`.
+#[derive(Debug, Default)]
+struct Flag(bool);
+
+impl<'de> Deserialize<'de> for Flag {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        Ok(Flag(value != serde_json::Value::Bool(false)))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Responses {
+    One(Response),
+    Many(Vec<Response>),
+}
+
+#[derive(Deserialize)]
+struct Response {
+    error: Option<ApiErrorResponse>,
+    query: Option<Query>,
+}
+
+#[derive(Deserialize)]
+struct ApiErrorResponse {
+    code: String,
+    #[serde(default)]
+    info: String,
+}
+
+#[derive(Deserialize)]
+struct Query {
+    #[serde(default)]
+    pages: Pages,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Pages {
+    /// `formatversion=2`
+    List(Vec<ApiPage>),
+    /// `formatversion=1`, keyed by page id
+    Map(HashMap<String, ApiPage>),
+}
+
+impl Default for Pages {
+    fn default() -> Self {
+        Pages::List(Vec::new())
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiPage {
+    pageid: Option<u64>,
+    /// Missing for invalid titles.
+    ns: Option<i32>,
+    title: String,
+    #[serde(default)]
+    missing: Flag,
+    #[serde(default)]
+    invalid: Flag,
+    #[serde(default)]
+    revisions: Vec<ApiRevision>,
+}
+
+#[derive(Deserialize)]
+struct ApiRevision {
+    revid: i32,
+    timestamp: Option<String>,
+    user: Option<String>,
+    userid: Option<i32>,
+    #[serde(default)]
+    anon: Flag,
+    #[serde(default)]
+    userhidden: Flag,
+    #[serde(default)]
+    minor: Flag,
+    sha1: Option<String>,
+    comment: Option<String>,
+    #[serde(default)]
+    commenthidden: Flag,
+    /// by role
+    slots: Option<BTreeMap<String, ApiSlot>>,
+    /// content of requests without `rvslots`
+    #[serde(flatten)]
+    legacy_content: ApiSlot,
+}
+
+#[derive(Default, Deserialize)]
+struct ApiSlot {
+    contentmodel: Option<String>,
+    contentformat: Option<String>,
+    #[serde(alias = "*")]
+    content: Option<String>,
+    #[serde(default)]
+    texthidden: Flag,
+    sha1: Option<String>,
+}
+
+impl ApiSlot {
+    fn text(&mut self, revision_id: i32) -> Result<Text, ApiJsonError> {
+        if self.texthidden.0 {
+            Ok(Text::Deleted)
+        } else {
+            let content = self.content.take().ok_or(ApiJsonError::MissingField {
+                revision_id,
+                field: "content",
+            })?;
+            Ok(Text::Normal(content))
+        }
+    }
+}
+
+fn parse_sha1(revision_id: i32, sha1: Option<String>) -> Result<Option<Sha1Hash>, ApiJsonError> {
+    match sha1 {
+        Some(sha1) => match Sha1Hash::from_hex(&sha1) {
+            Some(hash) => Ok(Some(hash)),
+            None => Err(ApiJsonError::InvalidSha1 { revision_id, sha1 }),
+        },
+        None => Ok(None),
+    }
+}
+
+impl ApiRevision {
+    fn into_revision(self) -> Result<Revision, ApiJsonError> {
+        let revision_id = self.revid;
+        let missing = |field| ApiJsonError::MissingField { revision_id, field };
+
+        let timestamp = self.timestamp.ok_or_else(|| missing("timestamp"))?;
+        let timestamp = parse_timestamp(&timestamp).ok_or(ApiJsonError::InvalidTimestamp {
+            revision_id,
+            timestamp,
+        })?;
+
+        let contributor = if self.userhidden.0 {
+            Contributor::deleted()
+        } else {
+            Contributor {
+                username: CompactString::from(self.user.ok_or_else(|| missing("user"))?),
+                // anonymous edits have user id 0
+                id: self.userid.filter(|id| !self.anon.0 && *id != 0),
+                deleted: false,
+            }
+        };
+
+        let mut slots = self.slots.unwrap_or_default();
+        let mut main = slots.remove(MAIN_SLOT).unwrap_or(self.legacy_content);
+        let text = main.text(revision_id)?;
+        let sha1 = parse_sha1(revision_id, self.sha1)?;
+
+        let slots = slots
+            .into_iter()
+            .map(|(role, mut slot)| {
+                Ok(Slot {
+                    role: role.into(),
+                    text: slot.text(revision_id)?,
+                    sha1: parse_sha1(revision_id, slot.sha1)?,
+                    model: slot.contentmodel.map(CompactString::from),
+                    format: slot.contentformat.map(CompactString::from),
+                })
+            })
+            .collect::<Result<Vec<_>, ApiJsonError>>()?;
+
+        // dumps omit the comment tag for empty comments
+        let comment = if self.commenthidden.0 {
+            Some(Comment::Deleted)
+        } else {
+            self.comment
+                .filter(|comment| !comment.is_empty())
+                .map(|comment| Comment::Normal(comment.into()))
+        };
+
+        Ok(Revision {
+            id: revision_id,
+            timestamp,
+            contributor,
+            text,
+            sha1,
+            comment,
+            minor: self.minor.0,
+            model: main.contentmodel.map(CompactString::from),
+            format: main.contentformat.map(CompactString::from),
+            slots,
+        })
+    }
+}
@@ -0,0 +1,209 @@
+// SPDX-License-Identifier: MPL-2.0
+use std::{collections::VecDeque, fmt::Debug};
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use super::{
+    parallel::{find_last, parse_fragment, PageResult, PAGE_END},
+    Diagnostic, DumpParser, Page, ParserOptions, ParsingError, SiteInfo,
+};
+
+const SITE_INFO_END: &[u8] = b"</siteinfo>";
+
+/// A parser for reading Wikimedia XML dumps from an asynchronous reader.
+///
+/// Produces the same pages, diagnostics and errors as [`DumpParser`]. The stream is read
+/// asynchronously until it contains one or more complete `<page>` elements, which are then
+/// parsed by a [`DumpParser`] in memory. This works like the splitting of
+/// [`ParallelDumpReader`](super::ParallelDumpReader), just without threads.
+///
+/// Parsing itself is not asynchronous: a call to [`parse_page`](AsyncDumpParser::parse_page)
+/// blocks the task while the buffered pages are parsed. For very large pages, consider
+/// moving the analysis (which is much slower than parsing) to a blocking task.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use tokio::io::AsyncBufRead;
+/// use wikiwho::dump_parser::{AsyncDumpParser, ParsingError};
+///
+/// async fn print_titles(reader: impl AsyncBufRead + Unpin) -> Result<(), ParsingError> {
+///     let mut parser = AsyncDumpParser::new(reader).await?;
+///     while let Some(page) = parser.parse_page().await? {
+///         println!("{}", page.title);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct AsyncDumpParser<R> {
+    reader: R,
+    options: ParserOptions,
+    site_info: SiteInfo,
+
+    /// Bytes read from the stream but not parsed yet
+    pending: Vec<u8>,
+    /// Offset of `pending` in the stream
+    offset: u64,
+    current_pages: VecDeque<PageResult>,
+    current_diagnostics: Vec<Diagnostic>,
+    finished: bool,
+}
+
+impl<R> Debug for AsyncDumpParser<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncDumpParser")
+            .field("site_info", &self.site_info)
+            .field("pending.len", &self.pending.len())
+            .field("offset", &self.offset)
+            .field("current_pages.len", &self.current_pages.len())
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncDumpParser<R> {
+    /// Creates a new parser from the given asynchronous buffered reader.
+    ///
+    /// Immediately reads and parses the `<siteinfo>` header from the XML stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParsingError`] if reading fails, the XML is malformed or the `<siteinfo>`
+    /// block cannot be parsed.
+    pub async fn new(reader: R) -> Result<Self, ParsingError> {
+        Self::with_options(reader, ParserOptions::default()).await
+    }
+
+    /// Creates a new parser like [`AsyncDumpParser::new`], using the given options.
+    pub async fn with_options(reader: R, options: ParserOptions) -> Result<Self, ParsingError> {
+        let mut new = Self {
+            reader,
+            options,
+            site_info: SiteInfo::default(),
+            pending: Vec::new(),
+            offset: 0,
+            current_pages: VecDeque::new(),
+            current_diagnostics: Vec::new(),
+            finished: false,
+        };
+
+        // read at least the complete header, the parser reports a truncated one
+        let mut searched: usize = 0;
+        loop {
+            let eof = new.fill_pending().await?;
+            let search_start = searched.saturating_sub(SITE_INFO_END.len() - 1);
+            if eof || find_last(&new.pending[search_start..], SITE_INFO_END).is_some() {
+                break;
+            }
+            searched = new.pending.len();
+        }
+
+        let parser = DumpParser::with_options(new.pending.as_slice(), new.options.clone())?;
+        new.site_info = parser.site_info().clone();
+        new.current_diagnostics = parser.diagnostics().to_vec();
+        let header_len = parser.bytes_consumed();
+
+        new.pending.drain(..header_len as usize);
+        new.offset = header_len;
+        Ok(new)
+    }
+
+    /// Returns the site information parsed from the `<siteinfo>` header.
+    pub fn site_info(&self) -> &SiteInfo {
+        &self.site_info
+    }
+
+    /// Returns the diagnostics reported while parsing the most recently returned page,
+    /// or the `<siteinfo>` header if no page has been returned yet.
+    ///
+    /// See [`DumpParser::diagnostics`].
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.current_diagnostics
+    }
+
+    /// Returns the next `<page>` element.
+    ///
+    /// Has the same return values as [`DumpParser::parse_page`]. Errors from the reader are
+    /// returned as [`ParsingErrorKind::XmlError`](super::ParsingErrorKind::XmlError). After an
+    /// error that is not [recoverable](ParsingError::is_recoverable) has been returned, all
+    /// following calls return `Ok(None)`.
+    pub async fn parse_page(&mut self) -> Result<Option<Page>, ParsingError> {
+        loop {
+            if let Some(result) = self.current_pages.pop_front() {
+                self.current_diagnostics.clear();
+                match result {
+                    Ok((page, diagnostics)) => {
+                        self.current_diagnostics = diagnostics;
+                        return Ok(Some(page));
+                    }
+                    Err(error) => {
+                        if !error.is_recoverable() {
+                            self.finished = true;
+                        }
+                        return Err(error);
+                    }
+                }
+            }
+            self.current_diagnostics.clear();
+            if self.finished {
+                return Ok(None);
+            }
+
+            let data = match self.read_pages().await {
+                Ok(Some(data)) => data,
+                Ok(None) => {
+                    self.finished = true;
+                    return Ok(None);
+                }
+                Err(error) => {
+                    self.finished = true;
+                    return Err(error);
+                }
+            };
+            self.current_pages = parse_fragment(&data, self.offset, self.options.clone()).into();
+            self.offset += data.len() as u64;
+        }
+    }
+
+    /// Reads until the stream contains at least one complete page and returns all complete
+    /// pages read so far, or the rest of the stream at the end of the input.
+    ///
+    /// Returns `None` once there are no more pages.
+    async fn read_pages(&mut self) -> Result<Option<Vec<u8>>, ParsingError> {
+        // everything before this index has already been searched for a page boundary
+        let mut searched: usize = 0;
+        loop {
+            let search_start = searched.saturating_sub(PAGE_END.len() - 1);
+            if let Some(end) = find_last(&self.pending[search_start..], PAGE_END) {
+                let rest = self.pending.split_off(search_start + end + PAGE_END.len());
+                return Ok(Some(std::mem::replace(&mut self.pending, rest)));
+            }
+            searched = self.pending.len();
+
+            if self.fill_pending().await? {
+                if find_last(&self.pending, b"<page").is_none() {
+                    // only the closing `</mediawiki>` tag is left
+                    return Ok(None);
+                }
+                // a truncated page, the parser reports the error
+                return Ok(Some(std::mem::take(&mut self.pending)));
+            }
+        }
+    }
+
+    /// Appends the next buffer of the reader to `pending`. Returns `true` at the end of the input.
+    async fn fill_pending(&mut self) -> Result<bool, ParsingError> {
+        loop {
+            match self.reader.fill_buf().await {
+                Ok(buf) => {
+                    let read = buf.len();
+                    self.pending.extend_from_slice(buf);
+                    self.reader.consume(read);
+                    return Ok(read == 0);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+// SPDX-License-Identifier: MPL-2.0
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom},
+    path::Path,
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use super::{DumpParser, ParserOptions, ParsingError};
+
+/// A reader returned by [`decompress`] and [`open_decompressed`].
+pub type DecompressedReader = Box<dyn BufRead + Send>;
+
+/// Compression formats of the dumps published by Wikimedia.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Plain XML.
+    None,
+    /// bzip2, including multistream dumps (concatenated streams).
+    Bzip2,
+    /// gzip, including concatenated members.
+    Gzip,
+    /// Zstandard, including multiple frames.
+    Zstd,
+    /// xz, including concatenated streams.
+    Xz,
+    /// A 7z archive containing the dump as its first file.
+    SevenZip,
+}
+
+impl Compression {
+    /// Number of bytes [`detect`](Compression::detect) needs to recognise every format.
+    pub const MAGIC_LEN: usize = 6;
+
+    /// Detects the compression format from the first bytes of a file.
+    ///
+    /// Anything that isn't recognised is assumed to be uncompressed.
+    pub fn detect(magic: &[u8]) -> Self {
+        match magic {
+            [b'B', b'Z', b'h', b'1'..=b'9', ..] => Compression::Bzip2,
+            [0x1f, 0x8b, ..] => Compression::Gzip,
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Compression::Zstd,
+            [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Compression::Xz,
+            [b'7', b'z', 0xbc, 0xaf, 0x27, 0x1c, ..] => Compression::SevenZip,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// Wraps `reader` in a decoder for the compression format detected from its first bytes.
+///
+/// 7z archives need random access, they can only be read by [`open_decompressed`].
+///
+/// # Errors
+///
+/// Returns an [`io::Error`] if the first bytes can't be read, the decoder can't be set up
+/// or the input is a 7z archive.
+pub fn decompress<R: BufRead + Send + 'static>(reader: R) -> io::Result<DecompressedReader> {
+    let (compression, reader) = sniff(reader)?;
+    if compression == Compression::SevenZip {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "7z archives can only be read from a file, not a stream",
+        ));
+    }
+    decoder(compression, reader)
+}
+
+/// Opens the dump at `path` and decompresses it according to its first bytes.
+///
+/// The file extension is ignored. The first file of a 7z archive is decompressed
+/// on a background thread.
+///
+/// # Errors
+///
+/// Returns an [`io::Error`] if the file can't be opened or the decoder can't be set up.
+pub fn open_decompressed(path: impl AsRef<Path>) -> io::Result<DecompressedReader> {
+    let mut file = File::open(path)?;
+    let mut magic = Vec::with_capacity(Compression::MAGIC_LEN);
+    (&mut file)
+        .take(Compression::MAGIC_LEN as u64)
+        .read_to_end(&mut magic)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    match Compression::detect(&magic) {
+        Compression::SevenZip => seven_zip(file),
+        compression => decoder(compression, BufReader::new(file)),
+    }
+}
+
+impl DumpParser<DecompressedReader> {
+    /// Opens the dump at `path`, decompressing it if necessary, and parses the `<siteinfo>`
+    /// header.
+    ///
+    /// The compression is detected from the first bytes of the file, see [`open_decompressed`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParsingError`] if the file can't be opened or decompressed, the XML is
+    /// malformed or the `<siteinfo>` block cannot be parsed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ParsingError> {
+        Self::open_with_options(path, ParserOptions::default())
+    }
+
+    /// Opens the dump at `path` like [`DumpParser::open`], using the given options.
+    pub fn open_with_options(
+        path: impl AsRef<Path>,
+        options: ParserOptions,
+    ) -> Result<Self, ParsingError> {
+        Self::with_options(open_decompressed(path)?, options)
+    }
+
+    /// Creates a parser for a possibly compressed stream, e.g. stdin.
+    ///
+    /// The compression is detected from the first bytes of the stream, see [`decompress`].
+    pub fn from_reader_autodetect<R: BufRead + Send + 'static>(
+        reader: R,
+    ) -> Result<Self, ParsingError> {
+        Self::from_reader_autodetect_with_options(reader, ParserOptions::default())
+    }
+
+    /// Creates a parser like [`DumpParser::from_reader_autodetect`], using the given options.
+    pub fn from_reader_autodetect_with_options<R: BufRead + Send + 'static>(
+        reader: R,
+        options: ParserOptions,
+    ) -> Result<Self, ParsingError> {
+        Self::with_options(decompress(reader)?, options)
+    }
+}
+
+/// Reads the magic bytes and puts them back in front of the rest of the stream.
+fn sniff<R: BufRead>(mut reader: R) -> io::Result<(Compression, impl BufRead)> {
+    let mut magic = Vec::with_capacity(Compression::MAGIC_LEN);
+    (&mut reader)
+        .take(Compression::MAGIC_LEN as u64)
+        .read_to_end(&mut magic)?;
+    Ok((
+        Compression::detect(&magic),
+        Cursor::new(magic).chain(reader),
+    ))
+}
+
+fn decoder<R: BufRead + Send + 'static>(
+    compression: Compression,
+    reader: R,
+) -> io::Result<DecompressedReader> {
+    Ok(match compression {
+        Compression::None => Box::new(reader),
+        // the single-stream decoders stop silently after the first stream of a multistream dump
+        Compression::Bzip2 => Box::new(BufReader::new(bzip2::bufread::MultiBzDecoder::new(reader))),
+        Compression::Gzip => Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader))),
+        Compression::Zstd => Box::new(BufReader::new(zstd::Decoder::with_buffer(reader)?)),
+        Compression::Xz => Box::new(BufReader::new(xz2::bufread::XzDecoder::new_multi_decoder(
+            reader,
+        ))),
+        Compression::SevenZip => unreachable!("7z archives are not streams"),
+    })
+}
+
+/// Chunks of decompressed data in flight between the 7z decoder thread and the parser.
+const SEVEN_ZIP_CHANNEL_CAPACITY: usize = 4;
+const SEVEN_ZIP_CHUNK_SIZE: usize = 1 << 16;
+
+/// Decompresses the first file of the archive on a background thread.
+///
+/// sevenz-rust only hands out the files of an archive to a callback, so the decoder runs
+/// until the parser stops asking for data.
+fn seven_zip(file: File) -> io::Result<DecompressedReader> {
+    let len = file.metadata()?.len();
+    let mut archive = sevenz_rust::SevenZReader::new(file, len, sevenz_rust::Password::empty())
+        .map_err(io::Error::other)?;
+
+    let (sender, receiver) = mpsc::sync_channel(SEVEN_ZIP_CHANNEL_CAPACITY);
+    thread::Builder::new()
+        .name("7z decoder".to_owned())
+        .spawn(move || {
+            let result = archive.for_each_entries(|entry, reader| {
+                if entry.is_directory || !entry.has_stream {
+                    return Ok(true);
+                }
+                loop {
+                    let mut chunk = Vec::with_capacity(SEVEN_ZIP_CHUNK_SIZE);
+                    let result = reader
+                        .take(SEVEN_ZIP_CHUNK_SIZE as u64)
+                        .read_to_end(&mut chunk);
+                    let done = matches!(result, Ok(0) | Err(_));
+                    // the parser may be gone, that's fine
+                    if sender.send(result.map(|_| chunk)).is_err() || done {
+                        // only the first file is the dump
+                        return Ok(false);
+                    }
+                }
+            });
+            if let Err(e) = result {
+                let _ = sender.send(Err(io::Error::other(e)));
+            }
+        })?;
+
+    Ok(Box::new(ChannelReader {
+        receiver,
+        chunk: Vec::new(),
+        position: 0,
+    }))
+}
+
+/// Reads the chunks sent by the 7z decoder thread.
+struct ChannelReader {
+    receiver: Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for ChannelReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.position == self.chunk.len() {
+            match self.receiver.recv() {
+                Ok(Ok(chunk)) if chunk.is_empty() => return Ok(&[]),
+                Ok(Ok(chunk)) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                Ok(Err(e)) => return Err(e),
+                // the decoder thread is done
+                Err(_) => return Ok(&[]),
+            }
+        }
+        Ok(&self.chunk[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position = (self.position + amount).min(self.chunk.len());
+    }
+}
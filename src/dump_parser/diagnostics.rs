@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: MPL-2.0
+use std::{fmt::Display, sync::Arc};
+
+use compact_str::CompactString;
+
+use super::{ParsingError, ParsingErrorKind};
+
+/// Where in the dump a problem was found.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Location {
+    /// Byte offset in the (decompressed) XML stream.
+    ///
+    /// This is the position of the reader directly after the XML event that caused the problem.
+    pub position: u64,
+    /// Title of the page being parsed, if it was already known at that point.
+    pub page_title: Option<CompactString>,
+    /// Id of the revision being parsed, if it was already known at that point.
+    pub revision_id: Option<i32>,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "byte {}", self.position)?;
+        if let Some(title) = &self.page_title {
+            write!(f, ", page {:?}", title)?;
+        }
+        if let Some(id) = self.revision_id {
+            write!(f, ", revision {}", id)?;
+        }
+        Ok(())
+    }
+}
+
+/// A problem with the input that the parser recovered from.
+///
+/// Diagnostics are reported through [`ParserOptions::on_diagnostic`](super::ParserOptions::on_diagnostic)
+/// and collected per page, see [`DumpParser::diagnostics`](super::DumpParser::diagnostics).
+/// In [strict mode](super::ParserOptions::strict) they are returned as
+/// [`ParsingErrorKind::Malformed`] errors instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub location: Location,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at {})", self.kind, self.location)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum DiagnosticKind {
+    /// A tag name is not valid UTF-8. Parsing continues, but the results may be incorrect
+    /// if there is more than one distinct non-UTF-8 tag.
+    #[error("non-UTF-8 tag `{0}`")]
+    NonUtf8Tag(String),
+    /// A tag is missing a mandatory attribute, the tag is ignored.
+    #[error("missing expected attribute `{attribute}` for tag `{tag}`")]
+    MissingAttribute {
+        attribute: &'static str,
+        tag: &'static str,
+    },
+    /// An end tag without a corresponding start tag, the end tag is ignored.
+    #[error("unexpected end tag `{0}`")]
+    UnexpectedEndTag(String),
+    /// An end tag that does not match the innermost open tag.
+    #[error("mismatched tags, expected end of {expected} but found `{actual}`")]
+    MismatchedTags { expected: String, actual: String },
+    /// A revision is missing a mandatory field and has been dropped.
+    #[error("revision is missing mandatory field `{0}` and was dropped")]
+    MissingField(&'static str),
+    /// A namespace key in the `<siteinfo>` is not a number, the namespace is ignored.
+    #[error("invalid namespace key `{0}`")]
+    InvalidNamespaceKey(String),
+    /// A `case` tag or attribute in the `<siteinfo>` has an unknown value, it is ignored.
+    #[error("invalid case rule `{0}`")]
+    InvalidCase(String),
+    /// The `version` attribute of the `<mediawiki>` tag is not a version number.
+    #[error("invalid schema version `{0}`")]
+    InvalidSchemaVersion(String),
+    /// The `<ns>` of a page is not a number, namespace 0 is used instead.
+    #[error("invalid page namespace `{0}`")]
+    InvalidNamespace(String),
+    /// A page id is not a number, the id is ignored.
+    #[error("invalid page id `{0}`")]
+    InvalidPageId(String),
+    /// A revision id is not a number, a random negative id is used instead.
+    #[error("invalid revision id `{0}`")]
+    InvalidRevisionId(String),
+    /// A revision timestamp could not be parsed, the revision will be dropped.
+    #[error("invalid revision timestamp `{0}`")]
+    InvalidTimestamp(String),
+    /// A contributor id is not a number, the id is ignored.
+    #[error("invalid contributor id `{0}`")]
+    InvalidContributorId(String),
+    /// A SHA1 hash does not have the expected length, the hash is ignored.
+    #[error("invalid sha1 hash `{0}`")]
+    InvalidSha1(String),
+    /// The SHA1 hash in the dump does not match the revision text, the computed hash is used.
+    ///
+    /// Only checked with [`ParserOptions::verify_sha1`](super::ParserOptions::verify_sha1).
+    #[error("sha1 `{expected}` does not match the revision text (`{actual}`)")]
+    Sha1Mismatch { expected: String, actual: String },
+    /// An entity reference other than the predefined XML entities, it is kept as-is in the text.
+    #[error("unknown entity `&{0};`")]
+    UnknownEntity(String),
+    /// A revision id appears more than once in the same page. The revision is kept.
+    #[error("duplicate revision id {0}")]
+    DuplicateRevisionId(i32),
+}
+
+/// Callback for [`ParserOptions::on_diagnostic`](super::ParserOptions::on_diagnostic).
+pub type DiagnosticCallback = Arc<dyn Fn(&Diagnostic) + Send + Sync>;
+
+/// Collects the diagnostics of the current page and forwards them to the user callback,
+/// or turns them into errors in strict mode.
+///
+/// Also tracks the page title and revision id that end up in the [`Location`] of a diagnostic.
+#[derive(Default)]
+pub(crate) struct Diagnostics {
+    pub(crate) callback: Option<DiagnosticCallback>,
+    pub(crate) strict: bool,
+    pub(crate) collected: Vec<Diagnostic>,
+
+    pub(crate) page_title: Option<CompactString>,
+    pub(crate) revision_id: Option<i32>,
+    /// Added to reader positions, for parsers that start in the middle of the stream
+    pub(crate) position_offset: u64,
+}
+
+impl Diagnostics {
+    pub(crate) fn location(&self, position: u64) -> Location {
+        Location {
+            position: self.position_offset + position,
+            page_title: self.page_title.clone(),
+            revision_id: self.revision_id,
+        }
+    }
+
+    /// Reports a problem with the input.
+    ///
+    /// Returns an error instead in strict mode, which the caller must propagate.
+    pub(crate) fn report(
+        &mut self,
+        kind: DiagnosticKind,
+        position: u64,
+    ) -> Result<(), ParsingError> {
+        let diagnostic = Diagnostic {
+            location: self.location(position),
+            kind,
+        };
+        if self.strict {
+            tracing::error!(message = "Malformed input", %diagnostic);
+            return Err(
+                ParsingError::from(ParsingErrorKind::Malformed(diagnostic.kind))
+                    .at(diagnostic.location),
+            );
+        }
+        tracing::warn!(message = "Recovered from malformed input", %diagnostic);
+
+        if let Some(callback) = &self.callback {
+            callback(&diagnostic);
+        }
+        self.collected.push(diagnostic);
+        Ok(())
+    }
+
+    /// Resets the collected diagnostics and the location context at the start of a new page.
+    pub(crate) fn start_page(&mut self) {
+        self.collected.clear();
+        self.page_title = None;
+        self.revision_id = None;
+    }
+}
@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MPL-2.0
+use std::{any::type_name_of_val, fmt::Debug, fs::File, path::Path, sync::Arc};
+
+use memmap2::Mmap;
+
+use super::{
+    configure_reader, Diagnostic, Event, Page, ParserOptions, ParserState, ParsingError,
+    SharedBuffer, SiteInfo, XmlEvents,
+};
+
+/// A parser for uncompressed dumps in memory, usually a memory-mapped file.
+///
+/// Produces the same pages, diagnostics and errors as [`DumpParser`](super::DumpParser),
+/// but reads the XML events straight from the input instead of copying them into a buffer.
+/// Revision text that needs no entity unescaping (no `&lt;`, `&amp;`, ...) isn't copied
+/// either: it becomes a [`Text::Shared`](super::Text::Shared) slice of the input. The
+/// analysis lowercases such text directly into the source string of its
+/// [`ArcSubstring`](crate::algorithm::ArcSubstring)s, so the text is copied only once.
+///
+/// Shared texts keep the whole input alive, e.g. the mapping of the file. Convert them with
+/// [`Text::as_str`](super::Text::as_str) to keep only some texts after the parser is gone.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use wikiwho::{algorithm::PageAnalysis, dump_parser::MmapDumpParser};
+///
+/// let mut parser = MmapDumpParser::open("dump.xml").unwrap();
+/// while let Some(page) = parser.parse_page().unwrap() {
+///     let analysis = PageAnalysis::analyse_page(&page.revisions).unwrap();
+///     println!("{}: {} tokens", page.title, analysis.words.len());
+/// }
+/// ```
+pub struct MmapDumpParser {
+    input: SharedBuffer,
+    /// position of the next event in `input`
+    position: usize,
+    state: ParserState,
+}
+
+impl Debug for MmapDumpParser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MmapDumpParser")
+            .field("input", &type_name_of_val(&self.input))
+            .field("input.len", &(*self.input).as_ref().len())
+            .field("position", &self.position)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl MmapDumpParser {
+    /// Memory-maps the uncompressed dump at `path` and parses the `<siteinfo>` header.
+    ///
+    /// The file must not be modified while it is mapped, i.e. as long as the parser or any
+    /// [`Text::Shared`](super::Text::Shared) of its pages exists. A truncated or modified
+    /// file can crash the program.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParsingError`] if the file can't be mapped, the XML is malformed or
+    /// the `<siteinfo>` block cannot be parsed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ParsingError> {
+        Self::open_with_options(path, ParserOptions::default())
+    }
+
+    /// Memory-maps the dump at `path` like [`MmapDumpParser::open`], using the given options.
+    pub fn open_with_options(
+        path: impl AsRef<Path>,
+        options: ParserOptions,
+    ) -> Result<Self, ParsingError> {
+        let file = File::open(path)?;
+        // SAFETY: undefined behaviour if the file is modified while mapped, which we can't
+        // prevent. This is documented on `open`, like for every other user of mmap.
+        let mmap = unsafe { Mmap::map(&file)? };
+        Self::from_buffer(Arc::new(mmap), options)
+    }
+
+    /// Creates a parser for a dump that is already in memory, e.g. mapped by the caller.
+    pub fn from_buffer(input: SharedBuffer, options: ParserOptions) -> Result<Self, ParsingError> {
+        let mut state = ParserState::new(options);
+        state.pending_text.input = Some(input.clone());
+
+        let mut events = SliceEvents::new((*input).as_ref(), 0);
+        state
+            .parse_site_info(&mut events)
+            .map_err(|e| state.locate(e, events.position()))?;
+        let position = events.position() as usize;
+
+        Ok(Self {
+            input,
+            position,
+            state,
+        })
+    }
+
+    /// Returns the site information parsed from the `<siteinfo>` header.
+    pub fn site_info(&self) -> &SiteInfo {
+        &self.state.site_info
+    }
+
+    /// Returns the diagnostics reported while parsing the most recent page,
+    /// or the `<siteinfo>` header if no page has been parsed yet.
+    ///
+    /// See [`DumpParser::diagnostics`](super::DumpParser::diagnostics).
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.state.diagnostics.collected
+    }
+
+    /// Returns the number of bytes of the input parsed so far.
+    pub fn bytes_consumed(&self) -> u64 {
+        self.position as u64
+    }
+
+    /// Returns the next `<page>` element.
+    ///
+    /// Has the same return values as [`DumpParser::parse_page`](super::DumpParser::parse_page).
+    pub fn parse_page(&mut self) -> Result<Option<Page>, ParsingError> {
+        // the reader only lives for one page, it can't borrow from the `input` we own otherwise
+        let input = (*self.input).as_ref();
+        let mut events = SliceEvents::new(&input[self.position..], self.position as u64);
+        let result = self.state.parse_page(&mut events);
+        self.position = events.position() as usize;
+        result
+    }
+}
+
+/// Events borrowed from a slice of the input, without copying them into a buffer.
+struct SliceEvents<'a> {
+    reader: quick_xml::Reader<&'a [u8]>,
+    /// position of the slice in the input
+    offset: u64,
+}
+
+impl<'a> SliceEvents<'a> {
+    fn new(data: &'a [u8], offset: u64) -> Self {
+        let mut reader = quick_xml::Reader::from_reader(data);
+        configure_reader(&mut reader);
+        // a slice starting between pages hasn't seen the opening `<mediawiki>` tag
+        reader.config_mut().allow_unmatched_ends = true;
+        Self { reader, offset }
+    }
+}
+
+impl XmlEvents for SliceEvents<'_> {
+    fn next_event(&mut self) -> Result<(Event<'_>, u64), quick_xml::Error> {
+        let event = self.reader.read_event()?;
+        Ok((event, self.position()))
+    }
+
+    fn position(&self) -> u64 {
+        self.offset + self.reader.buffer_position()
+    }
+}
@@ -1,17 +1,41 @@
 // SPDX-License-Identifier: MPL-2.0
+#[cfg(feature = "serde")]
+pub mod api_json;
+#[cfg(feature = "async")]
+mod async_parser;
+#[cfg(feature = "compression")]
+mod compression;
+mod diagnostics;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod parallel;
+pub mod stats;
+mod text_provider;
 mod types;
+mod writer;
+#[cfg(feature = "async")]
+pub use async_parser::AsyncDumpParser;
+#[cfg(feature = "compression")]
+pub use compression::{decompress, open_decompressed, Compression, DecompressedReader};
+pub use diagnostics::{Diagnostic, DiagnosticCallback, DiagnosticKind, Location};
+#[cfg(feature = "mmap")]
+pub use mmap::MmapDumpParser;
+pub use parallel::ParallelDumpReader;
+pub use text_provider::{FillTexts, RevisionTextProvider};
 pub use types::*;
+pub use writer::DumpWriter;
 
 use std::{
-    any::type_name_of_val, borrow::Cow, collections::HashMap, convert::Infallible, fmt::Debug,
-    io::BufRead,
+    any::type_name_of_val, borrow::Cow, collections::HashSet, fmt::Debug, io::BufRead, sync::Arc,
 };
 
 use compact_str::CompactString;
-use quick_xml::events::{BytesEnd, BytesStart};
+use quick_xml::events::{BytesEnd, BytesRef, BytesStart, BytesText, Event};
 use rand::RngExt;
 use tracing::instrument;
 
+use diagnostics::Diagnostics;
+
 // we normally don't retrieve the value of the tags, so this is the most efficient backend
 type TagStringInterner = string_interner::StringInterner<string_interner::backend::BucketBackend>;
 
@@ -21,24 +45,33 @@ type TagStringInterner = string_interner::StringInterner<string_interner::backen
 enum Tag {
     MediaWiki,  // <mediawiki version="0.11" ...other attributes>...</mediawiki> is the root tag
     SiteInfo, // <siteinfo><dbname>...</dbname><namespaces>...</namespaces> ...other tags</siteinfo>
+    Sitename, // <sitename>Wiktionary</sitename>
     DbName,   // <dbname>dewiktionary</dbname>
+    Base,     // <base>https://de.wiktionary.org/wiki/Wiktionary:Hauptseite</base>
+    Generator, // <generator>MediaWiki 1.43.0-wmf.20</generator>
+    Case,     // <case>case-sensitive</case>
     Namespaces, // <namespaces><namespace key="0" /> ...more namespace tags</namespaces>
-    Namespace(String), // <namespace key="1">Diskussion</namespace>
-    Page,     // <page>...tags are (title, ns, id, revision)</page>
-    Title,    // <title>blah</title>
-    Ns,       // <ns>0</ns>
-    Id,       // <id>500</id>
+    Namespace(String, Option<String>), // <namespace key="1" case="case-sensitive">Diskussion</namespace>
+    Page,                              // <page>...tags are (title, ns, id, revision)</page>
+    Title,                             // <title>blah</title>
+    Ns,                                // <ns>0</ns>
+    Id,                                // <id>500</id>
     Revision, // <revision>...tags are (id, timestamp, contributor, text, sha1, comment, )</revision>
     Timestamp, // <timestamp>2003-12-05T06:41:50Z</timestamp>
-    Contributor, // <contributor><username>blah</username><id>500</id></contributor>
+    Contributor(bool), // <contributor>...</contributor> or <contributor deleted="deleted" />
     Username, // <username>blah</username>
     Ip,       // sometimes: <contributor><ip>123.456.789.122</ip></contributor>
     // Text's sha1 attribute seems to be preferred over the sha1 tag (https://github.com/mediawiki-utilities/python-mwxml/blob/2b477be6aa9794064d03b5be38c7759d1570488b/mwxml/iteration/revision.py#L83-L96)
     Text(bool, Option<String>), // <text bytes="20" sha1="3h3w...">blah</text> or <text bytes="20" sha1="3h3w..." deleted="deleted" />
     // Sha1 hash is base36 encoded (0-padded to 31 characters)
-    Sha1,                                    // <sha1>3h3w...</sha1>
-    Comment,                                 // <comment>blah</comment>
-    Minor,                                   // <minor />
+    Sha1,          // <sha1>3h3w...</sha1>
+    Comment(bool), // <comment>blah</comment> or <comment deleted="deleted" />
+    Minor,         // <minor />
+    Model,         // <model>wikitext</model>
+    Format,        // <format>text/x-wiki</format>
+    // additional slots of multi-content revisions, the main slot uses the text tag of the revision
+    Content, // <content><role>mediainfo</role><model>...</model><format>...</format><text>...</text></content>
+    Role,    // <role>mediainfo</role>
     Unknown(string_interner::DefaultSymbol), // any other tag
 }
 
@@ -47,16 +80,32 @@ impl Debug for Tag {
         match self {
             Tag::MediaWiki => write!(f, "<mediawiki>"),
             Tag::SiteInfo => write!(f, "<siteinfo>"),
+            Tag::Sitename => write!(f, "<sitename>"),
             Tag::DbName => write!(f, "<dbname>"),
+            Tag::Base => write!(f, "<base>"),
+            Tag::Generator => write!(f, "<generator>"),
+            Tag::Case => write!(f, "<case>"),
             Tag::Namespaces => write!(f, "<namespaces>"),
-            Tag::Namespace(key) => write!(f, "<namespace key={}>", key),
+            Tag::Namespace(key, case) => {
+                write!(f, "<namespace key={}", key)?;
+                if let Some(case) = case {
+                    write!(f, " case={}", case)?;
+                }
+                write!(f, ">")
+            }
             Tag::Page => write!(f, "<page>"),
             Tag::Title => write!(f, "<title>"),
             Tag::Ns => write!(f, "<ns>"),
             Tag::Id => write!(f, "<id>"),
             Tag::Revision => write!(f, "<revision>"),
             Tag::Timestamp => write!(f, "<timestamp>"),
-            Tag::Contributor => write!(f, "<contributor>"),
+            Tag::Contributor(deleted) => {
+                if *deleted {
+                    write!(f, "<contributor deleted>")
+                } else {
+                    write!(f, "<contributor>")
+                }
+            }
             Tag::Username => write!(f, "<username>"),
             Tag::Ip => write!(f, "<ip>"),
             Tag::Text(deleted, sha1) => {
@@ -70,8 +119,18 @@ impl Debug for Tag {
                 write!(f, ">")
             }
             Tag::Sha1 => write!(f, "<sha1>"),
-            Tag::Comment => write!(f, "<comment>"),
+            Tag::Comment(deleted) => {
+                if *deleted {
+                    write!(f, "<comment deleted>")
+                } else {
+                    write!(f, "<comment>")
+                }
+            }
             Tag::Minor => write!(f, "<minor>"),
+            Tag::Model => write!(f, "<model>"),
+            Tag::Format => write!(f, "<format>"),
+            Tag::Content => write!(f, "<content>"),
+            Tag::Role => write!(f, "<role>"),
             // TODO: find a way to retrieve the string for the interned symbol
             Tag::Unknown(tag) => write!(f, "<unknown tag - interned symbol: {:?}>", tag),
         }
@@ -94,6 +153,17 @@ enum TagReadingError<T> {
 #[derive(Debug, thiserror::Error)]
 struct NonUtf8Tag<T>(T);
 
+/// Whether a `<contributor>` or `<comment>` tag was hidden, i.e. has a `deleted="deleted"` attribute.
+fn has_deleted_attribute(e: &BytesStart) -> Result<bool, quick_xml::Error> {
+    for attr in e.attributes() {
+        let attr = attr.map_err(quick_xml::Error::from)?;
+        if attr.key.as_ref() == b"deleted" {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
 impl Tag {
     fn from_start_bytes(
         e: &BytesStart,
@@ -102,19 +172,29 @@ impl Tag {
         match e.name().as_ref() {
             b"mediawiki" => Ok(Tag::MediaWiki),
             b"siteinfo" => Ok(Tag::SiteInfo),
+            b"sitename" => Ok(Tag::Sitename),
             b"dbname" => Ok(Tag::DbName),
+            b"base" => Ok(Tag::Base),
+            b"generator" => Ok(Tag::Generator),
+            b"case" => Ok(Tag::Case),
             b"namespaces" => Ok(Tag::Namespaces),
             b"namespace" => {
+                let mut key = None;
+                let mut case = None;
+
                 for attr in e.attributes() {
                     let attr = attr.map_err(quick_xml::Error::from)?;
-
-                    if attr.key.as_ref() == b"key" {
-                        let key = attr.unescape_value()?;
-                        return Ok(Tag::Namespace(key.into_owned()));
+                    match attr.key.as_ref() {
+                        b"key" => key = Some(attr.unescape_value()?.into_owned()),
+                        b"case" => case = Some(attr.unescape_value()?.into_owned()),
+                        _ => {}
                     }
                 }
 
-                Err(TagReadingError::MissingAttribute("key", "namespace"))
+                match key {
+                    Some(key) => Ok(Tag::Namespace(key, case)),
+                    None => Err(TagReadingError::MissingAttribute("key", "namespace")),
+                }
             }
             b"page" => Ok(Tag::Page),
             b"title" => Ok(Tag::Title),
@@ -122,7 +202,7 @@ impl Tag {
             b"id" => Ok(Tag::Id),
             b"revision" => Ok(Tag::Revision),
             b"timestamp" => Ok(Tag::Timestamp),
-            b"contributor" => Ok(Tag::Contributor),
+            b"contributor" => Ok(Tag::Contributor(has_deleted_attribute(e)?)),
             b"username" => Ok(Tag::Username),
             b"ip" => Ok(Tag::Ip),
             b"text" => {
@@ -148,8 +228,12 @@ impl Tag {
                 Ok(Tag::Text(deleted, sha1.map(Cow::into_owned)))
             }
             b"sha1" => Ok(Tag::Sha1),
-            b"comment" => Ok(Tag::Comment),
+            b"comment" => Ok(Tag::Comment(has_deleted_attribute(e)?)),
             b"minor" => Ok(Tag::Minor),
+            b"model" => Ok(Tag::Model),
+            b"format" => Ok(Tag::Format),
+            b"content" => Ok(Tag::Content),
+            b"role" => Ok(Tag::Role),
             _ => {
                 let name = e.name().into_inner();
 
@@ -172,22 +256,30 @@ impl Tag {
         match (self, e.name().as_ref()) {
             (Tag::MediaWiki, b"mediawiki") => Ok(true),
             (Tag::SiteInfo, b"siteinfo") => Ok(true),
+            (Tag::Sitename, b"sitename") => Ok(true),
             (Tag::DbName, b"dbname") => Ok(true),
+            (Tag::Base, b"base") => Ok(true),
+            (Tag::Generator, b"generator") => Ok(true),
+            (Tag::Case, b"case") => Ok(true),
             (Tag::Namespaces, b"namespaces") => Ok(true),
-            (Tag::Namespace(_), b"namespace") => Ok(true),
+            (Tag::Namespace(_, _), b"namespace") => Ok(true),
             (Tag::Page, b"page") => Ok(true),
             (Tag::Title, b"title") => Ok(true),
             (Tag::Ns, b"ns") => Ok(true),
             (Tag::Id, b"id") => Ok(true),
             (Tag::Revision, b"revision") => Ok(true),
             (Tag::Timestamp, b"timestamp") => Ok(true),
-            (Tag::Contributor, b"contributor") => Ok(true),
+            (Tag::Contributor(_), b"contributor") => Ok(true),
             (Tag::Username, b"username") => Ok(true),
             (Tag::Ip, b"ip") => Ok(true),
             (Tag::Text(_, _), b"text") => Ok(true),
             (Tag::Sha1, b"sha1") => Ok(true),
-            (Tag::Comment, b"comment") => Ok(true),
+            (Tag::Comment(_), b"comment") => Ok(true),
             (Tag::Minor, b"minor") => Ok(true),
+            (Tag::Model, b"model") => Ok(true),
+            (Tag::Format, b"format") => Ok(true),
+            (Tag::Content, b"content") => Ok(true),
+            (Tag::Role, b"role") => Ok(true),
             (Tag::Unknown(expected_tag), tag_name) => {
                 if let Ok(tag) = std::str::from_utf8(tag_name) {
                     let tag = tag_interner.get_or_intern(tag);
@@ -202,16 +294,95 @@ impl Tag {
     }
 }
 
+/// Returns the page title without the namespace prefix and with underscores replaced by spaces.
+fn page_title(title: &str) -> CompactString {
+    fn normalize_title(title: &str) -> Cow<'_, str> {
+        if title.contains("_") {
+            title.replace("_", " ").into()
+        } else {
+            title.into()
+        }
+    }
+
+    if let Some(title) = title.split_once(":") {
+        // split off the namespace
+        CompactString::from(normalize_title(title.1))
+    } else {
+        CompactString::from(normalize_title(title))
+    }
+}
+
+/// Parses a base36 encoded SHA1 hash as used in dumps.
+fn parse_sha1(text: &str) -> Option<Sha1Hash> {
+    let bytes: [u8; 31] = text.as_bytes().try_into().ok()?;
+    Some(Sha1Hash(bytes))
+}
+
+/// Parses a revision timestamp, either `2003-12-05T06:41:50Z` or `20031205064150`.
+fn parse_timestamp(text: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    // Source: https://github.com/mediawiki-utilities/python-mwtypes/blob/523a93f98fe1372938fc15872b5abb1f267cc643/mwtypes/timestamp.py#L12
+    const TIMESTAMP_FORMAT_LONG: &str = "%Y-%m-%dT%H:%M:%SZ";
+    const TIMESTAMP_FORMAT_SHORT: &str = "%Y%m%d%H%M%S";
+
+    chrono::NaiveDateTime::parse_from_str(text, TIMESTAMP_FORMAT_SHORT)
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(text, TIMESTAMP_FORMAT_LONG))
+        .map(|dt| chrono::DateTime::from_naive_utc_and_offset(dt, chrono::Utc))
+        .ok()
+}
+
 #[derive(Debug)]
 struct RevisionBuilder {
     id: Option<i32>,
     timestamp: Option<chrono::DateTime<chrono::Utc>>,
     contributor_name: Option<CompactString>,
     contributor_id: Option<i32>,
+    contributor_deleted: bool,
     text: Option<Text>,
     sha1: Option<Sha1Hash>,
-    comment: Option<CompactString>,
+    comment: Option<Comment>,
     minor: bool,
+    model: Option<CompactString>,
+    format: Option<CompactString>,
+    slots: Vec<SlotBuilder>,
+}
+
+/// A `<content>` element of a multi-content revision.
+#[derive(Debug, Default)]
+struct SlotBuilder {
+    role: Option<CompactString>,
+    model: Option<CompactString>,
+    format: Option<CompactString>,
+    text: Option<Text>,
+    sha1: Option<Sha1Hash>,
+}
+
+impl SlotBuilder {
+    fn set_text(
+        &mut self,
+        text: Text,
+        sha1: Option<&str>,
+        diagnostics: &mut Diagnostics,
+        position: u64,
+    ) -> Result<(), ParsingError> {
+        self.text = Some(text);
+        if let Some(sha1) = sha1 {
+            self.sha1 = parse_sha1(sha1);
+            if self.sha1.is_none() {
+                diagnostics.report(DiagnosticKind::InvalidSha1(sha1.to_string()), position)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn try_build(self) -> Option<Slot> {
+        Some(Slot {
+            role: self.role?,
+            model: self.model,
+            format: self.format,
+            text: self.text?,
+            sha1: self.sha1,
+        })
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -225,10 +396,14 @@ impl RevisionBuilder {
             timestamp: None,
             contributor_name: None,
             contributor_id: None,
+            contributor_deleted: false,
             text: None,
             sha1: None,
             comment: None,
             minor: false,
+            model: None,
+            format: None,
+            slots: Vec::new(),
         }
     }
 
@@ -239,28 +414,207 @@ impl RevisionBuilder {
         if self.timestamp.is_none() {
             return Err(BuildRevisionError("timestamp", self.into()));
         }
-        if self.contributor_name.is_none() {
+        if self.contributor_name.is_none() && !self.contributor_deleted {
             return Err(BuildRevisionError("contributor_name", self.into()));
         }
         if self.text.is_none() {
             return Err(BuildRevisionError("text", self.into()));
         }
+        if self.slots.iter().any(|slot| slot.role.is_none()) {
+            return Err(BuildRevisionError("content role", self.into()));
+        }
+        if self.slots.iter().any(|slot| slot.text.is_none()) {
+            return Err(BuildRevisionError("content text", self.into()));
+        }
 
         Ok(Revision {
             id: self.id.unwrap(),
             timestamp: self.timestamp.unwrap(),
-            contributor: Contributor {
-                username: self.contributor_name.unwrap(),
-                id: self.contributor_id,
+            contributor: if self.contributor_deleted {
+                Contributor::deleted()
+            } else {
+                Contributor {
+                    username: self.contributor_name.unwrap(),
+                    id: self.contributor_id,
+                    deleted: false,
+                }
             },
             text: self.text.unwrap(),
             sha1: self.sha1,
             comment: self.comment,
             minor: self.minor,
+            model: self.model,
+            format: self.format,
+            slots: self
+                .slots
+                .into_iter()
+                .filter_map(SlotBuilder::try_build)
+                .collect(),
         })
     }
 }
 
+/// Text content of the current tag, assembled from text events and entity references.
+///
+/// quick-xml reports entity and character references (e.g. `&lt;` or `&#228;`) as
+/// separate events, so the text of a single tag can span several events.
+#[derive(Default)]
+struct PendingText {
+    text: String,
+    /// the text as a slice of `input`, as long as it consists of a single text event
+    shared: Option<SharedStr>,
+    /// reader position after the last event that contributed to the text
+    position: u64,
+    /// buffer the events are borrowed from, text in it is shared instead of copied
+    input: Option<SharedBuffer>,
+}
+
+impl PendingText {
+    fn push_text(&mut self, e: &BytesText, position: u64) -> Result<(), quick_xml::Error> {
+        let text = e.decode()?;
+        if self.text.is_empty() && self.shared.is_none() {
+            self.shared = match &text {
+                Cow::Borrowed(text) => self.share(text),
+                Cow::Owned(_) => None,
+            };
+            if self.shared.is_none() {
+                // avoid copying twice for the common case of text without references
+                self.text = text.into_owned();
+            }
+        } else {
+            self.unshare();
+            self.text.push_str(&text);
+        }
+        self.position = position;
+        Ok(())
+    }
+
+    fn push_reference(
+        &mut self,
+        e: &BytesRef,
+        position: u64,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<(), ParsingError> {
+        self.unshare();
+        if let Some(ch) = e.resolve_char_ref()? {
+            self.text.push(ch);
+        } else {
+            let name = e.decode().map_err(quick_xml::Error::from)?;
+            if let Some(value) = quick_xml::escape::resolve_predefined_entity(&name) {
+                self.text.push_str(value);
+            } else {
+                // keep the reference as-is, export dumps only use the predefined entities
+                diagnostics.report(DiagnosticKind::UnknownEntity(name.to_string()), position)?;
+                self.text.push('&');
+                self.text.push_str(&name);
+                self.text.push(';');
+            }
+        }
+        self.position = position;
+        Ok(())
+    }
+
+    /// Returns `text` as a slice of the input buffer, if it is part of it.
+    fn share(&self, text: &str) -> Option<SharedStr> {
+        let input = self.input.as_ref()?;
+        let start = (**input)
+            .as_ref()
+            .element_offset(text.as_bytes().first()?)?;
+        SharedStr::new(input.clone(), start..start + text.len()).ok()
+    }
+
+    /// Copies the shared text before appending to it.
+    fn unshare(&mut self) {
+        if let Some(shared) = self.shared.take() {
+            self.text.push_str(&shared);
+        }
+    }
+
+    fn take(&mut self) -> Option<(TagText, u64)> {
+        if let Some(shared) = self.shared.take() {
+            Some((TagText::Shared(shared), self.position))
+        } else if self.text.is_empty() {
+            None
+        } else {
+            Some((
+                TagText::Owned(std::mem::take(&mut self.text)),
+                self.position,
+            ))
+        }
+    }
+}
+
+/// Text content of a tag, see [`PendingText`].
+enum TagText {
+    Owned(String),
+    Shared(SharedStr),
+}
+
+impl TagText {
+    fn into_owned(self) -> String {
+        match self {
+            TagText::Owned(text) => text,
+            TagText::Shared(text) => text.to_string(),
+        }
+    }
+
+    fn into_text(self) -> Text {
+        match self {
+            TagText::Owned(text) => Text::Normal(text),
+            TagText::Shared(text) => Text::Shared(text),
+        }
+    }
+}
+
+impl std::ops::Deref for TagText {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            TagText::Owned(text) => text,
+            TagText::Shared(text) => text,
+        }
+    }
+}
+
+impl AsRef<str> for TagText {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+/// Where the parser reads XML events from.
+trait XmlEvents {
+    /// Reads the next event, returns it with the reader position after it.
+    fn next_event(&mut self) -> Result<(Event<'_>, u64), quick_xml::Error>;
+
+    /// Returns the reader position after the last event.
+    fn position(&self) -> u64;
+}
+
+/// Events of a [`BufRead`], read into a buffer.
+struct BufferedEvents<R> {
+    reader: quick_xml::Reader<R>,
+    buf: Vec<u8>,
+}
+
+impl<R: BufRead> XmlEvents for BufferedEvents<R> {
+    fn next_event(&mut self) -> Result<(Event<'_>, u64), quick_xml::Error> {
+        let event = self.reader.read_event_into(&mut self.buf)?;
+        Ok((event, self.reader.buffer_position()))
+    }
+
+    fn position(&self) -> u64 {
+        self.reader.buffer_position()
+    }
+}
+
+fn configure_reader<R>(reader: &mut quick_xml::Reader<R>) {
+    let config = reader.config_mut();
+    // expand_empty_elements not set, take care to handle empty elements!
+    config.check_end_names = false; /* we do this anyway so avoid extra allocations */
+}
+
 /// A streaming parser for Wikimedia XML dump files.
 ///
 /// Parses pages one at a time from a `<mediawiki>` XML export.
@@ -282,109 +636,217 @@ impl RevisionBuilder {
 /// }
 /// ```
 pub struct DumpParser<R: BufRead> {
+    events: BufferedEvents<R>,
+    state: ParserState,
+}
+
+impl<R: BufRead> Debug for DumpParser<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DumpParser")
+            .field("xml_parser", &type_name_of_val(&self.events.reader))
+            // print buffer length and capacity
+            .field("buf.len", &self.events.buf.len())
+            .field("buf.capacity", &self.events.buf.capacity())
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+/// The state of a parser, independent of where the XML events come from.
+struct ParserState {
     tag_interner: TagStringInterner,
-    xml_parser: quick_xml::Reader<R>,
-    buf: Vec<u8>,
     current_path: Vec<Tag>,
     site_info: SiteInfo,
-    non_utf8_reporter: NonUtf8Reporter,
+    diagnostics: Diagnostics,
+    pending_text: PendingText,
+    verify_sha1: bool,
+    stub: bool,
+    skip_text: bool,
+    /// parses a sequence of pages without the surrounding `<mediawiki>` tags
+    is_fragment: bool,
+    /// set after a recoverable error, the rest of the broken page is skipped on the next call
+    skip_rest_of_page: bool,
 }
 
-impl<R: BufRead> Debug for DumpParser<R> {
+impl Debug for ParserState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("DumpParser")
+        f.debug_struct("ParserState")
             .field("tag_interner", &type_name_of_val(&self.tag_interner))
-            .field("xml_parser", &type_name_of_val(&self.xml_parser))
-            // print buffer length and capacity
-            .field("buf.len", &self.buf.len())
-            .field("buf.capacity", &self.buf.capacity())
             .field("current_path", &self.current_path)
             .field("site_info", &self.site_info)
+            .field("diagnostics.len", &self.diagnostics.collected.len())
             .finish()
     }
 }
 
-#[derive(Debug)]
-struct NonUtf8Reporter {
-    num_tags: usize,
+/// Options for [`DumpParser`] and [`ParallelDumpReader`].
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub struct ParserOptions {
+    /// Called for every [`Diagnostic`], i.e. every problem in the input the parser recovered from.
+    ///
+    /// With [`ParallelDumpReader`] the callback is invoked from the parser threads,
+    /// so diagnostics may not arrive in document order.
+    pub on_diagnostic: Option<DiagnosticCallback>,
+    /// Return an error for malformed input instead of recovering from it.
+    ///
+    /// Every problem that would otherwise be reported as a [`Diagnostic`], e.g. non-UTF-8 tags,
+    /// mismatched tags, revisions missing mandatory fields or duplicate revision ids,
+    /// is returned as [`ParsingErrorKind::Malformed`].
+    /// See [`ParsingError::is_recoverable`] for continuing after such an error.
+    pub strict: bool,
+    /// Verify the SHA1 hash of every revision against its text, and compute it if the dump
+    /// has none.
+    ///
+    /// A hash that does not match the text is reported as [`DiagnosticKind::Sha1Mismatch`]
+    /// and replaced with the computed one, so [`Revision::sha1`] always identifies the text.
+    /// Hashing the text of every revision is expensive, so this is disabled by default.
+    ///
+    /// Revisions with more than the main [slot](Revision::slots) are not verified, since
+    /// their hash combines the hashes of all slots.
+    pub verify_sha1: bool,
+    /// Parse a stub dump (e.g. `stub-meta-history`), which has no revision text.
+    ///
+    /// Empty `<text />` tags become [`Text::Stub`] placeholders instead of empty text,
+    /// see [`RevisionTextProvider`] for filling in the text from another source.
+    pub stub: bool,
+    /// Don't decode revision text, e.g. for collecting [statistics](stats) of a dump.
+    ///
+    /// Every `<text>` that isn't deleted becomes a [`Text::Stub`] with the size from its
+    /// `bytes` attribute, as in a [stub](ParserOptions::stub) dump. The text is still read
+    /// from the input, but neither unescaped nor copied.
+    pub skip_text: bool,
 }
 
-impl NonUtf8Reporter {
-    fn new() -> Self {
-        Self { num_tags: 0 }
+impl Debug for ParserOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParserOptions")
+            .field("on_diagnostic", &self.on_diagnostic.as_ref().map(|_| ".."))
+            .field("strict", &self.strict)
+            .field("verify_sha1", &self.verify_sha1)
+            .field("stub", &self.stub)
+            .field("skip_text", &self.skip_text)
+            .finish()
     }
+}
 
-    fn register(&mut self, name: &[u8]) {
-        self.num_tags += 1;
-
-        if self.num_tags == 1 {
-            tracing::warn!(message = "Non-UTF-8 tag in XML detected. This is not expected. Parsing will continue, but the results may be incorrect. Further non-UTF-8 tags will not be reported.", name = String::from_utf8_lossy(name).as_ref());
+impl ParserOptions {
+    pub const fn new() -> Self {
+        Self {
+            on_diagnostic: None,
+            strict: false,
+            verify_sha1: false,
+            stub: false,
+            skip_text: false,
         }
     }
 
-    fn tag_from_start_bytes(
-        &mut self,
-        e: &BytesStart,
-        tag_interner: &mut TagStringInterner,
-    ) -> Result<Tag, TagReadingError<Infallible>> {
-        match Tag::from_start_bytes(e, tag_interner) {
-            Ok(tag) => Ok(tag),
-            Err(TagReadingError::NonUtf8Tag(tag)) => {
-                self.register(e.name().as_ref());
+    pub fn on_diagnostic(mut self, callback: impl Fn(&Diagnostic) + Send + Sync + 'static) -> Self {
+        self.on_diagnostic = Some(Arc::new(callback));
+        self
+    }
 
-                if cfg!(feature = "strict") {
-                    todo!("not sure how to abort parsing here");
-                } else {
-                    Ok(tag)
-                }
-            }
-            Err(e) => match e {
-                TagReadingError::NonUtf8Tag(_) => unreachable!(),
-                TagReadingError::XmlError(e) => Err(TagReadingError::XmlError(e)),
-                TagReadingError::MissingAttribute(att, tag) => {
-                    Err(TagReadingError::MissingAttribute(att, tag))
-                }
-            },
-        }
+    pub const fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub const fn verify_sha1(mut self, verify_sha1: bool) -> Self {
+        self.verify_sha1 = verify_sha1;
+        self
     }
+
+    pub const fn stub(mut self, stub: bool) -> Self {
+        self.stub = stub;
+        self
+    }
+
+    pub const fn skip_text(mut self, skip_text: bool) -> Self {
+        self.skip_text = skip_text;
+        self
+    }
+}
+
+/// An error that aborted parsing, together with the [`Location`] where it occurred.
+#[derive(Debug, thiserror::Error)]
+#[error("{kind} (at {location})")]
+pub struct ParsingError {
+    #[source]
+    kind: ParsingErrorKind,
+    location: Location,
 }
 
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
-pub enum ParsingError {
+pub enum ParsingErrorKind {
     #[error("XML error")]
     XmlError(#[from] quick_xml::Error),
     #[error("unexpected end of file")]
     Eof,
-    #[cfg(feature = "strict")]
-    #[error("missing field: {0}")]
-    MissingField(&'static str),
-    #[cfg(feature = "strict")]
-    #[error("mismatched tags")]
-    MismatchedTags,
+    /// Malformed input, only returned in [strict mode](ParserOptions::strict).
+    #[error(transparent)]
+    Malformed(DiagnosticKind),
+}
+
+impl ParsingError {
+    pub fn kind(&self) -> &ParsingErrorKind {
+        &self.kind
+    }
+
+    pub fn into_kind(self) -> ParsingErrorKind {
+        self.kind
+    }
+
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    /// Returns `true` if the parser can continue after this error.
+    ///
+    /// This is the case for [`ParsingErrorKind::Malformed`] errors returned by
+    /// [`DumpParser::parse_page`] (and [`ParallelDumpReader::parse_page`]):
+    /// the next call skips the rest of the malformed page and continues with the following one.
+    /// Errors in the `<siteinfo>` header are never recoverable, since no parser is constructed.
+    pub fn is_recoverable(&self) -> bool {
+        match self.kind {
+            ParsingErrorKind::XmlError(_) | ParsingErrorKind::Eof => false,
+            ParsingErrorKind::Malformed(_) => true,
+        }
+    }
+
+    /// Errors are created without location deep inside the parser,
+    /// the public entry points attach it before returning.
+    fn at(mut self, location: Location) -> Self {
+        self.location = location;
+        self
+    }
+}
+
+impl From<ParsingErrorKind> for ParsingError {
+    fn from(kind: ParsingErrorKind) -> Self {
+        Self {
+            kind,
+            location: Location::default(),
+        }
+    }
+}
+
+impl From<quick_xml::Error> for ParsingError {
+    fn from(e: quick_xml::Error) -> Self {
+        ParsingErrorKind::XmlError(e).into()
+    }
 }
 
 impl From<std::io::Error> for ParsingError {
     fn from(e: std::io::Error) -> Self {
-        Self::XmlError(e.into())
+        ParsingErrorKind::XmlError(e.into()).into()
     }
 }
 
-// impl ParsingError {
-//     fn is_recoverable(&self) -> bool {
-//         match self {
-//             ParsingError::XmlError(_) => todo!("decide if error is recoverable"),
-//             ParsingError::Eof => false,
-//         }
-//     }
-// }
-
 impl<R: BufRead> DumpParser<R> {
-    fn new_impl(reader: R, preallocate: bool) -> Self {
+    fn new_impl(reader: R, preallocate: bool, options: ParserOptions) -> Self {
         let mut xml_parser = quick_xml::Reader::from_reader(reader);
-        let config = xml_parser.config_mut();
-        // expand_empty_elements not set, take care to handle empty elements!
-        config.check_end_names = false; /* we do this anyway so avoid extra allocations */
+        configure_reader(&mut xml_parser);
 
         let buf = if preallocate {
             // preallocate 1 MiB for the buffer
@@ -394,15 +856,11 @@ impl<R: BufRead> DumpParser<R> {
         };
 
         Self {
-            tag_interner: TagStringInterner::new(),
-            xml_parser,
-            buf,
-            current_path: Vec::new(),
-            site_info: SiteInfo {
-                dbname: CompactString::default(),
-                namespaces: HashMap::new(),
+            events: BufferedEvents {
+                reader: xml_parser,
+                buf,
             },
-            non_utf8_reporter: NonUtf8Reporter::new(),
+            state: ParserState::new(options),
         }
     }
 
@@ -416,16 +874,32 @@ impl<R: BufRead> DumpParser<R> {
     /// Returns [`ParsingError`] if the XML is malformed or the `<siteinfo>` block
     /// cannot be parsed.
     pub fn new(reader: R) -> Result<Self, ParsingError> {
-        let mut new = Self::new_impl(reader, true);
+        Self::with_options(reader, ParserOptions::default())
+    }
+
+    /// Creates a new parser like [`DumpParser::new`], using the given options.
+    pub fn with_options(reader: R, options: ParserOptions) -> Result<Self, ParsingError> {
+        let mut new = Self::new_impl(reader, true, options);
 
-        new.parse_site_info()?;
+        new.state
+            .parse_site_info(&mut new.events)
+            .map_err(|e| new.locate(e))?;
 
         Ok(new)
     }
 
     /// Returns the site information parsed from the `<siteinfo>` header.
     pub fn site_info(&self) -> &SiteInfo {
-        &self.site_info
+        &self.state.site_info
+    }
+
+    /// Returns the diagnostics reported while parsing the most recent page,
+    /// or the `<siteinfo>` header if no page has been parsed yet.
+    ///
+    /// A [`Diagnostic`] describes a problem in the input the parser recovered from,
+    /// e.g. an invalid timestamp or a revision that was dropped because of a missing field.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.state.diagnostics.collected
     }
 
     /// Returns the number of bytes consumed from the underlying reader so far.
@@ -434,38 +908,125 @@ impl<R: BufRead> DumpParser<R> {
     /// or to determine the byte extent of a page in the serialized XML
     /// (by comparing the value before and after a [`parse_page`](DumpParser::parse_page) call).
     pub fn bytes_consumed(&self) -> u64 {
-        self.xml_parser.buffer_position()
+        self.events.position()
     }
 
     #[doc(hidden)] // for testing
     pub fn xml_parser(&mut self) -> &mut quick_xml::Reader<R> {
-        &mut self.xml_parser
+        &mut self.events.reader
+    }
+
+    /// Consumes the parser and returns the underlying reader.
+    ///
+    /// quick-xml reads through [`BufRead::fill_buf`] and only consumes what it has parsed,
+    /// so the reader is positioned directly after the last event returned by the parser.
+    pub(crate) fn into_inner(self) -> R {
+        self.events.reader.into_inner()
+    }
+
+    /// Creates a parser for a fragment of a dump that starts inside the `<mediawiki>` root tag,
+    /// i.e. a sequence of zero or more `<page>` elements without the `<siteinfo>` header.
+    ///
+    /// `position_offset` is the position of the fragment in the stream, used for [`Location`]s.
+    pub(crate) fn new_fragment(reader: R, options: ParserOptions, position_offset: u64) -> Self {
+        let mut parser = Self::new_impl(reader, false, options);
+        parser.state.current_path.push(Tag::MediaWiki);
+        parser.state.is_fragment = true;
+        parser.state.diagnostics.position_offset = position_offset;
+        parser
+    }
+
+    fn locate(&self, error: ParsingError) -> ParsingError {
+        self.state.locate(error, self.events.position())
+    }
+
+    /// Parses the next `<page>` element from the XML stream.
+    ///
+    /// # Return values
+    ///
+    /// - `Ok(Some(page))` — a complete page was successfully parsed.
+    /// - `Ok(None)` — the end of the stream was reached cleanly; no more pages.
+    /// - `Err(error)` with [`ParsingErrorKind::Eof`] — the stream ended mid-page (truncated/malformed dump).
+    /// - `Err(error)` with [`ParsingErrorKind::Malformed`] — the page is malformed and the parser
+    ///   is in [strict mode](ParserOptions::strict). Parsing can continue with the next page.
+    /// - `Err(other)` — an XML parsing error occurred.
+    ///
+    /// Revisions within a page are returned in document order (oldest first).
+    ///
+    /// Problems the parser recovered from are available from [`diagnostics`](DumpParser::diagnostics)
+    /// until the next call.
+    pub fn parse_page(&mut self) -> Result<Option<Page>, ParsingError> {
+        self.state.parse_page(&mut self.events)
     }
 
-    #[instrument(level = "debug")]
+    pub fn parse_single_page(reader: R, read_bytes: &mut usize) -> Result<Page, ParsingError> {
+        let mut parser = Self::new_fragment(reader, ParserOptions::default(), 0);
+
+        let page = parser
+            .parse_page()?
+            .ok_or_else(|| parser.locate(ParsingErrorKind::Eof.into()))?;
+
+        *read_bytes = parser.bytes_consumed() as usize;
+
+        Ok(page)
+    }
+}
+
+impl ParserState {
+    fn new(options: ParserOptions) -> Self {
+        Self {
+            tag_interner: TagStringInterner::new(),
+            current_path: Vec::new(),
+            site_info: SiteInfo::default(),
+            diagnostics: Diagnostics {
+                callback: options.on_diagnostic,
+                strict: options.strict,
+                ..Diagnostics::default()
+            },
+            pending_text: PendingText::default(),
+            verify_sha1: options.verify_sha1,
+            stub: options.stub,
+            skip_text: options.skip_text,
+            is_fragment: false,
+            skip_rest_of_page: false,
+        }
+    }
+
+    fn locate(&self, error: ParsingError, position: u64) -> ParsingError {
+        error.at(self.diagnostics.location(position))
+    }
+
+    #[instrument(level = "debug", skip(diagnostics))]
     fn parse_start_bytes(
         e: &BytesStart,
         expecting_namespace: bool,
 
         // unfortunately have to pass all these as arguments, because otherwise we get problems with the borrow checker
-        non_utf8_reporter: &mut NonUtf8Reporter,
         tag_interner: &mut TagStringInterner,
         current_path: &[Tag],
-    ) -> Result<Tag, quick_xml::Error> {
-        match non_utf8_reporter.tag_from_start_bytes(e, tag_interner) {
+        diagnostics: &mut Diagnostics,
+        position: u64,
+    ) -> Result<Tag, ParsingError> {
+        match Tag::from_start_bytes(e, tag_interner) {
             Ok(tag) => Ok(tag),
+            Err(TagReadingError::NonUtf8Tag(tag)) => {
+                diagnostics.report(
+                    DiagnosticKind::NonUtf8Tag(String::from_utf8_lossy(e.name().as_ref()).into()),
+                    position,
+                )?;
+                Ok(tag)
+            }
             Err(TagReadingError::MissingAttribute(attr, tag)) => {
                 if tag == "namespace" {
-                    if cfg!(feature = "strict") {
-                        todo!();
-                    }
-                    // print warning and skip the tag
+                    // report and skip the tag
                     if expecting_namespace {
-                        tracing::warn!(
-                            message = "missing expected attribute, ignoring the namespace",
-                            attribute = attr,
-                            tag = tag
-                        );
+                        diagnostics.report(
+                            DiagnosticKind::MissingAttribute {
+                                attribute: attr,
+                                tag,
+                            },
+                            position,
+                        )?;
                     } else {
                         tracing::info!(
                             message = "found known tag in unexpected location",
@@ -473,7 +1034,7 @@ impl<R: BufRead> DumpParser<R> {
                             path = ?current_path
                         );
                     }
-                    Ok(Tag::Namespace("ignored".to_string()))
+                    Ok(Tag::Namespace("ignored".to_string(), None))
                 } else {
                     // unexpected
                     // TODO: adjust this if more tags get mandatory attributes
@@ -483,15 +1044,12 @@ impl<R: BufRead> DumpParser<R> {
                     );
                 }
             }
-            Err(TagReadingError::XmlError(e)) => Err(e),
-            _ => unreachable!(),
+            Err(TagReadingError::XmlError(e)) => Err(e.into()),
         }
     }
 
     // debugging aid for format changes
-    fn check_known_tags_in_unexpected_location(&self, is_empty: bool) {
-        let current_path = &self.current_path;
-
+    fn check_known_tags_in_unexpected_location(current_path: &[Tag], is_empty: bool) {
         if current_path.is_empty() {
             return;
         }
@@ -511,24 +1069,18 @@ impl<R: BufRead> DumpParser<R> {
         e: &BytesEnd,
         current_path: &mut Vec<Tag>,
         tag_interner: &mut TagStringInterner,
-        xml_parser: &mut quick_xml::Reader<R>,
+        diagnostics: &mut Diagnostics,
+        position: u64,
     ) -> Result<Option<Tag>, ParsingError> {
         // error handling for mismatched tags
         let tag = if let Some(tag) = current_path.pop() {
             tag
         } else {
             let tag = String::from_utf8_lossy(e.name().into_inner());
-            tracing::error!(message = "Unexpected end tag", tag = tag.as_ref(), current_path = ?current_path, position = xml_parser.buffer_position());
 
-            #[cfg(feature = "strict")]
-            {
-                return Err(ParsingError::MismatchedTags);
-            }
-            #[cfg(not(feature = "strict"))]
-            {
-                tracing::warn!("Ignoring unexpected end tag. This may lead to incorrect results.");
-                return Ok(None);
-            }
+            // ignoring the unexpected end tag, this may lead to incorrect results
+            diagnostics.report(DiagnosticKind::UnexpectedEndTag(tag.into_owned()), position)?;
+            return Ok(None);
         };
 
         // ignore non-utf8 error here because we already reported it when the tag was read
@@ -537,130 +1089,397 @@ impl<R: BufRead> DumpParser<R> {
             .matches_end_bytes(e, tag_interner)
             .unwrap_or_else(|e| e.0);
         if !matches {
-            tracing::error!(
-                message = "Mismatched tags",
-                expected = ?tag,
-                actual = String::from_utf8_lossy(e.name().as_ref()).as_ref(),
-                current_path = ?current_path,
-                position = xml_parser.buffer_position()
-            );
+            // ignoring the mismatched tag, this may lead to incorrect results
+            diagnostics.report(
+                DiagnosticKind::MismatchedTags {
+                    expected: format!("{:?}", tag),
+                    actual: String::from_utf8_lossy(e.name().as_ref()).into_owned(),
+                },
+                position,
+            )?;
+
+            // (1) either this closing tag does not have a corresponding opening tag,
+            // (2) or it is not the expected closing tag (e.g. typo),
+            // (3) or a previous opening tag is not closed
+            // let's try to recover as best as possible
+
+            // for (1) we would have to push the tag back onto the stack
+            // for (2) we'd just continue
+            // for (3) we'd need to find the corresponding opening tag and close it
+            // we can't distinguish between these cases, so we'll just continue
+        }
 
-            #[cfg(feature = "strict")]
-            {
-                return Err(ParsingError::MismatchedTags);
+        Ok(Some(tag))
+    }
+
+    fn parse_case(
+        value: &str,
+        diagnostics: &mut Diagnostics,
+        position: u64,
+    ) -> Result<Option<self::Case>, ParsingError> {
+        let case = self::Case::from_dump_value(value);
+        if case.is_none() {
+            diagnostics.report(DiagnosticKind::InvalidCase(value.to_string()), position)?;
+        }
+        Ok(case)
+    }
+
+    fn add_namespace(
+        site_info: &mut SiteInfo,
+        key: &str,
+        case: &Option<String>,
+        name: &str,
+        diagnostics: &mut Diagnostics,
+        position: u64,
+    ) -> Result<(), ParsingError> {
+        let Ok(key) = key.parse() else {
+            // a missing key has already been reported
+            if key != "ignored" {
+                diagnostics.report(
+                    DiagnosticKind::InvalidNamespaceKey(key.to_string()),
+                    position,
+                )?;
             }
-            #[cfg(not(feature = "strict"))]
-            {
-                tracing::warn!("Ignoring mismatched tag. This may lead to incorrect results.");
+            return Ok(());
+        };
+        let case = match case {
+            Some(case) => Self::parse_case(case, diagnostics, position)?,
+            None => None,
+        };
 
-                // (1) either this closing tag does not have a corresponding opening tag,
-                // (2) or it is not the expected closing tag (e.g. typo),
-                // (3) or a previous opening tag is not closed
-                // let's try to recover as best as possible
+        site_info.namespaces.insert(
+            key,
+            self::Namespace {
+                name: CompactString::from(name),
+                case,
+            },
+        );
+        Ok(())
+    }
 
-                // for (1) we would have to push the tag back onto the stack
-                // for (2) we'd just continue
-                // for (3) we'd need to find the corresponding opening tag and close it
-                // we can't distinguish between these cases, so we'll just continue
+    fn handle_site_info_text(
+        text: TagText,
+        position: u64,
+
+        // passed as arguments because the current XML event still borrows the buffer
+        current_path: &[Tag],
+        diagnostics: &mut Diagnostics,
+        site_info: &mut SiteInfo,
+    ) -> Result<(), ParsingError> {
+        use Tag::*;
+
+        match current_path {
+            [MediaWiki, SiteInfo, Sitename] => {
+                site_info.sitename = CompactString::from(text.as_ref());
+            }
+            [MediaWiki, SiteInfo, DbName] => {
+                site_info.dbname = CompactString::from(text.as_ref());
+            }
+            [MediaWiki, SiteInfo, Base] => {
+                site_info.base = CompactString::from(text.as_ref());
+            }
+            [MediaWiki, SiteInfo, Generator] => {
+                site_info.generator = CompactString::from(text.as_ref());
             }
+            [MediaWiki, SiteInfo, Case] => {
+                site_info.case = Self::parse_case(&text, diagnostics, position)?;
+            }
+            [MediaWiki, SiteInfo, Namespaces, Namespace(key, case)] => {
+                Self::add_namespace(site_info, key, case, &text, diagnostics, position)?;
+            }
+            // quick_xml will output any formatting (e.g. newlines, whitespaces) after the opening tag
+            // and before the closing tag (i.e. outside the child tags) as text events.
+            // suppress "known tag in unexpected location" warning for these tags.
+            [MediaWiki] | [MediaWiki, SiteInfo] | [MediaWiki, SiteInfo, Namespaces] => {}
+            _ => Self::check_known_tags_in_unexpected_location(current_path, false),
         }
 
-        Ok(Some(tag))
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn handle_page_text(
+        text: TagText,
+        position: u64,
+
+        // passed as arguments because the current XML event still borrows the buffer
+        current_path: &[Tag],
+        diagnostics: &mut Diagnostics,
+        page: &mut Page,
+        revision_builder: &mut Option<RevisionBuilder>,
+        span: &tracing::Span,
+    ) -> Result<(), ParsingError> {
+        use Tag::*;
+
+        match current_path {
+            // Page tags
+            [MediaWiki, Page, Title] => {
+                page.title = page_title(&text);
+                span.record("title", page.title.as_str());
+                diagnostics.page_title = Some(page.title.clone());
+            }
+            [MediaWiki, Page, Id] => {
+                if let Ok(id) = text.parse() {
+                    page.id = Some(id);
+                } else {
+                    diagnostics
+                        .report(DiagnosticKind::InvalidPageId(text.into_owned()), position)?;
+                }
+            }
+            [MediaWiki, Page, Ns] => {
+                let ns = if let Ok(id) = text.parse() {
+                    id
+                } else {
+                    diagnostics.report(
+                        DiagnosticKind::InvalidNamespace(text.into_owned()),
+                        position,
+                    )?;
+                    0
+                };
+                page.namespace = ns;
+            }
+            // Revision tags
+            [MediaWiki, Page, Revision, Id] => {
+                if let Some(revision_builder) = revision_builder {
+                    revision_builder.id = if let Ok(id) = text.parse() {
+                        Some(id)
+                    } else {
+                        diagnostics.report(
+                            DiagnosticKind::InvalidRevisionId(text.into_owned()),
+                            position,
+                        )?;
+                        // always use negative ids for invalid ids
+                        Some(rand::rng().random_range(i32::MIN..-100))
+                    };
+                    diagnostics.revision_id = revision_builder.id;
+                }
+            }
+            [MediaWiki, Page, Revision, Timestamp] => {
+                if let Some(revision_builder) = revision_builder {
+                    revision_builder.timestamp = if let Some(timestamp) = parse_timestamp(&text) {
+                        Some(timestamp)
+                    } else {
+                        diagnostics.report(
+                            DiagnosticKind::InvalidTimestamp(text.into_owned()),
+                            position,
+                        )?;
+                        None
+                    };
+                }
+            }
+            [MediaWiki, Page, Revision, Contributor(_), Username] => {
+                if let Some(revision_builder) = revision_builder {
+                    revision_builder.contributor_name = Some(CompactString::from(text.as_ref()));
+                }
+            }
+            // alternative to Username tag - can happen sometimes
+            [MediaWiki, Page, Revision, Contributor(_), Ip] => {
+                if let Some(revision_builder) = revision_builder {
+                    revision_builder.contributor_name = Some(CompactString::from(text.as_ref()));
+                }
+            }
+            [MediaWiki, Page, Revision, Contributor(_), Id] => {
+                if let Some(revision_builder) = revision_builder {
+                    revision_builder.contributor_id = if let Ok(id) = text.parse() {
+                        Some(id)
+                    } else {
+                        diagnostics.report(
+                            DiagnosticKind::InvalidContributorId(text.into_owned()),
+                            position,
+                        )?;
+                        None
+                    };
+                }
+            }
+            [MediaWiki, Page, Revision, Text(deleted, _)] => {
+                if let Some(revision_builder) = revision_builder {
+                    revision_builder.text = Some(if *deleted {
+                        self::Text::Deleted
+                    } else {
+                        text.into_text()
+                    });
+                }
+            }
+            [MediaWiki, Page, Revision, Sha1] => {
+                if let Some(revision_builder) = revision_builder {
+                    if let Some(sha1) = parse_sha1(&text) {
+                        revision_builder.sha1 = Some(sha1);
+                    } else {
+                        diagnostics
+                            .report(DiagnosticKind::InvalidSha1(text.into_owned()), position)?;
+                    }
+                }
+            }
+            [MediaWiki, Page, Revision, Comment(deleted)] => {
+                if let Some(revision_builder) = revision_builder {
+                    revision_builder.comment = Some(if *deleted {
+                        self::Comment::Deleted
+                    } else {
+                        self::Comment::Normal(CompactString::from(text.as_ref()))
+                    });
+                }
+            }
+            [MediaWiki, Page, Revision, Minor] => {
+                // minor tag should be empty, but just in case it's not handle it here as well
+                if let Some(revision_builder) = revision_builder {
+                    revision_builder.minor = true;
+                }
+            }
+            [MediaWiki, Page, Revision, Model] => {
+                if let Some(revision_builder) = revision_builder {
+                    revision_builder.model = Some(CompactString::from(text.as_ref()));
+                }
+            }
+            [MediaWiki, Page, Revision, Format] => {
+                if let Some(revision_builder) = revision_builder {
+                    revision_builder.format = Some(CompactString::from(text.as_ref()));
+                }
+            }
+            [MediaWiki, Page, Revision, Content, Role] => {
+                if let Some(slot) = revision_builder.as_mut().and_then(|r| r.slots.last_mut()) {
+                    slot.role = Some(CompactString::from(text.as_ref()));
+                }
+            }
+            [MediaWiki, Page, Revision, Content, Model] => {
+                if let Some(slot) = revision_builder.as_mut().and_then(|r| r.slots.last_mut()) {
+                    slot.model = Some(CompactString::from(text.as_ref()));
+                }
+            }
+            [MediaWiki, Page, Revision, Content, Format] => {
+                if let Some(slot) = revision_builder.as_mut().and_then(|r| r.slots.last_mut()) {
+                    slot.format = Some(CompactString::from(text.as_ref()));
+                }
+            }
+            [MediaWiki, Page, Revision, Content, Text(deleted, sha1)] => {
+                if let Some(slot) = revision_builder.as_mut().and_then(|r| r.slots.last_mut()) {
+                    let text = if *deleted {
+                        self::Text::Deleted
+                    } else {
+                        text.into_text()
+                    };
+                    slot.set_text(text, sha1.as_deref(), diagnostics, position)?;
+                }
+            }
+            // quick_xml will output any formatting (e.g. newlines, whitespaces) after the opening tag
+            // and before the closing tag (i.e. outside the child tags) as text events.
+            // suppress "known tag in unexpected location" warning for these tags.
+            [MediaWiki]
+            | [MediaWiki, Page]
+            | [MediaWiki, Page, Revision]
+            | [MediaWiki, Page, Revision, Contributor(_)]
+            | [MediaWiki, Page, Revision, Content] => {}
+            _ => Self::check_known_tags_in_unexpected_location(current_path, false),
+        }
+
+        Ok(())
     }
 
-    #[instrument(level = "debug")]
-    fn parse_site_info(&mut self) -> Result<(), ParsingError> {
-        let mut site_info = SiteInfo {
-            dbname: CompactString::default(),
-            namespaces: HashMap::new(),
+    fn parse_schema_version(
+        e: &BytesStart,
+        diagnostics: &mut Diagnostics,
+        position: u64,
+    ) -> Result<Option<SchemaVersion>, ParsingError> {
+        let Some(version) = e
+            .try_get_attribute("version")
+            .map_err(quick_xml::Error::from)?
+        else {
+            return Ok(None);
         };
 
+        let version = version.unescape_value()?;
+        let schema_version = SchemaVersion::parse(&version);
+        if schema_version.is_none() {
+            diagnostics.report(
+                DiagnosticKind::InvalidSchemaVersion(version.into_owned()),
+                position,
+            )?;
+        }
+        Ok(schema_version)
+    }
+
+    #[instrument(level = "debug", skip(events))]
+    fn parse_site_info(&mut self, events: &mut impl XmlEvents) -> Result<(), ParsingError> {
+        let mut site_info = SiteInfo::default();
+
         loop {
-            match self.xml_parser.read_event_into(&mut self.buf)? {
+            let (event, position) = events.next_event()?;
+            if !matches!(
+                event,
+                quick_xml::events::Event::Text(_) | quick_xml::events::Event::GeneralRef(_)
+            ) {
+                if let Some((text, position)) = self.pending_text.take() {
+                    Self::handle_site_info_text(
+                        text,
+                        position,
+                        &self.current_path,
+                        &mut self.diagnostics,
+                        &mut site_info,
+                    )?;
+                }
+            }
+
+            match event {
                 quick_xml::events::Event::Start(ref e) => {
                     let tag = Self::parse_start_bytes(
                         e,
                         true,
-                        &mut self.non_utf8_reporter,
                         &mut self.tag_interner,
                         &self.current_path,
+                        &mut self.diagnostics,
+                        position,
                     )?;
 
+                    if tag == Tag::MediaWiki {
+                        site_info.schema_version =
+                            Self::parse_schema_version(e, &mut self.diagnostics, position)?;
+                    }
+
                     self.current_path.push(tag);
                 }
                 quick_xml::events::Event::Empty(ref e) => {
                     let tag = Self::parse_start_bytes(
                         e,
                         true,
-                        &mut self.non_utf8_reporter,
                         &mut self.tag_interner,
                         &self.current_path,
+                        &mut self.diagnostics,
+                        position,
                     )?;
 
                     use Tag::*;
 
                     self.current_path.push(tag);
                     match self.current_path.as_slice() {
-                        [MediaWiki, SiteInfo, Namespaces, Namespace(id)] => {
-                            let key = if let Ok(id) = id.parse() {
-                                id
-                            } else {
-                                tracing::warn!(
-                                    message = "Ignoring namespace with invalid id",
-                                    id,
-                                    name = "ignored",
-                                    position = self.xml_parser.buffer_position()
-                                );
-                                continue;
-                            };
-                            site_info.namespaces.insert(key, self::Namespace::Default);
+                        [MediaWiki, SiteInfo, Namespaces, Namespace(key, case)] => {
+                            Self::add_namespace(
+                                &mut site_info,
+                                key,
+                                case,
+                                "",
+                                &mut self.diagnostics,
+                                position,
+                            )?;
+                        }
+                        _ => {
+                            Self::check_known_tags_in_unexpected_location(&self.current_path, true)
                         }
-                        _ => self.check_known_tags_in_unexpected_location(true),
                     }
                     self.current_path.pop();
                 }
                 quick_xml::events::Event::Text(e) => {
-                    let text = e.decode().map_err(quick_xml::Error::from)?;
-
-                    use Tag::*;
-
-                    match self.current_path.as_slice() {
-                        [MediaWiki, SiteInfo, DbName] => {
-                            site_info.dbname = CompactString::from(text.as_ref());
-                        }
-                        [MediaWiki, SiteInfo, Namespaces, Namespace(id)] => {
-                            let key = if let Ok(id) = id.parse() {
-                                id
-                            } else {
-                                if id != "ignored" {
-                                    tracing::warn!(
-                                        message = "Ignoring namespace with invalid id",
-                                        id,
-                                        name = text.as_ref(),
-                                        position = self.xml_parser.buffer_position()
-                                    );
-                                }
-                                continue;
-                            };
-                            site_info.namespaces.insert(
-                                key,
-                                self::Namespace::Named(CompactString::from(text.as_ref())),
-                            );
-                        }
-                        // quick_xml will output any formatting (e.g. newlines, whitespaces) after the opening tag
-                        // and before the closing tag (i.e. outside the child tags) as text events.
-                        // suppress "known tag in unexpected location" warning for these tags.
-                        [MediaWiki] | [MediaWiki, SiteInfo] | [MediaWiki, SiteInfo, Namespaces] => {
-                        }
-                        _ => self.check_known_tags_in_unexpected_location(false),
-                    }
+                    self.pending_text.push_text(&e, position)?;
+                }
+                quick_xml::events::Event::GeneralRef(e) => {
+                    self.pending_text
+                        .push_reference(&e, position, &mut self.diagnostics)?;
                 }
                 quick_xml::events::Event::End(ref e) => {
                     let tag = Self::check_end_tag(
                         e,
                         &mut self.current_path,
                         &mut self.tag_interner,
-                        &mut self.xml_parser,
+                        &mut self.diagnostics,
+                        position,
                     )?;
 
                     if tag == Some(Tag::SiteInfo) {
@@ -672,31 +1491,120 @@ impl<R: BufRead> DumpParser<R> {
                     // we should never reach eof in a correct file because we break when we find the closing tag
 
                     tracing::error!(partial_site_info = ?site_info, current_path = ?self.current_path);
-                    return Err(ParsingError::Eof);
+                    return Err(ParsingErrorKind::Eof.into());
                 }
                 _ => {}
             }
-            self.buf.clear();
         }
 
         self.site_info = site_info;
         Ok(())
     }
 
-    /// Parses the next `<page>` element from the XML stream.
-    ///
-    /// # Return values
-    ///
-    /// - `Ok(Some(page))` — a complete page was successfully parsed.
-    /// - `Ok(None)` — the end of the stream was reached cleanly; no more pages.
-    /// - `Err(ParsingError::Eof)` — the stream ended mid-page (truncated/malformed dump).
-    /// - `Err(other)` — an XML parsing error occurred.
-    ///
-    /// Revisions within a page are returned in document order (oldest first).
-    pub fn parse_page(&mut self) -> Result<Option<Page>, ParsingError> {
+    fn parse_page(&mut self, events: &mut impl XmlEvents) -> Result<Option<Page>, ParsingError> {
+        self.diagnostics.start_page();
+        if std::mem::take(&mut self.skip_rest_of_page) {
+            self.skip_rest_of_page(events)
+                .map_err(|e| self.locate(e, events.position()))?;
+        }
+
+        self.parse_page_impl(events).map_err(|e| {
+            if e.is_recoverable() {
+                self.skip_rest_of_page = true;
+            }
+            self.locate(e, events.position())
+        })
+    }
+
+    /// With [`ParserOptions::skip_text`], the content of `<text>` tags is ignored.
+    fn skipping_text(&self) -> bool {
+        self.skip_text && matches!(self.current_path.last(), Some(Tag::Text(..)))
+    }
+
+    /// The text of an empty `<text />` tag, a [`Text::Stub`] in a stub dump.
+    fn empty_text(e: &BytesStart, deleted: bool, stub: bool) -> Result<Text, quick_xml::Error> {
+        if deleted {
+            return Ok(Text::Deleted);
+        }
+        if !stub {
+            return Ok(Text::Normal(String::new()));
+        }
+
+        let mut bytes = None;
+        let mut location = None;
+        let mut id = None;
+        for attr in e.attributes() {
+            let attr = attr.map_err(quick_xml::Error::from)?;
+            match attr.key.as_ref() {
+                b"bytes" => bytes = attr.unescape_value()?.parse().ok(),
+                b"location" => location = Some(CompactString::from(attr.unescape_value()?)),
+                b"id" => id = Some(CompactString::from(attr.unescape_value()?)),
+                _ => {}
+            }
+        }
+        Ok(Text::Stub {
+            bytes,
+            // export-0.11 has both, older stubs only the text id
+            location: location.or(id),
+        })
+    }
+
+    fn verify_revision_sha1(
+        &mut self,
+        revision: &mut Revision,
+        position: u64,
+    ) -> Result<(), ParsingError> {
+        if !revision.slots.is_empty() {
+            // the hash of a revision with several slots combines the hashes of all slots
+            return Ok(());
+        }
+        let Some(text) = revision.text.content() else {
+            // the hash of deleted text can't be verified
+            return Ok(());
+        };
+
+        let computed = Sha1Hash::compute(text);
+        if let Some(sha1) = revision.sha1.filter(|sha1| *sha1 != computed) {
+            self.diagnostics.report(
+                DiagnosticKind::Sha1Mismatch {
+                    expected: String::from_utf8_lossy(&sha1.0).into_owned(),
+                    actual: String::from_utf8_lossy(&computed.0).into_owned(),
+                },
+                position,
+            )?;
+        }
+        revision.sha1 = Some(computed);
+        Ok(())
+    }
+
+    /// Skips to the end of the current page after a recoverable error.
+    fn skip_rest_of_page(&mut self, events: &mut impl XmlEvents) -> Result<(), ParsingError> {
+        self.pending_text.take();
+        let Some(page_index) = self.current_path.iter().position(|tag| *tag == Tag::Page) else {
+            // the error occurred at the closing tag of the page
+            return Ok(());
+        };
+        self.current_path.truncate(page_index);
+
+        // pages are never nested, so the next closing page tag ends the broken page
+        loop {
+            match events.next_event()?.0 {
+                quick_xml::events::Event::End(ref e) if e.name().as_ref() == b"page" => break,
+                quick_xml::events::Event::Eof => return Err(ParsingErrorKind::Eof.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_page_impl(
+        &mut self,
+        events: &mut impl XmlEvents,
+    ) -> Result<Option<Page>, ParsingError> {
         let span = tracing::span!(tracing::Level::DEBUG, "parse_page", self=?self, title=tracing::field::Empty);
 
         let mut page = Page {
+            id: None,
             title: CompactString::default(),
             namespace: 0,
             revisions: Vec::new(),
@@ -704,16 +1612,36 @@ impl<R: BufRead> DumpParser<R> {
         let mut started_page = false;
 
         let mut revision_builder = None;
+        let mut revision_ids = HashSet::new();
 
         loop {
-            match self.xml_parser.read_event_into(&mut self.buf)? {
+            let (event, position) = events.next_event()?;
+            if !matches!(
+                event,
+                quick_xml::events::Event::Text(_) | quick_xml::events::Event::GeneralRef(_)
+            ) {
+                if let Some((text, position)) = self.pending_text.take() {
+                    Self::handle_page_text(
+                        text,
+                        position,
+                        &self.current_path,
+                        &mut self.diagnostics,
+                        &mut page,
+                        &mut revision_builder,
+                        &span,
+                    )?;
+                }
+            }
+
+            match event {
                 quick_xml::events::Event::Start(ref e) => {
                     let tag = Self::parse_start_bytes(
                         e,
                         false,
-                        &mut self.non_utf8_reporter,
                         &mut self.tag_interner,
                         &self.current_path,
+                        &mut self.diagnostics,
+                        position,
                     )?;
 
                     if tag == Tag::Page {
@@ -722,17 +1650,58 @@ impl<R: BufRead> DumpParser<R> {
 
                     if tag == Tag::Revision {
                         revision_builder = Some(RevisionBuilder::new());
+                        self.diagnostics.revision_id = None;
+                    }
+
+                    if tag == Tag::Content {
+                        if let Some(revision_builder) = &mut revision_builder {
+                            revision_builder.slots.push(SlotBuilder::default());
+                        }
+                    }
+
+                    if tag == Tag::Contributor(true) {
+                        if let Some(revision_builder) = &mut revision_builder {
+                            revision_builder.contributor_deleted = true;
+                        }
                     }
 
                     self.current_path.push(tag);
+
+                    if self.skip_text {
+                        use Tag::*;
+
+                        // the content is ignored, see `skipping_text`
+                        match self.current_path.as_slice() {
+                            [MediaWiki, Page, Revision, Text(deleted, _)] => {
+                                if let Some(revision_builder) = &mut revision_builder {
+                                    revision_builder.text =
+                                        Some(Self::empty_text(e, *deleted, true)?);
+                                }
+                            }
+                            [MediaWiki, Page, Revision, Content, Text(deleted, sha1)] => {
+                                if let Some(slot) =
+                                    revision_builder.as_mut().and_then(|r| r.slots.last_mut())
+                                {
+                                    slot.set_text(
+                                        Self::empty_text(e, *deleted, true)?,
+                                        sha1.as_deref(),
+                                        &mut self.diagnostics,
+                                        position,
+                                    )?;
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
                 }
                 quick_xml::events::Event::Empty(ref e) => {
                     let tag = Self::parse_start_bytes(
                         e,
                         false,
-                        &mut self.non_utf8_reporter,
                         &mut self.tag_interner,
                         &self.current_path,
+                        &mut self.diagnostics,
+                        position,
                     )?;
 
                     self.current_path.push(tag);
@@ -741,10 +1710,23 @@ impl<R: BufRead> DumpParser<R> {
 
                     match self.current_path.as_slice() {
                         // Revision tags
-                        [MediaWiki, Page, Revision, Text(_, _)] => {
+                        [MediaWiki, Page, Revision, Text(deleted, _)] => {
                             // empty text tag
                             if let Some(revision_builder) = &mut revision_builder {
-                                revision_builder.text = Some(self::Text::Normal(String::new()));
+                                revision_builder.text =
+                                    Some(Self::empty_text(e, *deleted, self.stub)?);
+                            }
+                        }
+                        [MediaWiki, Page, Revision, Content, Text(deleted, sha1)] => {
+                            if let Some(slot) =
+                                revision_builder.as_mut().and_then(|r| r.slots.last_mut())
+                            {
+                                slot.set_text(
+                                    Self::empty_text(e, *deleted, self.stub)?,
+                                    sha1.as_deref(),
+                                    &mut self.diagnostics,
+                                    position,
+                                )?;
                             }
                         }
                         [MediaWiki, Page, Revision, Minor] => {
@@ -754,207 +1736,75 @@ impl<R: BufRead> DumpParser<R> {
                             }
                         }
                         [MediaWiki, Page, Revision, Sha1] => {} /* sometimes there is a sha1 tag but it's empty */
-                        [MediaWiki, Page, Revision, Comment] => {} /* same for comment tag, just handle it as if it's not there */
-                        _ => self.check_known_tags_in_unexpected_location(true),
-                    }
-                    self.current_path.pop();
-                }
-                quick_xml::events::Event::Text(e) => {
-                    let text = e.decode().map_err(quick_xml::Error::from)?;
-
-                    use Tag::*;
-
-                    match self.current_path.as_slice() {
-                        // Page tags
-                        [MediaWiki, Page, Title] => {
-                            fn normalize_title(title: &str) -> Cow<'_, str> {
-                                if title.contains("_") {
-                                    title.replace("_", " ").into()
-                                } else {
-                                    title.into()
-                                }
-                            }
-
-                            if let Some(title) = text.split_once(":") {
-                                // split off the namespace
-                                page.title = CompactString::from(normalize_title(title.1));
-                            } else {
-                                page.title = CompactString::from(normalize_title(&text));
-                            }
-                            span.record("title", page.title.as_str());
-                        }
-                        [MediaWiki, Page, Id] => { /* ignore page id */ }
-                        [MediaWiki, Page, Ns] => {
-                            let ns = if let Ok(id) = text.parse() {
-                                id
-                            } else {
-                                tracing::warn!(
-                                    message = "Found invalid namespace id, defaulting to 0",
-                                    ns = text.as_ref(),
-                                    position = self.xml_parser.buffer_position()
-                                );
-                                0
-                            };
-                            page.namespace = ns;
-                        }
-                        // Revision tags
-                        [MediaWiki, Page, Revision, Id] => {
-                            if let Some(revision_builder) = &mut revision_builder {
-                                revision_builder.id = if let Ok(id) = text.parse() {
-                                    Some(id)
-                                } else {
-                                    tracing::info!(
-                                        message =
-                                            "Found invalid revision id, generating a random id",
-                                        id = text.as_ref(),
-                                        position = self.xml_parser.buffer_position()
-                                    );
-                                    // always use negative ids for invalid ids
-                                    Some(rand::rng().random_range(i32::MIN..-100))
-                                };
-                            }
-                        }
-                        [MediaWiki, Page, Revision, Timestamp] => {
-                            // Source: https://github.com/mediawiki-utilities/python-mwtypes/blob/523a93f98fe1372938fc15872b5abb1f267cc643/mwtypes/timestamp.py#L12
-                            const TIMESTAMP_FORMAT_LONG: &str = "%Y-%m-%dT%H:%M:%SZ";
-                            const TIMESTAMP_FORMAT_SHORT: &str = "%Y%m%d%H%M%S";
-
-                            if let Some(revision_builder) = &mut revision_builder {
-                                revision_builder.timestamp = if let Ok(timestamp) =
-                                    chrono::NaiveDateTime::parse_from_str(
-                                        text.as_ref(),
-                                        TIMESTAMP_FORMAT_SHORT,
-                                    )
-                                    .or_else(|_| {
-                                        chrono::NaiveDateTime::parse_from_str(
-                                            text.as_ref(),
-                                            TIMESTAMP_FORMAT_LONG,
-                                        )
-                                    })
-                                    .map(|dt| {
-                                        chrono::DateTime::from_naive_utc_and_offset(dt, chrono::Utc)
-                                    }) {
-                                    Some(timestamp)
-                                } else {
-                                    tracing::warn!(
-                                        message = "Found invalid revision timestamp",
-                                        timestamp = text.as_ref(),
-                                        position = self.xml_parser.buffer_position()
-                                    );
-                                    None
-                                };
-                            }
-                        }
-                        [MediaWiki, Page, Revision, Contributor, Username] => {
-                            if let Some(revision_builder) = &mut revision_builder {
-                                revision_builder.contributor_name =
-                                    Some(CompactString::from(text.as_ref()));
-                            }
-                        }
-                        // alternative to Username tag - can happen sometimes
-                        [MediaWiki, Page, Revision, Contributor, Ip] => {
-                            if let Some(revision_builder) = &mut revision_builder {
-                                revision_builder.contributor_name =
-                                    Some(CompactString::from(text.as_ref()));
-                            }
-                        }
-                        [MediaWiki, Page, Revision, Contributor, Id] => {
+                        [MediaWiki, Page, Revision, Comment(false)] => {} /* same for comment tag, just handle it as if it's not there */
+                        [MediaWiki, Page, Revision, Comment(true)] => {
                             if let Some(revision_builder) = &mut revision_builder {
-                                revision_builder.contributor_id = if let Ok(id) = text.parse() {
-                                    Some(id)
-                                } else {
-                                    tracing::warn!(
-                                        message = "Found invalid contributor id",
-                                        id = text.as_ref(),
-                                        position = self.xml_parser.buffer_position()
-                                    );
-                                    None
-                                };
+                                revision_builder.comment = Some(self::Comment::Deleted);
                             }
                         }
-                        [MediaWiki, Page, Revision, Text(deleted, _)] => {
-                            if let Some(revision_builder) = &mut revision_builder {
-                                revision_builder.text = Some(if *deleted {
-                                    self::Text::Deleted
-                                } else {
-                                    self::Text::Normal(text.into_owned())
-                                });
-                            }
-                        }
-                        [MediaWiki, Page, Revision, Sha1] => {
+                        [MediaWiki, Page, Revision, Contributor(true)] => {
                             if let Some(revision_builder) = &mut revision_builder {
-                                let mut sha1 = [0; 31];
-                                let bytes = text.as_bytes();
-                                if bytes.len() == 31 {
-                                    sha1.copy_from_slice(bytes);
-                                    revision_builder.sha1 = Some(Sha1Hash(sha1));
-                                } else {
-                                    tracing::warn!(
-                                        message = "Found invalid sha1 hash",
-                                        sha1 = text.as_ref(),
-                                        position = self.xml_parser.buffer_position()
-                                    );
-                                }
+                                revision_builder.contributor_deleted = true;
                             }
                         }
-                        [MediaWiki, Page, Revision, Comment] => {
-                            if let Some(revision_builder) = &mut revision_builder {
-                                revision_builder.comment = Some(CompactString::from(text.as_ref()));
-                            }
+                        _ => {
+                            Self::check_known_tags_in_unexpected_location(&self.current_path, true)
                         }
-                        [MediaWiki, Page, Revision, Minor] => {
-                            // minor tag should be empty, but just in case it's not handle it here as well
-                            if let Some(revision_builder) = &mut revision_builder {
-                                revision_builder.minor = true;
-                            }
-                        }
-                        // quick_xml will output any formatting (e.g. newlines, whitespaces) after the opening tag
-                        // and before the closing tag (i.e. outside the child tags) as text events.
-                        // suppress "known tag in unexpected location" warning for these tags.
-                        [MediaWiki]
-                        | [MediaWiki, Page]
-                        | [MediaWiki, Page, Revision]
-                        | [MediaWiki, Page, Revision, Contributor] => {}
-                        _ => self.check_known_tags_in_unexpected_location(false),
                     }
+                    self.current_path.pop();
+                }
+                quick_xml::events::Event::Text(_) | quick_xml::events::Event::GeneralRef(_)
+                    if self.skipping_text() => {}
+                quick_xml::events::Event::Text(e) => {
+                    self.pending_text.push_text(&e, position)?;
+                }
+                quick_xml::events::Event::GeneralRef(e) => {
+                    self.pending_text
+                        .push_reference(&e, position, &mut self.diagnostics)?;
                 }
                 quick_xml::events::Event::End(ref e) => {
                     let tag = Self::check_end_tag(
                         e,
                         &mut self.current_path,
                         &mut self.tag_interner,
-                        &mut self.xml_parser,
-                    )?;
+                        &mut self.diagnostics,
+                        position,
+                    )
+                    .inspect_err(|_| {
+                        if e.name().as_ref() == b"page" {
+                            // the broken page is closed, don't skip the next one when recovering
+                            if let Some(index) =
+                                self.current_path.iter().position(|tag| *tag == Tag::Page)
+                            {
+                                self.current_path.truncate(index);
+                            }
+                        }
+                    })?;
 
                     if tag == Some(Tag::Revision) {
                         if let Some(revision_builder) = revision_builder.take() {
-                            let revision = match revision_builder.try_build() {
+                            let mut revision = match revision_builder.try_build() {
                                 Ok(revision) => revision,
                                 Err(BuildRevisionError(field, revision_builder)) => {
-                                    #[cfg(feature = "strict")]
-                                    {
-                                        tracing::error!(
-                                            message = "Missing mandatory field in revision",
-                                            field,
-                                            partial_revision = ?revision_builder,
-                                            revision_end_position = self.xml_parser.buffer_position()
-                                        );
-                                        return Err(ParsingError::MissingField(field));
-                                    }
-                                    #[cfg(not(feature = "strict"))]
-                                    {
-                                        tracing::warn!(
-                                            message = "Ignoring revision with missing mandatory field",
-                                            field,
-                                            partial_revision = ?revision_builder,
-                                            revision_end_position = self.xml_parser.buffer_position()
-                                        );
-                                        continue;
-                                    }
+                                    tracing::debug!(partial_revision = ?revision_builder);
+                                    self.diagnostics
+                                        .report(DiagnosticKind::MissingField(field), position)?;
+                                    self.diagnostics.revision_id = None;
+                                    continue;
                                 }
                             };
+                            if self.verify_sha1 {
+                                self.verify_revision_sha1(&mut revision, position)?;
+                            }
+                            if !revision_ids.insert(revision.id) {
+                                self.diagnostics.report(
+                                    DiagnosticKind::DuplicateRevisionId(revision.id),
+                                    position,
+                                )?;
+                            }
                             page.revisions.push(revision);
                         }
+                        self.diagnostics.revision_id = None;
                     }
 
                     if tag == Some(Tag::Page) {
@@ -964,35 +1814,28 @@ impl<R: BufRead> DumpParser<R> {
                 quick_xml::events::Event::Eof => {
                     if started_page {
                         tracing::error!(message = "Unexpected end of file", partial_page = ?page, current_path = ?self.current_path);
-                        return Err(ParsingError::Eof);
+                        return Err(ParsingErrorKind::Eof.into());
                     } else {
-                        #[cfg(feature = "strict")]
-                        if !self.current_path.is_empty() {
+                        // a fragment ends inside the `<mediawiki>` tag
+                        let unclosed_tags = if self.is_fragment {
+                            !matches!(self.current_path.as_slice(), [Tag::MediaWiki])
+                        } else {
+                            !self.current_path.is_empty()
+                        };
+                        if self.diagnostics.strict && unclosed_tags {
                             tracing::error!(
                                 message = "Unexpected end of file",
                                 current_path = ?self.current_path
                             );
-                            return Err(ParsingError::Eof);
+                            return Err(ParsingErrorKind::Eof.into());
                         }
                         return Ok(None);
                     }
                 }
                 _ => {}
             }
-            self.buf.clear();
         }
 
         Ok(Some(page))
     }
-
-    pub fn parse_single_page(reader: R, read_bytes: &mut usize) -> Result<Page, ParsingError> {
-        let mut parser = Self::new_impl(reader, false);
-        parser.current_path.push(Tag::MediaWiki);
-
-        let page = parser.parse_page()?.ok_or(ParsingError::Eof)?;
-
-        *read_bytes = parser.bytes_consumed() as usize;
-
-        Ok(page)
-    }
 }
@@ -0,0 +1,359 @@
+// SPDX-License-Identifier: MPL-2.0
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Debug,
+    io::BufRead,
+    sync::{
+        mpsc::{sync_channel, Receiver, SyncSender},
+        Arc, Mutex,
+    },
+};
+
+use super::{Diagnostic, DumpParser, Page, ParserOptions, ParsingError, SiteInfo};
+
+// the closing tag can only appear as markup: inside text and attribute values `<` must be escaped
+pub(super) const PAGE_END: &[u8] = b"</page>";
+
+/// Chunks are cut at the first page boundary after this many bytes.
+///
+/// Batching several small pages into one chunk keeps the channel and thread wake-up
+/// overhead low, while large pages simply end up as a chunk of their own.
+const CHUNK_TARGET_SIZE: usize = 4 * 1024 * 1024;
+
+/// A chunk of the XML stream, containing zero or more complete `<page>` elements.
+struct Chunk {
+    sequence: u64,
+    /// Offset of the chunk start in the decompressed stream
+    offset: u64,
+    data: Vec<u8>,
+}
+
+pub(super) type PageResult = Result<(Page, Vec<Diagnostic>), ParsingError>;
+
+/// Result of parsing one [`Chunk`].
+///
+/// Contains the parsed pages in document order, followed by at most one unrecoverable error.
+/// Recoverable errors in strict mode appear in place of the page they belong to.
+struct ChunkResult {
+    sequence: u64,
+    pages: Vec<PageResult>,
+}
+
+/// A parser that splits the XML stream at `<page>` boundaries and parses the pages on
+/// multiple threads.
+///
+/// The `<siteinfo>` header is parsed on the calling thread during construction, just like
+/// [`DumpParser::new`]. Afterwards a splitter thread reads the remaining stream, cuts it into
+/// chunks of whole pages and hands them to a pool of parser threads, each running
+/// [`DumpParser`] on its own chunk. Pages are returned in document order.
+///
+/// Since only a cheap byte search runs on the splitter thread, parsing throughput scales with
+/// the number of parser threads until decompression or reading the input becomes the bottleneck.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use wikiwho::dump_parser::ParallelDumpReader;
+/// use std::io::BufReader;
+/// use std::fs::File;
+///
+/// let reader = BufReader::new(File::open("dump.xml").unwrap());
+/// let mut parser = ParallelDumpReader::new(reader, 4).unwrap();
+///
+/// while let Some(page) = parser.parse_page().unwrap() {
+///     println!("{}", page.title);
+/// }
+/// ```
+pub struct ParallelDumpReader {
+    site_info: SiteInfo,
+    results: Receiver<ChunkResult>,
+    /// returns a credit to the splitter thread for each chunk handed out in order
+    credits: SyncSender<()>,
+
+    next_sequence: u64,
+    out_of_order: BTreeMap<u64, ChunkResult>,
+    current_pages: VecDeque<PageResult>,
+    current_diagnostics: Vec<Diagnostic>,
+    finished: bool,
+}
+
+impl Debug for ParallelDumpReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParallelDumpReader")
+            .field("site_info", &self.site_info)
+            .field("next_sequence", &self.next_sequence)
+            .field("out_of_order.len", &self.out_of_order.len())
+            .field("current_pages.len", &self.current_pages.len())
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
+impl ParallelDumpReader {
+    /// Creates a new parallel parser from the given buffered reader, using `num_threads`
+    /// parser threads (at least one).
+    ///
+    /// Immediately parses the `<siteinfo>` header from the XML stream on the calling thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParsingError`] if the XML is malformed or the `<siteinfo>` block
+    /// cannot be parsed.
+    pub fn new<R: BufRead + Send + 'static>(
+        reader: R,
+        num_threads: usize,
+    ) -> Result<Self, ParsingError> {
+        Self::with_options(reader, num_threads, ParserOptions::default())
+    }
+
+    /// Creates a new parallel parser like [`ParallelDumpReader::new`], using the given options.
+    pub fn with_options<R: BufRead + Send + 'static>(
+        reader: R,
+        num_threads: usize,
+        options: ParserOptions,
+    ) -> Result<Self, ParsingError> {
+        let num_threads = num_threads.max(1);
+
+        let parser = DumpParser::with_options(reader, options.clone())?;
+        let site_info = parser.site_info().clone();
+        let site_info_diagnostics = parser.diagnostics().to_vec();
+        let header_len = parser.bytes_consumed();
+        let reader = parser.into_inner();
+
+        // bounded channels give back-pressure, the credits additionally bound the number of
+        // chunks that can pile up out of order behind a single slow chunk
+        let max_in_flight = num_threads * 2;
+        let (work_tx, work_rx) = sync_channel::<Chunk>(num_threads);
+        let (result_tx, result_rx) = sync_channel::<ChunkResult>(max_in_flight);
+        let (credit_tx, credit_rx) = sync_channel::<()>(max_in_flight);
+        for _ in 0..max_in_flight {
+            credit_tx.send(()).expect("channel has enough capacity");
+        }
+
+        let work_rx = Arc::new(Mutex::new(work_rx));
+        for n in 0..num_threads {
+            let work_rx = work_rx.clone();
+            let result_tx = result_tx.clone();
+            let options = options.clone();
+            std::thread::Builder::new()
+                .name(format!("dump parser {n}"))
+                .spawn(move || parse_chunks(work_rx, result_tx, options))?;
+        }
+
+        let splitter_result_tx = result_tx;
+        std::thread::Builder::new()
+            .name("dump splitter".to_string())
+            .spawn(move || {
+                split_pages(reader, header_len, work_tx, credit_rx, splitter_result_tx)
+            })?;
+
+        Ok(Self {
+            site_info,
+            results: result_rx,
+            credits: credit_tx,
+            next_sequence: 0,
+            out_of_order: BTreeMap::new(),
+            current_pages: VecDeque::new(),
+            current_diagnostics: site_info_diagnostics,
+            finished: false,
+        })
+    }
+
+    /// Returns the site information parsed from the `<siteinfo>` header.
+    pub fn site_info(&self) -> &SiteInfo {
+        &self.site_info
+    }
+
+    /// Returns the diagnostics reported while parsing the most recently returned page,
+    /// or the `<siteinfo>` header if no page has been returned yet.
+    ///
+    /// See [`DumpParser::diagnostics`].
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.current_diagnostics
+    }
+
+    /// Returns the next `<page>` element in document order.
+    ///
+    /// Has the same return values as [`DumpParser::parse_page`]. After an error that is not
+    /// [recoverable](ParsingError::is_recoverable) has been returned, all following calls
+    /// return `Ok(None)`.
+    pub fn parse_page(&mut self) -> Result<Option<Page>, ParsingError> {
+        loop {
+            if let Some(result) = self.current_pages.pop_front() {
+                self.current_diagnostics.clear();
+                match result {
+                    Ok((page, diagnostics)) => {
+                        self.current_diagnostics = diagnostics;
+                        return Ok(Some(page));
+                    }
+                    Err(error) => {
+                        if !error.is_recoverable() {
+                            self.finished = true;
+                        }
+                        return Err(error);
+                    }
+                }
+            }
+            self.current_diagnostics.clear();
+            if self.finished {
+                return Ok(None);
+            }
+
+            let result = match self.out_of_order.remove(&self.next_sequence) {
+                Some(result) => result,
+                None => match self.results.recv() {
+                    Ok(result) if result.sequence != self.next_sequence => {
+                        self.out_of_order.insert(result.sequence, result);
+                        continue;
+                    }
+                    Ok(result) => result,
+                    Err(_) => {
+                        // all threads finished and every chunk has been handed out
+                        self.finished = true;
+                        return Ok(None);
+                    }
+                },
+            };
+
+            self.next_sequence += 1;
+            self.current_pages = result.pages.into();
+            // allow the splitter to send another chunk; fails only if it already finished
+            let _ = self.credits.try_send(());
+        }
+    }
+}
+
+impl Iterator for ParallelDumpReader {
+    type Item = Result<Page, ParsingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parse_page().transpose()
+    }
+}
+
+fn parse_chunks(
+    work_rx: Arc<Mutex<Receiver<Chunk>>>,
+    result_tx: SyncSender<ChunkResult>,
+    options: ParserOptions,
+) {
+    loop {
+        let chunk = match work_rx.lock().unwrap().recv() {
+            Ok(chunk) => chunk,
+            Err(_) => break, // channel closed, no more work
+        };
+
+        let result = ChunkResult {
+            sequence: chunk.sequence,
+            pages: parse_fragment(&chunk.data, chunk.offset, options.clone()),
+        };
+        // if the consumer has dropped, stop
+        if result_tx.send(result).is_err() {
+            break;
+        }
+    }
+}
+
+/// Parses all pages of a fragment of the dump (see [`DumpParser::new_fragment`]) that starts
+/// at `offset` in the stream.
+///
+/// The pages are returned in document order, followed by at most one unrecoverable error.
+pub(super) fn parse_fragment(data: &[u8], offset: u64, options: ParserOptions) -> Vec<PageResult> {
+    let mut parser = DumpParser::new_fragment(data, options, offset);
+    let mut pages = Vec::new();
+    loop {
+        match parser.parse_page() {
+            Ok(Some(page)) => pages.push(Ok((page, parser.diagnostics().to_vec()))),
+            Ok(None) => break,
+            Err(e) => {
+                let recoverable = e.is_recoverable();
+                pages.push(Err(e));
+                if !recoverable {
+                    break;
+                }
+            }
+        }
+    }
+    pages
+}
+
+fn split_pages<R: BufRead>(
+    mut reader: R,
+    mut offset: u64,
+    work_tx: SyncSender<Chunk>,
+    credit_rx: Receiver<()>,
+    result_tx: SyncSender<ChunkResult>,
+) {
+    let mut sequence = 0;
+    let mut pending = Vec::new();
+    // everything before this index has already been searched for a page boundary
+    let mut searched: usize = 0;
+    // end of the last page boundary found in `pending`
+    let mut last_boundary: usize = 0;
+
+    loop {
+        let buf = match reader.fill_buf() {
+            Ok(buf) => buf,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                // report the error in document order, after all chunks sent so far
+                let _ = result_tx.send(ChunkResult {
+                    sequence,
+                    pages: vec![Err(e.into())],
+                });
+                return;
+            }
+        };
+        let eof = buf.is_empty();
+        pending.extend_from_slice(buf);
+        let read = buf.len();
+        reader.consume(read);
+
+        let search_start = searched.saturating_sub(PAGE_END.len() - 1);
+        if let Some(end) = find_last(&pending[search_start..], PAGE_END) {
+            last_boundary = search_start + end + PAGE_END.len();
+        }
+        searched = pending.len();
+
+        if eof && find_last(&pending[last_boundary..], b"<page").is_none() {
+            // drop the closing `</mediawiki>` tag, the fragment parsers never saw it opened
+            // (a truncated page is kept so that the parser reports the error)
+            pending.truncate(last_boundary);
+        }
+
+        if (last_boundary >= CHUNK_TARGET_SIZE) || (eof && !pending.is_empty()) {
+            let data = if eof {
+                std::mem::take(&mut pending)
+            } else {
+                let rest = pending.split_off(last_boundary);
+                std::mem::replace(&mut pending, rest)
+            };
+            searched = pending.len();
+            last_boundary = 0;
+
+            if credit_rx.recv().is_err() {
+                return; // consumer gone
+            }
+            let len = data.len() as u64;
+            if work_tx
+                .send(Chunk {
+                    sequence,
+                    offset,
+                    data,
+                })
+                .is_err()
+            {
+                return; // workers gone
+            }
+            sequence += 1;
+            offset += len;
+        }
+
+        if eof {
+            return;
+        }
+    }
+}
+
+pub(super) fn find_last(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
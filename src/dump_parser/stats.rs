@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: MPL-2.0
+//! Dump-level statistics for sizing a job before running the analysis.
+//!
+//! [`DumpStats`] counts pages, revisions, text bytes and anonymous edits per namespace and
+//! keeps the largest pages. Parse with [`ParserOptions::skip_text`](super::ParserOptions::skip_text)
+//! to take the text sizes from the `bytes` attributes instead of decoding the text.
+//!
+//! ```rust,no_run
+//! use wikiwho::dump_parser::{stats::DumpStats, DumpParser, ParserOptions};
+//!
+//! let reader = std::io::BufReader::new(std::fs::File::open("dump.xml").unwrap());
+//! let options = ParserOptions::new().skip_text(true);
+//! let mut parser = DumpParser::with_options(reader, options).unwrap();
+//! let stats = DumpStats::from_parser(&mut parser, 10).unwrap();
+//! println!(
+//!     "{} pages, {} revisions, {:.1}% anonymous",
+//!     stats.pages,
+//!     stats.revisions,
+//!     stats.anonymous_fraction() * 100.0
+//! );
+//! ```
+use std::{collections::BTreeMap, io::BufRead};
+
+use compact_str::CompactString;
+
+use super::{DumpParser, Page, ParsingError, Revision, Text};
+
+/// Summary of the pages of a dump.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct DumpStats {
+    pub pages: u64,
+    pub revisions: u64,
+    /// Size of the text of all revisions and slots, in bytes.
+    pub text_bytes: u64,
+    /// Revisions by unregistered users, i.e. with an IP address instead of a user id.
+    pub anonymous_revisions: u64,
+    /// Revisions with deleted (hidden) text.
+    pub deleted_texts: u64,
+    pub max_revisions_per_page: u64,
+    /// Number of pages by their number of revisions, in power-of-two buckets:
+    /// the key is the lower bound, e.g. `4` counts pages with 4 to 7 revisions.
+    pub revisions_per_page: BTreeMap<u64, u64>,
+    pub namespaces: BTreeMap<i32, NamespaceStats>,
+    /// The pages with the most text, largest first.
+    pub largest_pages: Vec<PageSummary>,
+    /// Number of pages kept in `largest_pages`, serialized so that deserialized statistics
+    /// keep tracking the largest pages in [`add_page`](Self::add_page).
+    largest_pages_limit: usize,
+}
+
+/// Counts for the pages of one namespace, see [`DumpStats::namespaces`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct NamespaceStats {
+    pub pages: u64,
+    pub revisions: u64,
+    pub text_bytes: u64,
+    pub anonymous_revisions: u64,
+}
+
+/// A page in [`DumpStats::largest_pages`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct PageSummary {
+    pub title: CompactString,
+    pub namespace: i32,
+    pub revisions: u64,
+    pub text_bytes: u64,
+}
+
+impl DumpStats {
+    /// Creates empty statistics that keep the `largest_pages` pages with the most text.
+    pub fn new(largest_pages: usize) -> Self {
+        Self {
+            largest_pages_limit: largest_pages,
+            ..Self::default()
+        }
+    }
+
+    /// Parses the remaining pages of `parser` and collects their statistics.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`ParsingError`], including recoverable ones in strict mode.
+    pub fn from_parser<R: BufRead>(
+        parser: &mut DumpParser<R>,
+        largest_pages: usize,
+    ) -> Result<Self, ParsingError> {
+        let mut stats = Self::new(largest_pages);
+        while let Some(page) = parser.parse_page()? {
+            stats.add_page(&page);
+        }
+        Ok(stats)
+    }
+
+    pub fn add_page(&mut self, page: &Page) {
+        let revisions = page.revisions.len() as u64;
+        let text_bytes: u64 = page.revisions.iter().map(revision_text_bytes).sum();
+        let anonymous_revisions = page.revisions.iter().filter(|r| is_anonymous(r)).count() as u64;
+
+        self.pages += 1;
+        self.revisions += revisions;
+        self.text_bytes += text_bytes;
+        self.anonymous_revisions += anonymous_revisions;
+        self.deleted_texts += page
+            .revisions
+            .iter()
+            .filter(|r| r.text == Text::Deleted)
+            .count() as u64;
+        self.max_revisions_per_page = self.max_revisions_per_page.max(revisions);
+        let bucket = if revisions == 0 {
+            0
+        } else {
+            1 << revisions.ilog2()
+        };
+        *self.revisions_per_page.entry(bucket).or_default() += 1;
+
+        let namespace = self.namespaces.entry(page.namespace).or_default();
+        namespace.pages += 1;
+        namespace.revisions += revisions;
+        namespace.text_bytes += text_bytes;
+        namespace.anonymous_revisions += anonymous_revisions;
+
+        // keep the list sorted, largest first; ties keep the page seen first
+        let index = self
+            .largest_pages
+            .partition_point(|p| p.text_bytes >= text_bytes);
+        if index < self.largest_pages_limit {
+            self.largest_pages.insert(
+                index,
+                PageSummary {
+                    title: page.title.clone(),
+                    namespace: page.namespace,
+                    revisions,
+                    text_bytes,
+                },
+            );
+            self.largest_pages.truncate(self.largest_pages_limit);
+        }
+    }
+
+    /// Returns the average number of revisions of a page.
+    pub fn mean_revisions_per_page(&self) -> f64 {
+        if self.pages == 0 {
+            0.0
+        } else {
+            self.revisions as f64 / self.pages as f64
+        }
+    }
+
+    /// Returns the fraction of revisions by unregistered users.
+    pub fn anonymous_fraction(&self) -> f64 {
+        if self.revisions == 0 {
+            0.0
+        } else {
+            self.anonymous_revisions as f64 / self.revisions as f64
+        }
+    }
+}
+
+/// Size of the text of the revision and all its slots, from the `bytes` attribute for stubs.
+fn revision_text_bytes(revision: &Revision) -> u64 {
+    std::iter::once(&revision.text)
+        .chain(revision.slots.iter().map(|slot| &slot.text))
+        .map(|text| match text {
+            Text::Stub { bytes, .. } => bytes.unwrap_or(0),
+            text => text.len() as u64,
+        })
+        .sum()
+}
+
+/// Like `wikiwho-cli`, which writes such editors as `0|<IP address>`.
+fn is_anonymous(revision: &Revision) -> bool {
+    !revision.contributor.deleted && revision.contributor.id.is_none_or(|id| id == 0)
+}
@@ -0,0 +1,131 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::{Revision, Text, MAIN_SLOT};
+
+/// A source for revision text that is not contained in the dump, e.g. a local content store
+/// for the revisions of a [stub dump](super::ParserOptions::stub).
+///
+/// [`fill_texts`](RevisionTextProvider::fill_texts) loads the text lazily, one revision at a
+/// time, while the revisions are fed into the analysis. This way only the texts of the
+/// revision currently being analysed are kept in memory.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::{fs, io, path::PathBuf};
+/// use wikiwho::{
+///     algorithm::PageAnalysis,
+///     dump_parser::{DumpParser, ParserOptions, Revision, RevisionTextProvider},
+/// };
+///
+/// /// Reads the text of every revision from `<directory>/<revision id>.txt`.
+/// struct TextDirectory(PathBuf);
+///
+/// impl RevisionTextProvider for TextDirectory {
+///     type Error = io::Error;
+///
+///     fn revision_text(&mut self, revision: &Revision, _role: &str) -> io::Result<Option<String>> {
+///         let path = self.0.join(format!("{}.txt", revision.id));
+///         match fs::read_to_string(path) {
+///             Ok(text) => Ok(Some(text)),
+///             Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+///             Err(e) => Err(e),
+///         }
+///     }
+/// }
+///
+/// let reader = io::BufReader::new(fs::File::open("stub-meta-history.xml").unwrap());
+/// let mut parser = DumpParser::with_options(reader, ParserOptions::new().stub(true)).unwrap();
+/// let mut texts = TextDirectory(PathBuf::from("texts"));
+///
+/// while let Some(page) = parser.parse_page().unwrap() {
+///     let mut revisions = texts.fill_texts(page.revisions);
+///     let analysis = PageAnalysis::analyse_page(&mut revisions);
+///     revisions.finish().unwrap();
+///     // ...
+/// }
+/// ```
+pub trait RevisionTextProvider {
+    type Error;
+
+    /// Returns the text of the slot `role` ([`MAIN_SLOT`] for [`Revision::text`]) of `revision`.
+    ///
+    /// Only called for [`Text::Stub`] texts. Returning `None` keeps the placeholder,
+    /// which makes the analysis skip the revision like a revision with deleted text.
+    fn revision_text(
+        &mut self,
+        revision: &Revision,
+        role: &str,
+    ) -> Result<Option<String>, Self::Error>;
+
+    /// Replaces every [`Text::Stub`] of `revision`, in the main and all other slots,
+    /// with the text returned by [`revision_text`](RevisionTextProvider::revision_text).
+    fn fill_text(&mut self, revision: &mut Revision) -> Result<(), Self::Error> {
+        if matches!(revision.text, Text::Stub { .. }) {
+            if let Some(text) = self.revision_text(revision, MAIN_SLOT)? {
+                revision.text = Text::Normal(text);
+            }
+        }
+        for index in 0..revision.slots.len() {
+            let slot = &revision.slots[index];
+            if matches!(slot.text, Text::Stub { .. }) {
+                if let Some(text) = self.revision_text(revision, &slot.role)? {
+                    revision.slots[index].text = Text::Normal(text);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns an iterator over `revisions` that fills in the text of each revision
+    /// right before it is returned, see [`FillTexts`].
+    fn fill_texts<I>(&mut self, revisions: I) -> FillTexts<'_, Self, I::IntoIter>
+    where
+        I: IntoIterator<Item = Revision>,
+        Self: Sized,
+    {
+        FillTexts {
+            provider: self,
+            revisions: revisions.into_iter(),
+            error: None,
+        }
+    }
+}
+
+/// Iterator returned by [`RevisionTextProvider::fill_texts`].
+///
+/// The first error of the provider ends the iteration. Pass the iterator by reference
+/// (`&mut revisions`) to the analysis and check [`finish`](FillTexts::finish) afterwards,
+/// an analysis of a history that was cut short by an error is incomplete.
+pub struct FillTexts<'a, P: RevisionTextProvider, I> {
+    provider: &'a mut P,
+    revisions: I,
+    error: Option<P::Error>,
+}
+
+impl<P: RevisionTextProvider, I> FillTexts<'_, P, I> {
+    /// Returns the error that ended the iteration, if any.
+    pub fn finish(self) -> Result<(), P::Error> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<P: RevisionTextProvider, I: Iterator<Item = Revision>> Iterator for FillTexts<'_, P, I> {
+    type Item = Revision;
+
+    fn next(&mut self) -> Option<Revision> {
+        if self.error.is_some() {
+            return None;
+        }
+        let mut revision = self.revisions.next()?;
+        match self.provider.fill_text(&mut revision) {
+            Ok(()) => Some(revision),
+            Err(error) => {
+                self.error = Some(error);
+                None
+            }
+        }
+    }
+}
@@ -1,44 +1,224 @@
 // SPDX-License-Identifier: MPL-2.0
-use std::{collections::HashMap, fmt::Debug};
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    ops::{Deref, Range},
+    str::Utf8Error,
+    sync::Arc,
+};
 
 use compact_str::CompactString;
+use sha1::{Digest, Sha1};
+use yoke::Yoke;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Contributor {
+    /// User name, or IP address for anonymous edits. Empty if the contributor is deleted.
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub username: CompactString,
     pub id: Option<i32>,
+    /// The contributor was hidden by an admin (revision deletion or suppression),
+    /// written as `<contributor deleted="deleted" />` in dumps.
+    ///
+    /// Dumps don't tell deleted and suppressed contributors apart.
+    /// A deleted contributor has an empty `username` and no `id`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub deleted: bool,
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+impl Contributor {
+    /// A contributor hidden by revision deletion or suppression.
+    pub fn deleted() -> Self {
+        Self {
+            username: CompactString::default(),
+            id: None,
+            deleted: true,
+        }
+    }
+}
+
+/// Edit summary of a revision.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Comment {
+    Normal(#[cfg_attr(feature = "schema", schemars(with = "String"))] CompactString),
+    /// The comment was hidden by an admin (revision deletion or suppression),
+    /// written as `<comment deleted="deleted" />` in dumps.
+    ///
+    /// Like mwxml, the analysis treats a deleted comment as if the revision had no comment.
+    Deleted,
+}
+
+impl Comment {
+    /// Returns the comment if it is visible.
+    pub fn as_visible(&self) -> Option<&str> {
+        match self {
+            Comment::Normal(comment) => Some(comment.as_str()),
+            Comment::Deleted => None,
+        }
+    }
+}
+
+/// Besides its own representation, deserializing from a human-readable format accepts the
+/// bare string that comments were serialized as before deleted comments were modelled.
+///
+/// An old edit summary that is literally `Deleted` can't be told apart from the
+/// representation of [`Comment::Deleted`] and is read as a deleted comment, i.e. it is lost.
+/// Binary formats only accept the current representation.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Comment {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "Comment")]
+        enum Tagged {
+            Normal(CompactString),
+            Deleted,
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Compatible {
+            Tagged(Tagged),
+            Bare(CompactString),
+        }
+
+        // untagged enums need a self-describing format, which binary formats aren't
+        let tagged = if deserializer.is_human_readable() {
+            match Compatible::deserialize(deserializer)? {
+                Compatible::Tagged(tagged) => tagged,
+                Compatible::Bare(comment) => Tagged::Normal(comment),
+            }
+        } else {
+            Tagged::deserialize(deserializer)?
+        };
+        Ok(match tagged {
+            Tagged::Normal(comment) => Comment::Normal(comment),
+            Tagged::Deleted => Comment::Deleted,
+        })
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
 pub enum Text {
     Normal(String),
+    /// Text borrowed from a shared input buffer instead of copied, e.g. from the memory-mapped
+    /// dump of a `MmapDumpParser` (with the `mmap` feature).
+    ///
+    /// Equal to, and serialized like, [`Text::Normal`] with the same content.
+    #[cfg_attr(feature = "serde", serde(skip_deserializing))]
+    Shared(SharedStr),
     Deleted,
+    /// Placeholder for text the dump doesn't contain, i.e. the `<text bytes="..." id="..." />`
+    /// of a stub dump parsed with [`ParserOptions::stub`](super::ParserOptions::stub).
+    ///
+    /// The analysis skips revisions with stub text like revisions with deleted text.
+    /// A [`RevisionTextProvider`](super::RevisionTextProvider) fills in the actual text.
+    Stub {
+        /// Length of the text in bytes, from the `bytes` attribute
+        bytes: Option<u64>,
+        /// Address of the text in the content store, from the `location` or `id` attribute
+        location: Option<CompactString>,
+    },
 }
 
-impl Text {
-    pub fn len(&self) -> usize {
+/// Written by hand so that [`Text::Shared`] is serialized as [`Text::Normal`] and the other
+/// variants keep the indices they had before it was added, which binary formats rely on.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Text {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStructVariant;
+
         match self {
-            Text::Normal(text) => text.len(),
-            Text::Deleted => 0,
+            Text::Normal(_) | Text::Shared(_) => {
+                serializer.serialize_newtype_variant("Text", 0, "Normal", self.as_str())
+            }
+            Text::Deleted => serializer.serialize_unit_variant("Text", 1, "Deleted"),
+            Text::Stub { bytes, location } => {
+                let mut stub = serializer.serialize_struct_variant("Text", 2, "Stub", 2)?;
+                stub.serialize_field("bytes", bytes)?;
+                stub.serialize_field("location", location)?;
+                stub.end()
+            }
         }
     }
+}
+
+impl Text {
+    pub fn len(&self) -> usize {
+        self.as_str().len()
+    }
 
     pub fn is_empty(&self) -> bool {
-        match self {
-            Text::Normal(text) => text.is_empty(),
-            Text::Deleted => true,
-        }
+        self.as_str().is_empty()
     }
 
     /// Returns the text as a string slice.
     ///
-    /// If the text is [`Text::Deleted`], an empty string is returned.
+    /// If the text is [`Text::Deleted`] or a [`Text::Stub`], an empty string is returned.
     pub fn as_str(&self) -> &str {
+        self.content().unwrap_or_default()
+    }
+
+    /// Returns the text, or `None` if it is [`Text::Deleted`] or a [`Text::Stub`].
+    pub fn content(&self) -> Option<&str> {
+        match self {
+            Text::Normal(text) => Some(text.as_str()),
+            Text::Shared(text) => Some(text.as_str()),
+            Text::Deleted | Text::Stub { .. } => None,
+        }
+    }
+
+    /// Returns the length of the text in bytes, for a [`Text::Stub`] from its `bytes` attribute.
+    ///
+    /// `None` for [`Text::Deleted`] and stubs without the attribute.
+    pub fn byte_len(&self) -> Option<u64> {
+        match self {
+            Text::Stub { bytes, .. } => *bytes,
+            _ => self.content().map(|text| text.len() as u64),
+        }
+    }
+}
+
+impl PartialEq for Text {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Text::Deleted, Text::Deleted) => true,
+            (
+                Text::Stub { bytes, location },
+                Text::Stub {
+                    bytes: other_bytes,
+                    location: other_location,
+                },
+            ) => bytes == other_bytes && location == other_location,
+            (Text::Normal(_) | Text::Shared(_), Text::Normal(_) | Text::Shared(_)) => {
+                self.as_str() == other.as_str()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Text {}
+
+impl Hash for Text {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // consistent with `PartialEq`: normal and shared text with the same content are equal
         match self {
-            Text::Normal(text) => text.as_str(),
-            Text::Deleted => "",
+            Text::Normal(_) | Text::Shared(_) => {
+                0u8.hash(state);
+                self.as_str().hash(state);
+            }
+            Text::Deleted => 1u8.hash(state),
+            Text::Stub { bytes, location } => {
+                2u8.hash(state);
+                bytes.hash(state);
+                location.hash(state);
+            }
         }
     }
 }
@@ -47,13 +227,90 @@ impl Debug for Text {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Text::Normal(text) => write!(f, "{:?}", text),
+            Text::Shared(text) => write!(f, "{:?}", text.as_str()),
             Text::Deleted => write!(f, "Deleted"),
+            Text::Stub { bytes, location } => f
+                .debug_struct("Stub")
+                .field("bytes", bytes)
+                .field("location", location)
+                .finish(),
         }
     }
 }
 
+/// An input buffer that [`SharedStr`]s borrow from, e.g. a memory-mapped file.
+pub type SharedBuffer = Arc<dyn AsRef<[u8]> + Send + Sync>;
+
+/// A string slice of a [`SharedBuffer`], which it keeps alive.
+///
+/// Cloning only increments the reference count of the buffer.
+#[derive(Clone)]
+pub struct SharedStr(Yoke<&'static str, SharedBuffer>);
+
+impl SharedStr {
+    /// Returns the bytes `range` of `buffer` as a string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are not valid UTF-8.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds of the buffer.
+    pub fn new(buffer: SharedBuffer, range: Range<usize>) -> Result<Self, Utf8Error> {
+        Yoke::try_attach_to_cart(buffer, |buffer| {
+            std::str::from_utf8(&(*buffer).as_ref()[range])
+        })
+        .map(Self)
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.get()
+    }
+
+    pub fn buffer(&self) -> &SharedBuffer {
+        self.0.backing_cart()
+    }
+}
+
+impl Deref for SharedStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Debug for SharedStr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for SharedStr {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for SharedStr {}
+
+impl Hash for SharedStr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SharedStr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Sha1Hash(
     /// 31 bytes, sha1 hash -> base36 encoded -> as ASCII bytes
     ///
@@ -61,6 +318,48 @@ pub struct Sha1Hash(
     pub [u8; 31],
 );
 
+impl Sha1Hash {
+    /// Computes the hash of `text` the way MediaWiki does:
+    /// the SHA1 digest, base36 encoded and 0-padded to 31 characters.
+    pub fn compute(text: &str) -> Self {
+        Self::from_digest(Sha1::digest(text.as_bytes()).into())
+    }
+
+    /// Converts a hexadecimal SHA1 digest, as returned by the MediaWiki Action API,
+    /// to the base36 form used in dumps.
+    ///
+    /// Returns `None` if `hex` is not 40 hexadecimal digits.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.as_bytes();
+        if hex.len() != 40 || !hex.iter().all(u8::is_ascii_hexdigit) {
+            return None;
+        }
+        let mut digest = [0; 20];
+        for (byte, pair) in digest.iter_mut().zip(hex.chunks_exact(2)) {
+            let pair = std::str::from_utf8(pair).ok()?;
+            *byte = u8::from_str_radix(pair, 16).ok()?;
+        }
+        Some(Self::from_digest(digest))
+    }
+
+    fn from_digest(mut number: [u8; 20]) -> Self {
+        const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+        let mut encoded = [b'0'; 31];
+        // long division of the 160 bit number by 36, the remainders are the digits from the right
+        for digit in encoded.iter_mut().rev() {
+            let mut remainder = 0u32;
+            for byte in number.iter_mut() {
+                let value = (remainder << 8) | *byte as u32;
+                *byte = (value / 36) as u8;
+                remainder = value % 36;
+            }
+            *digit = DIGITS[remainder as usize];
+        }
+        Self(encoded)
+    }
+}
+
 impl Debug for Sha1Hash {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Ok(as_str) = std::str::from_utf8(&self.0) {
@@ -79,38 +378,215 @@ pub struct Revision {
     pub timestamp: chrono::DateTime<chrono::Utc>,
     // aka. user
     pub contributor: Contributor,
+    /// Text of the main slot.
     pub text: Text,
+    /// Hash of the revision. With [`slots`](Revision::slots) it combines the hashes of all
+    /// slots, so the analysis hashes the main text itself then.
     pub sha1: Option<Sha1Hash>,
-    pub comment: Option<CompactString>,
+    /// `None` if the revision has no `<comment>` tag.
+    pub comment: Option<Comment>,
     pub minor: bool,
+    /// Content model of the main slot, e.g. `wikitext`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub model: Option<CompactString>,
+    /// Content format of the main slot, e.g. `text/x-wiki`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub format: Option<CompactString>,
+    /// Slots other than the main slot, in document order (multi-content revisions).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub slots: Vec<Slot>,
+}
+
+impl Revision {
+    /// Returns the text of the slot with the given role, or `None` if the revision has no such slot.
+    ///
+    /// The text of the [`MAIN_SLOT`] is [`Revision::text`].
+    pub fn slot_text(&self, role: &str) -> Option<&Text> {
+        if role == MAIN_SLOT {
+            Some(&self.text)
+        } else {
+            self.slots
+                .iter()
+                .find(|slot| slot.role == role)
+                .map(|slot| &slot.text)
+        }
+    }
+}
+
+/// Role of the main slot, which holds the page content in dumps without multi-content revisions.
+pub const MAIN_SLOT: &str = "main";
+
+/// A content slot of a multi-content revision (MCR) other than the main slot, from a
+/// `<content>` element, e.g. the structured data (`mediainfo`) of files on Wikimedia Commons.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Slot {
+    pub role: CompactString,
+    /// Content model, e.g. `wikibase-mediainfo`.
+    pub model: Option<CompactString>,
+    /// Content format, e.g. `application/json`.
+    pub format: Option<CompactString>,
+    pub text: Text,
+    /// Hash of the slot text, from the `sha1` attribute of its `<text>` tag.
+    pub sha1: Option<Sha1Hash>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Page {
+    /// The page id, `None` if the source has none.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub id: Option<i32>,
     pub title: CompactString,
     pub namespace: i32,
     pub revisions: Vec<Revision>,
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Default)]
-pub enum Namespace {
-    #[default]
-    Default,
-    Named(CompactString),
+impl Page {
+    /// Puts the revisions into the chronological order the analysis expects.
+    ///
+    /// Sorts the revisions by timestamp and then revision id, and drops exact copies of
+    /// a revision. Useful for pages glued together from several dump parts or API responses.
+    /// Of several revisions with the same id but different content, the one that comes last
+    /// is kept, assuming that later parts are more recent; the others are dropped and reported.
+    ///
+    /// Returns every anomaly that was found, an empty list means the revisions were already
+    /// in order and unique.
+    pub fn normalize_revisions(&mut self) -> Vec<RevisionAnomaly> {
+        let mut anomalies = Vec::new();
+
+        let key = |revision: &Revision| (revision.timestamp, revision.id);
+        let mut latest = None;
+        for revision in &self.revisions {
+            if latest.is_some_and(|latest| key(revision) < latest) {
+                anomalies.push(RevisionAnomaly::OutOfOrder(revision.id));
+            } else {
+                latest = Some(key(revision));
+            }
+        }
+
+        let mut unique: Vec<Revision> = Vec::with_capacity(self.revisions.len());
+        // index in `unique` of the revision with an id
+        let mut by_id: HashMap<i32, usize> = HashMap::new();
+        for revision in std::mem::take(&mut self.revisions) {
+            match by_id.get(&revision.id) {
+                Some(&i) if unique[i] == revision => {
+                    anomalies.push(RevisionAnomaly::Duplicate(revision.id));
+                }
+                Some(&i) => {
+                    anomalies.push(RevisionAnomaly::ConflictingId(revision.id));
+                    unique[i] = revision;
+                }
+                None => {
+                    by_id.insert(revision.id, unique.len());
+                    unique.push(revision);
+                }
+            }
+        }
+        unique.sort_by_key(key);
+        self.revisions = unique;
+
+        anomalies
+    }
+}
+
+/// A problem with the revisions of a page, found by [`Page::normalize_revisions`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum RevisionAnomaly {
+    /// The revision came after a newer revision and has been moved.
+    #[error("revision {0} was out of order")]
+    OutOfOrder(i32),
+    /// An exact copy of the revision has been dropped.
+    #[error("duplicate of revision {0} was dropped")]
+    Duplicate(i32),
+    /// Several revisions have this id but differ, an earlier one has been dropped in favour
+    /// of a later one.
+    #[error("conflicting revision with id {0} was dropped")]
+    ConflictingId(i32),
 }
 
-impl Debug for Namespace {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// Capitalization rule for page titles, from the `case` tag and attributes of the `<siteinfo>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Case {
+    /// `first-letter`: the first letter of a title is always uppercase.
+    FirstLetter,
+    /// `case-sensitive`: titles are used as they are.
+    CaseSensitive,
+}
+
+impl Case {
+    /// Parses the value used in dumps, e.g. `first-letter`.
+    pub fn from_dump_value(value: &str) -> Option<Self> {
+        match value {
+            "first-letter" => Some(Case::FirstLetter),
+            "case-sensitive" => Some(Case::CaseSensitive),
+            _ => None,
+        }
+    }
+
+    /// Returns the value used in dumps, e.g. `first-letter`.
+    pub fn as_dump_value(&self) -> &'static str {
         match self {
-            Namespace::Default => write!(f, "Default"),
-            Namespace::Named(name) => write!(f, "{:?}", name),
+            Case::FirstLetter => "first-letter",
+            Case::CaseSensitive => "case-sensitive",
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Namespace {
+    /// Name of the namespace, the prefix of page titles. Empty for the main namespace.
+    pub name: CompactString,
+    pub case: Option<Case>,
+}
+
+impl Namespace {
+    /// Returns `true` for a namespace without name, i.e. the main namespace.
+    pub fn is_default(&self) -> bool {
+        self.name.is_empty()
+    }
+}
+
+/// Version of the export schema, from the `version` attribute of the `<mediawiki>` tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SchemaVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl SchemaVersion {
+    /// Parses a version like `0.11`.
+    pub fn parse(version: &str) -> Option<Self> {
+        let (major, minor) = version.split_once('.')?;
+        Some(Self {
+            major: major.parse().ok()?,
+            minor: minor.parse().ok()?,
+        })
+    }
+}
+
+impl Display for SchemaVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct SiteInfo {
+    /// Export schema version of the dump, `None` if the `<mediawiki>` tag has no valid version.
+    pub schema_version: Option<SchemaVersion>,
+    /// Name of the site, e.g. `Wikipedia`.
+    pub sitename: CompactString,
+    /// Database name, e.g. `enwiki`.
     pub dbname: CompactString,
+    /// URL of the main page, e.g. `https://en.wikipedia.org/wiki/Main_Page`.
+    pub base: CompactString,
+    /// MediaWiki version that created the dump, e.g. `MediaWiki 1.43.0-wmf.20`.
+    pub generator: CompactString,
+    /// Default capitalization rule for page titles.
+    pub case: Option<Case>,
     pub namespaces: HashMap<i32, Namespace>,
 }
@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: MPL-2.0
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    net::IpAddr,
+};
+
+use compact_str::CompactString;
+use quick_xml::escape::escape;
+
+use super::{Comment, Contributor, Namespace, Page, Revision, Sha1Hash, SiteInfo, Text};
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// Writes pages as a Wikimedia XML dump (export schema 0.11) that can be read back
+/// with [`DumpParser`](super::DumpParser).
+///
+/// Only the data the parser keeps is written: parent ids, origins and
+/// other tags the parser ignores are omitted. Page titles get their namespace prefix back
+/// from the namespace names in the [`SiteInfo`]. The schema version is always written as `0.11`.
+///
+/// # Example
+///
+/// Copy all pages of the main namespace into a smaller dump:
+///
+/// ```rust,no_run
+/// use wikiwho::dump_parser::{DumpParser, DumpWriter};
+/// use std::io::{BufReader, BufWriter};
+/// use std::fs::File;
+///
+/// let reader = BufReader::new(File::open("dump.xml").unwrap());
+/// let mut parser = DumpParser::new(reader).unwrap();
+///
+/// let output = BufWriter::new(File::create("subset.xml").unwrap());
+/// let mut writer = DumpWriter::new(output, parser.site_info()).unwrap();
+///
+/// while let Some(page) = parser.parse_page().unwrap() {
+///     if page.namespace == 0 {
+///         writer.write_page(&page).unwrap();
+///     }
+/// }
+/// writer.finish().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct DumpWriter<W: Write> {
+    writer: W,
+    namespaces: HashMap<i32, Namespace>,
+}
+
+impl<W: Write> DumpWriter<W> {
+    /// Creates a new writer and writes the `<mediawiki>` start tag and the `<siteinfo>` header.
+    pub fn new(mut writer: W, site_info: &SiteInfo) -> io::Result<Self> {
+        writeln!(
+            writer,
+            r#"<mediawiki xmlns="http://www.mediawiki.org/xml/export-0.11/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://www.mediawiki.org/xml/export-0.11/ http://www.mediawiki.org/xml/export-0.11.xsd" version="0.11">"#
+        )?;
+        writeln!(writer, "  <siteinfo>")?;
+        for (tag, value) in [
+            ("sitename", &site_info.sitename),
+            ("dbname", &site_info.dbname),
+            ("base", &site_info.base),
+            ("generator", &site_info.generator),
+        ] {
+            if !value.is_empty() {
+                writeln!(writer, "    <{tag}>{}</{tag}>", escape(value))?;
+            }
+        }
+        if let Some(case) = site_info.case {
+            writeln!(writer, "    <case>{}</case>", case.as_dump_value())?;
+        }
+        writeln!(writer, "    <namespaces>")?;
+
+        let mut namespaces = site_info.namespaces.iter().collect::<Vec<_>>();
+        namespaces.sort_unstable_by_key(|(key, _)| **key);
+        for (key, namespace) in namespaces {
+            let case = namespace
+                .case
+                .map(|case| format!(r#" case="{}""#, case.as_dump_value()))
+                .unwrap_or_default();
+            if namespace.is_default() {
+                writeln!(writer, r#"      <namespace key="{key}"{case} />"#)?;
+            } else {
+                writeln!(
+                    writer,
+                    r#"      <namespace key="{key}"{case}>{}</namespace>"#,
+                    escape(&namespace.name)
+                )?;
+            }
+        }
+
+        writeln!(writer, "    </namespaces>")?;
+        writeln!(writer, "  </siteinfo>")?;
+
+        Ok(Self {
+            writer,
+            namespaces: site_info.namespaces.clone(),
+        })
+    }
+
+    /// Writes a `<page>` element with all of its revisions.
+    ///
+    /// To write only some revisions of a page, write a copy of the page
+    /// with the revisions filtered.
+    pub fn write_page(&mut self, page: &Page) -> io::Result<()> {
+        writeln!(self.writer, "  <page>")?;
+        match self.namespaces.get(&page.namespace) {
+            Some(namespace) if !namespace.is_default() => writeln!(
+                self.writer,
+                "    <title>{}:{}</title>",
+                escape(&namespace.name),
+                escape(&page.title)
+            )?,
+            _ => writeln!(self.writer, "    <title>{}</title>", escape(&page.title))?,
+        }
+        writeln!(self.writer, "    <ns>{}</ns>", page.namespace)?;
+        if let Some(id) = page.id {
+            writeln!(self.writer, "    <id>{id}</id>")?;
+        }
+
+        for revision in &page.revisions {
+            self.write_revision(revision)?;
+        }
+
+        writeln!(self.writer, "  </page>")
+    }
+
+    fn write_revision(&mut self, revision: &Revision) -> io::Result<()> {
+        let w = &mut self.writer;
+
+        writeln!(w, "    <revision>")?;
+        writeln!(w, "      <id>{}</id>", revision.id)?;
+        writeln!(
+            w,
+            "      <timestamp>{}</timestamp>",
+            revision.timestamp.format(TIMESTAMP_FORMAT)
+        )?;
+
+        let contributor = &revision.contributor;
+        if contributor.deleted {
+            writeln!(w, r#"      <contributor deleted="deleted" />"#)?;
+        } else {
+            writeln!(w, "      <contributor>")?;
+            Self::write_contributor_name(w, contributor)?;
+            writeln!(w, "      </contributor>")?;
+        }
+
+        if revision.minor {
+            writeln!(w, "      <minor />")?;
+        }
+        match &revision.comment {
+            Some(Comment::Normal(comment)) => {
+                writeln!(w, "      <comment>{}</comment>", escape(comment))?
+            }
+            Some(Comment::Deleted) => writeln!(w, r#"      <comment deleted="deleted" />"#)?,
+            None => {}
+        }
+        Self::write_model_and_format(w, "      ", &revision.model, &revision.format)?;
+
+        let sha1 = revision.sha1.as_ref();
+        Self::write_text(w, "      ", &revision.text, sha1)?;
+        if let Some(sha1) = sha1.and_then(|sha1| std::str::from_utf8(&sha1.0).ok()) {
+            writeln!(w, "      <sha1>{}</sha1>", escape(sha1))?;
+        }
+
+        for slot in &revision.slots {
+            writeln!(w, "      <content>")?;
+            writeln!(w, "        <role>{}</role>", escape(&slot.role))?;
+            Self::write_model_and_format(w, "        ", &slot.model, &slot.format)?;
+            Self::write_text(w, "        ", &slot.text, slot.sha1.as_ref())?;
+            writeln!(w, "      </content>")?;
+        }
+
+        writeln!(w, "    </revision>")
+    }
+
+    fn write_model_and_format(
+        w: &mut W,
+        indent: &str,
+        model: &Option<CompactString>,
+        format: &Option<CompactString>,
+    ) -> io::Result<()> {
+        if let Some(model) = model {
+            writeln!(w, "{indent}<model>{}</model>", escape(model))?;
+        }
+        if let Some(format) = format {
+            writeln!(w, "{indent}<format>{}</format>", escape(format))?;
+        }
+        Ok(())
+    }
+
+    fn write_text(w: &mut W, indent: &str, text: &Text, sha1: Option<&Sha1Hash>) -> io::Result<()> {
+        let sha1_attribute = sha1
+            .and_then(|sha1| std::str::from_utf8(&sha1.0).ok())
+            .map(|sha1| format!(r#" sha1="{}""#, escape(sha1)))
+            .unwrap_or_default();
+        match text {
+            Text::Normal(_) | Text::Shared(_) if text.is_empty() => {
+                writeln!(w, r#"{indent}<text bytes="0"{sha1_attribute} />"#)
+            }
+            Text::Normal(_) | Text::Shared(_) => writeln!(
+                w,
+                r#"{indent}<text bytes="{}"{sha1_attribute} xml:space="preserve">{}</text>"#,
+                text.len(),
+                escape(text.as_str())
+            ),
+            Text::Deleted => writeln!(w, r#"{indent}<text deleted="deleted" />"#),
+            Text::Stub { bytes, location } => {
+                write!(w, "{indent}<text")?;
+                if let Some(bytes) = bytes {
+                    write!(w, r#" bytes="{bytes}""#)?;
+                }
+                write!(w, "{sha1_attribute}")?;
+                if let Some(location) = location {
+                    write!(w, r#" location="{}""#, escape(location))?;
+                }
+                writeln!(w, " />")
+            }
+        }
+    }
+
+    fn write_contributor_name(w: &mut W, contributor: &Contributor) -> io::Result<()> {
+        if let Some(id) = contributor.id {
+            writeln!(
+                w,
+                "        <username>{}</username>",
+                escape(&contributor.username)
+            )?;
+            writeln!(w, "        <id>{id}</id>")
+        } else if contributor.username.parse::<IpAddr>().is_ok() {
+            writeln!(w, "        <ip>{}</ip>", contributor.username)
+        } else {
+            writeln!(
+                w,
+                "        <username>{}</username>",
+                escape(&contributor.username)
+            )
+        }
+    }
+
+    /// Writes the closing `</mediawiki>` tag, flushes and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        writeln!(self.writer, "</mediawiki>")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
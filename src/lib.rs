@@ -32,4 +32,5 @@ pub mod dump_parser;
 #[cfg(feature = "optimized-str")]
 #[doc(hidden)] /* only public for benchmarking */
 pub mod optimized_str;
+pub mod output;
 pub mod utils;
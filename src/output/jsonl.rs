@@ -0,0 +1,500 @@
+// SPDX-License-Identifier: MPL-2.0
+//! The page records of `wikiwho-cli -f jsonl` (one per line) and `-f json` (in an array).
+//!
+//! Each record type comes in two variants:
+//!
+//! - [`PageOutput`], [`RevisionOutput`] and [`TokenOutput`] borrow from a parsed [`Page`]
+//!   and its [`PageAnalysis`] and are what the CLI serializes,
+//! - [`OwnedPageOutput`], [`OwnedRevisionOutput`] and [`OwnedTokenOutput`] own their data
+//!   and are deserialized from the written records, e.g. with [`read_jsonl`].
+//!
+//! [`BorrowedPageOutput`], [`BorrowedRevisionOutput`] and [`BorrowedTokenOutput`] are
+//! deserialized as well, but borrow their strings from the input where possible, e.g. from a
+//! memory-mapped file read with [`read_jsonl_str`].
+//!
+//! A record holds the analysed (non-spam) revisions of the page with their editors, the ids
+//! of the revisions detected as spam and the tokens in `all_tokens`, with the revision that
+//! added each token (`o_rev_id` and `editor`) and the revisions that reintroduced (`in`) and
+//! removed (`out`) it. Editors are written as the user id, or `0|<username>` for anonymous
+//! editors. More revision metadata can be added with [`RevisionFields`].
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use wikiwho::output::jsonl::read_jsonl;
+//!
+//! let reader = std::io::BufReader::new(std::fs::File::open("out.jsonl").unwrap());
+//! for page in read_jsonl(reader) {
+//!     let page = page.unwrap();
+//!     let authors = page.all_tokens.iter().filter(|token| token.editor == "11").count();
+//!     println!("{}: {authors} tokens by user 11", page.article_title);
+//! }
+//! ```
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    io::Read,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+pub use super::RevisionSelection;
+use super::{revisions_by_id, Editor, SCHEMA_VERSION};
+use crate::{
+    algorithm::{PageAnalysis, RevisionPointer, WordPointer},
+    dump_parser::{Page, Revision},
+    utils::iterate_revision_tokens,
+};
+
+/// The record of a page.
+///
+/// The fields borrow from the [`Page`] and the [`PageAnalysis`]. The `Yokeable` derive lets
+/// the record be yoked to a cart holding both, e.g. to send it to a writer thread.
+#[derive(Debug, Serialize, yoke::Yokeable)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(description = "The record of a page."))]
+pub struct PageOutput<'a> {
+    /// [`SCHEMA_VERSION`]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(description = "Version of the record format.")
+    )]
+    pub schema_version: u32,
+    pub article_title: &'a str,
+    pub namespace: i32,
+    pub revisions: Vec<RevisionOutput<'a>>,
+    pub spam_ids: &'a [i32],
+    pub all_tokens: Vec<TokenOutput<'a>>,
+    /// Token ids of each selected revision, unless only the latest revision was selected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision_tokens: Option<Vec<RevisionTokensOutput>>,
+}
+
+/// Optional fields of the revisions in a [`PageOutput`], none by default.
+///
+/// Fields that are not enabled are left out of the record. The counts are `null` for
+/// revisions detected as spam, which are only listed with [`spam`](RevisionFields::spam).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RevisionFields {
+    /// `comment`: the edit summary, `null` if there is none or it was hidden.
+    pub comment: bool,
+    /// `minor`: whether the revision is marked as a minor edit.
+    pub minor: bool,
+    /// `size`: length of the revision text in bytes, `null` if the text was deleted.
+    pub size: bool,
+    /// `sha1`: the SHA1 of the revision text from the dump, `null` if the dump has none.
+    pub sha1: bool,
+    /// `token_count`: number of tokens of the revision.
+    pub token_count: bool,
+    /// `added`, `removed` and `reintroduced`: number of tokens the revision added for the
+    /// first time, removed and reintroduced.
+    pub changes: bool,
+    /// `spam`: whether the revision was detected as spam. Revisions detected as spam are
+    /// listed as well.
+    pub spam: bool,
+}
+
+impl RevisionFields {
+    /// All optional fields.
+    pub const ALL: RevisionFields = RevisionFields {
+        comment: true,
+        minor: true,
+        size: true,
+        sha1: true,
+        token_count: true,
+        changes: true,
+        spam: true,
+    };
+}
+
+#[derive(Debug, Serialize, yoke::Yokeable)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RevisionOutput<'a> {
+    pub id: i32,
+    /// RFC 3339
+    pub timestamp: String,
+    pub editor: Editor<'a>,
+    /// See [`RevisionFields`] for the optional fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<Option<&'a str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minor: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<Option<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<Option<&'a str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_count: Option<Option<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub added: Option<Option<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removed: Option<Option<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reintroduced: Option<Option<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spam: Option<bool>,
+}
+
+/// The tokens of a revision, referring to the page's `all_tokens` table by `token_id`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RevisionTokensOutput {
+    pub id: i32,
+    pub tokens: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, yoke::Yokeable)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TokenOutput<'a> {
+    pub token_id: usize,
+    #[serde(rename = "str")]
+    pub value: &'a str,
+    pub o_rev_id: i32,
+    pub editor: Editor<'a>,
+    #[serde(rename = "in")]
+    pub inbound: Vec<i32>,
+    #[serde(rename = "out")]
+    pub outbound: Vec<i32>,
+}
+
+/// A page record read back, see [`PageOutput`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnedPageOutput {
+    /// [`SCHEMA_VERSION`], 0 for records written before it existed.
+    #[serde(default)]
+    pub schema_version: u32,
+    pub article_title: String,
+    pub namespace: i32,
+    pub revisions: Vec<OwnedRevisionOutput>,
+    pub spam_ids: Vec<i32>,
+    pub all_tokens: Vec<OwnedTokenOutput>,
+    /// Token ids of each selected revision, unless only the latest revision was selected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision_tokens: Option<Vec<RevisionTokensOutput>>,
+}
+
+/// A revision read back, see [`RevisionOutput`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnedRevisionOutput {
+    pub id: i32,
+    pub timestamp: DateTime<Utc>,
+    /// The user id, or `0|<username>` for anonymous editors.
+    pub editor: String,
+    /// See [`RevisionFields`], `None` if the field is `null` or was not written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minor: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_count: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removed: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reintroduced: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spam: Option<bool>,
+}
+
+/// A token read back, see [`TokenOutput`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnedTokenOutput {
+    pub token_id: usize,
+    #[serde(rename = "str")]
+    pub value: String,
+    pub o_rev_id: i32,
+    /// The user id, or `0|<username>` for anonymous editors.
+    pub editor: String,
+    #[serde(rename = "in")]
+    pub inbound: Vec<i32>,
+    #[serde(rename = "out")]
+    pub outbound: Vec<i32>,
+}
+
+/// A page record read back with borrowed strings, see [`PageOutput`] and
+/// [`read_jsonl_str`].
+///
+/// Strings without escape sequences borrow from the input, others are copied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BorrowedPageOutput<'a> {
+    /// [`SCHEMA_VERSION`], 0 for records written before it existed.
+    #[serde(default)]
+    pub schema_version: u32,
+    #[serde(borrow)]
+    pub article_title: Cow<'a, str>,
+    pub namespace: i32,
+    #[serde(borrow)]
+    pub revisions: Vec<BorrowedRevisionOutput<'a>>,
+    pub spam_ids: Vec<i32>,
+    #[serde(borrow)]
+    pub all_tokens: Vec<BorrowedTokenOutput<'a>>,
+    /// Token ids of each selected revision, unless only the latest revision was selected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision_tokens: Option<Vec<RevisionTokensOutput>>,
+}
+
+/// A revision read back with borrowed strings, see [`RevisionOutput`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BorrowedRevisionOutput<'a> {
+    pub id: i32,
+    pub timestamp: DateTime<Utc>,
+    /// The user id, or `0|<username>` for anonymous editors.
+    #[serde(borrow)]
+    pub editor: Cow<'a, str>,
+    /// See [`RevisionFields`], `None` if the field is `null` or was not written.
+    #[serde(borrow, default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<Cow<'a, str>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minor: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(borrow, default, skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<Cow<'a, str>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_count: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removed: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reintroduced: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spam: Option<bool>,
+}
+
+/// A token read back with borrowed strings, see [`TokenOutput`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BorrowedTokenOutput<'a> {
+    pub token_id: usize,
+    #[serde(borrow, rename = "str")]
+    pub value: Cow<'a, str>,
+    pub o_rev_id: i32,
+    /// The user id, or `0|<username>` for anonymous editors.
+    #[serde(borrow)]
+    pub editor: Cow<'a, str>,
+    #[serde(rename = "in")]
+    pub inbound: Vec<i32>,
+    #[serde(rename = "out")]
+    pub outbound: Vec<i32>,
+}
+
+impl<'a> PageOutput<'a> {
+    /// Builds the record of a page, `analysis` must be the analysis of `page`.
+    ///
+    /// With [`RevisionSelection::Latest`], `all_tokens` holds the tokens of the current
+    /// revision. Otherwise it is the table of all tokens of the selected revisions, in order of
+    /// their first occurrence, and `revision_tokens` lists the ids of the tokens of each
+    /// selected revision. Selected revisions that were detected as spam are left out.
+    pub fn new(page: &'a Page, analysis: &'a PageAnalysis, revisions: &RevisionSelection) -> Self {
+        Self::with_revision_fields(page, analysis, revisions, RevisionFields::default())
+    }
+
+    /// Builds the record of a page like [`PageOutput::new`], with the optional `fields` of
+    /// the revisions.
+    pub fn with_revision_fields(
+        page: &'a Page,
+        analysis: &'a PageAnalysis,
+        revisions: &RevisionSelection,
+        fields: RevisionFields,
+    ) -> Self {
+        let revisions_by_id = revisions_by_id(page);
+
+        let analysed: HashMap<i32, &RevisionPointer> = analysis
+            .ordered_revisions
+            .iter()
+            .map(|rev_ptr| (rev_ptr.id, rev_ptr))
+            .collect();
+        // revision id -> (removed, reintroduced)
+        let mut changes: HashMap<i32, (usize, usize)> = HashMap::new();
+        if fields.changes {
+            for word in &analysis.words {
+                let word_analysis = &analysis[word];
+                for rev_ptr in &word_analysis.outbound {
+                    changes.entry(rev_ptr.id).or_default().0 += 1;
+                }
+                for rev_ptr in &word_analysis.inbound {
+                    changes.entry(rev_ptr.id).or_default().1 += 1;
+                }
+            }
+        }
+
+        let listed: Vec<&Revision> = if fields.spam {
+            page.revisions
+                .iter()
+                .filter(|rev| analysed.contains_key(&rev.id) || analysis.spam_ids.contains(&rev.id))
+                .collect()
+        } else {
+            analysis
+                .ordered_revisions
+                .iter()
+                .map(|rev_ptr| revisions_by_id[&rev_ptr.id])
+                .collect()
+        };
+
+        let revision_outputs = listed
+            .into_iter()
+            .map(|xml_revision| {
+                let rev_ptr = analysed.get(&xml_revision.id);
+                let (removed, reintroduced) =
+                    changes.get(&xml_revision.id).copied().unwrap_or_default();
+                RevisionOutput {
+                    id: xml_revision.id,
+                    timestamp: xml_revision.timestamp.to_rfc3339(),
+                    editor: Editor(&xml_revision.contributor),
+                    comment: fields.comment.then(|| {
+                        xml_revision
+                            .comment
+                            .as_ref()
+                            .and_then(|comment| comment.as_visible())
+                    }),
+                    minor: fields.minor.then_some(xml_revision.minor),
+                    size: fields.size.then(|| xml_revision.text.byte_len()),
+                    sha1: fields.sha1.then(|| {
+                        xml_revision
+                            .sha1
+                            .as_ref()
+                            .and_then(|sha1| std::str::from_utf8(&sha1.0).ok())
+                    }),
+                    token_count: fields.token_count.then(|| {
+                        rev_ptr.map(|rev_ptr| iterate_revision_tokens(analysis, rev_ptr).count())
+                    }),
+                    added: fields
+                        .changes
+                        .then(|| rev_ptr.map(|rev_ptr| analysis[*rev_ptr].original_adds)),
+                    removed: fields.changes.then(|| rev_ptr.map(|_| removed)),
+                    reintroduced: fields.changes.then(|| rev_ptr.map(|_| reintroduced)),
+                    spam: fields.spam.then(|| rev_ptr.is_none()),
+                }
+            })
+            .collect();
+
+        let token_output = |word_ptr: &'a WordPointer| {
+            let word_analysis = &analysis[word_ptr];
+            let xml_origin_revision = revisions_by_id[&word_analysis.origin_revision.id];
+            TokenOutput {
+                token_id: word_ptr.unique_id(),
+                value: &word_ptr.value,
+                o_rev_id: xml_origin_revision.id,
+                editor: Editor(&xml_origin_revision.contributor),
+                inbound: word_analysis.inbound.iter().map(|r| r.id).collect(),
+                outbound: word_analysis.outbound.iter().map(|r| r.id).collect(),
+            }
+        };
+
+        let (all_tokens, revision_tokens) = match revisions {
+            RevisionSelection::Latest => {
+                let all_tokens = iterate_revision_tokens(analysis, &analysis.current_revision)
+                    .map(token_output)
+                    .collect();
+                (all_tokens, None)
+            }
+            RevisionSelection::All | RevisionSelection::Ids(_) => {
+                let mut all_tokens = Vec::new();
+                let mut seen_tokens = HashSet::new();
+                let revision_tokens = analysis
+                    .ordered_revisions
+                    .iter()
+                    .filter(|rev_ptr| revisions.contains(rev_ptr.id))
+                    .map(|rev_ptr| {
+                        let tokens = iterate_revision_tokens(analysis, rev_ptr)
+                            .map(|word_ptr| {
+                                if seen_tokens.insert(word_ptr.unique_id()) {
+                                    all_tokens.push(token_output(word_ptr));
+                                }
+                                word_ptr.unique_id()
+                            })
+                            .collect();
+                        RevisionTokensOutput {
+                            id: rev_ptr.id,
+                            tokens,
+                        }
+                    })
+                    .collect();
+                (all_tokens, Some(revision_tokens))
+            }
+        };
+
+        PageOutput {
+            schema_version: SCHEMA_VERSION,
+            article_title: &page.title,
+            namespace: page.namespace,
+            revisions: revision_outputs,
+            spam_ids: &analysis.spam_ids,
+            all_tokens,
+            revision_tokens,
+        }
+    }
+}
+
+/// Reads the page records of `wikiwho-cli -f jsonl` output, one at a time.
+///
+/// `reader` should be buffered. Blank lines are skipped. After an error the iterator ends,
+/// since the position of the next record is unknown.
+pub fn read_jsonl<R: Read>(reader: R) -> JsonlReader<R> {
+    JsonlReader::new(reader)
+}
+
+/// Iterator over the records of JSONL output, see [`read_jsonl`].
+pub struct JsonlReader<R: Read, T = OwnedPageOutput> {
+    records: serde_json::StreamDeserializer<'static, serde_json::de::IoRead<R>, T>,
+    failed: bool,
+}
+
+impl<R: Read, T: DeserializeOwned> JsonlReader<R, T> {
+    pub(super) fn new(reader: R) -> Self {
+        Self {
+            records: serde_json::Deserializer::from_reader(reader).into_iter(),
+            failed: false,
+        }
+    }
+}
+
+impl<R: Read, T: DeserializeOwned> Iterator for JsonlReader<R, T> {
+    type Item = Result<T, serde_json::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let record = self.records.next()?;
+        self.failed = record.is_err();
+        Some(record)
+    }
+}
+
+/// Reads the page records of `wikiwho-cli -f jsonl` output from a string, borrowing from it
+/// like [`BorrowedPageOutput`] does.
+///
+/// Blank lines are skipped. After an error the iterator ends, like [`read_jsonl`].
+pub fn read_jsonl_str(input: &str) -> JsonlStrReader<'_> {
+    JsonlStrReader {
+        records: serde_json::Deserializer::from_str(input).into_iter(),
+        failed: false,
+    }
+}
+
+/// Iterator over the records of JSONL output in a string, see [`read_jsonl_str`].
+pub struct JsonlStrReader<'a> {
+    records:
+        serde_json::StreamDeserializer<'a, serde_json::de::StrRead<'a>, BorrowedPageOutput<'a>>,
+    failed: bool,
+}
+
+impl<'a> Iterator for JsonlStrReader<'a> {
+    type Item = Result<BorrowedPageOutput<'a>, serde_json::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let record = self.records.next()?;
+        self.failed = record.is_err();
+        Some(record)
+    }
+}
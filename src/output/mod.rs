@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: MPL-2.0
+//! Serializable views of analysis results, as written by `wikiwho-cli`.
+//!
+//! - `jsonl` (with the `serde` feature): the page records of `wikiwho-cli -f jsonl`, and a
+//!   reader for them.
+//! - `raw` (with the `serde` feature): the records of `wikiwho-cli -f raw`, a serialized
+//!   analysis with its page metadata, and a reader for them.
+//! - [`tsv`]: one line per token as tab-separated values.
+//! - `wikiwho_api` (with the `serde` feature): the response schemas of the WikiWho web API.
+//! - `schema` (with the `schema` feature): JSON Schemas of the JSON output formats.
+//! - `parquet` (with the `arrow` feature): columnar tables as Apache Parquet files.
+//! - `sqlite` (with the `sqlite` feature): normalised tables in an SQLite database.
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::{self, Display},
+};
+
+use crate::dump_parser::{Contributor, Page, Revision};
+
+#[cfg(feature = "serde")]
+pub mod jsonl;
+#[cfg(feature = "arrow")]
+pub mod parquet;
+#[cfg(feature = "serde")]
+pub mod raw;
+#[cfg(feature = "schema")]
+pub mod schema;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod tsv;
+#[cfg(feature = "serde")]
+pub mod wikiwho_api;
+
+/// Version of the `jsonl`, `json` and `raw` records, written as their `schema_version`.
+///
+/// It is increased when a field is removed or renamed or its meaning changes. New fields may
+/// be added without a new version, so readers should ignore fields they don't know.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Format of the timestamps in the text outputs, e.g. `2024-01-31T12:00:00Z`.
+pub(crate) const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// The revisions of `page` by id, to look up the metadata the analysis doesn't keep.
+pub(crate) fn revisions_by_id(page: &Page) -> HashMap<i32, &Revision> {
+    page.revisions.iter().map(|rev| (rev.id, rev)).collect()
+}
+
+/// Which revisions of a page to write the tokens of.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RevisionSelection {
+    /// Only the current revision of the analysis.
+    #[default]
+    Latest,
+    All,
+    /// Revisions with these ids, revisions of other pages are ignored.
+    Ids(BTreeSet<i32>),
+}
+
+impl RevisionSelection {
+    /// Whether the revision `id` is selected. Always `false` for [`RevisionSelection::Latest`],
+    /// which selects the current revision of the analysis instead.
+    pub fn contains(&self, id: i32) -> bool {
+        match self {
+            RevisionSelection::Latest => false,
+            RevisionSelection::All => true,
+            RevisionSelection::Ids(ids) => ids.contains(&id),
+        }
+    }
+}
+
+/// An editor as written in the output: the user id, or `0|<username>` for anonymous editors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Editor<'a>(pub &'a Contributor);
+
+impl Display for Editor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.id {
+            Some(id) if id != 0 => write!(f, "{id}"),
+            _ => write!(f, "0|{}", self.0.username),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Editor<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Editor<'_> {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Editor".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "description": "The user id, or `0|<username>` for anonymous editors.",
+        })
+    }
+}
@@ -0,0 +1,298 @@
+// SPDX-License-Identifier: MPL-2.0
+//! Analysis results as [Apache Parquet](https://parquet.apache.org/) tables, for loading
+//! into DuckDB, Spark, Polars and other columnar tools.
+//!
+//! [`ParquetWriter`] streams pages into two tables, each written to its own file:
+//!
+//! - `tokens`: one row per token of the latest revision of each page, in reading order:
+//!   `page_id`, `page_title`, `namespace`, `token_id`, `str`, `o_rev_id`, `editor` and the
+//!   revision ids `in` and `out` as list columns,
+//! - `revisions`: one row per analysed (non-spam) revision: `page_id`, `page_title`,
+//!   `namespace`, `rev_id`, `timestamp` and `editor`.
+//!
+//! Editors are written as the user id, or `0|<username>` for anonymous editors. Rows are
+//! collected into record batches and written in row groups of
+//! [`DEFAULT_ROW_GROUP_SIZE`] rows, so memory use stays bounded however large the dump is.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use wikiwho::algorithm::PageAnalysis;
+//! use wikiwho::dump_parser::DumpParser;
+//! use wikiwho::output::parquet::ParquetWriter;
+//!
+//! let reader = std::io::BufReader::new(std::fs::File::open("dump.xml").unwrap());
+//! let mut parser = DumpParser::new(reader).unwrap();
+//! let mut writer = ParquetWriter::create("out").unwrap();
+//! while let Some(page) = parser.parse_page().unwrap() {
+//!     let analysis = PageAnalysis::analyse_page(&page.revisions).unwrap();
+//!     writer.write_page(&page, &analysis).unwrap();
+//! }
+//! writer.finish().unwrap();
+//! ```
+use std::{fs::File, io::Write, path::Path, sync::Arc};
+
+use arrow_array::{
+    builder::{
+        Int32Builder, ListBuilder, StringBuilder, TimestampMillisecondBuilder, UInt64Builder,
+    },
+    ArrayRef, RecordBatch,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+pub use parquet::errors::ParquetError;
+use parquet::{
+    arrow::ArrowWriter,
+    basic::{Compression, ZstdLevel},
+    file::properties::WriterProperties,
+};
+
+use super::{revisions_by_id, Editor};
+use crate::{
+    algorithm::PageAnalysis,
+    dump_parser::{Page, Revision},
+    utils::iterate_revision_tokens,
+};
+
+/// Number of rows per row group, unless set with [`ParquetWriter::with_row_group_size`].
+pub const DEFAULT_ROW_GROUP_SIZE: usize = 1024 * 1024;
+
+/// Number of rows collected before they are handed to the Parquet encoder.
+const BATCH_SIZE: usize = 64 * 1024;
+
+/// File name of the `tokens` table in the directory of [`ParquetWriter::create`].
+pub const TOKENS_FILE: &str = "tokens.parquet";
+/// File name of the `revisions` table in the directory of [`ParquetWriter::create`].
+pub const REVISIONS_FILE: &str = "revisions.parquet";
+
+/// Writes pages into the `tokens` and `revisions` tables, see the [module documentation](self).
+///
+/// The files are only complete after [`finish`](ParquetWriter::finish).
+pub struct ParquetWriter<W: Write + Send> {
+    tokens: ArrowWriter<W>,
+    revisions: ArrowWriter<W>,
+    token_rows: TokenRows,
+    revision_rows: RevisionRows,
+}
+
+impl ParquetWriter<File> {
+    /// Creates the directory `dir` if needed and the table files [`TOKENS_FILE`] and
+    /// [`REVISIONS_FILE`] in it.
+    pub fn create(dir: impl AsRef<Path>) -> Result<Self, ParquetError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        Self::new(
+            File::create(dir.join(TOKENS_FILE))?,
+            File::create(dir.join(REVISIONS_FILE))?,
+        )
+    }
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    /// Creates a writer for the `tokens` and `revisions` tables.
+    pub fn new(tokens: W, revisions: W) -> Result<Self, ParquetError> {
+        Self::with_row_group_size(tokens, revisions, DEFAULT_ROW_GROUP_SIZE)
+    }
+
+    /// Like [`new`](ParquetWriter::new) with at most `row_group_size` rows per row group.
+    pub fn with_row_group_size(
+        tokens: W,
+        revisions: W,
+        row_group_size: usize,
+    ) -> Result<Self, ParquetError> {
+        let properties = WriterProperties::builder()
+            .set_max_row_group_size(row_group_size)
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        Ok(Self {
+            tokens: ArrowWriter::try_new(tokens, tokens_schema(), Some(properties.clone()))?,
+            revisions: ArrowWriter::try_new(revisions, revisions_schema(), Some(properties))?,
+            token_rows: TokenRows::default(),
+            revision_rows: RevisionRows::default(),
+        })
+    }
+
+    /// Adds the revisions and the tokens of the latest revision of a page.
+    ///
+    /// `analysis` must be the analysis of `page`.
+    pub fn write_page(&mut self, page: &Page, analysis: &PageAnalysis) -> Result<(), ParquetError> {
+        let revisions_by_id = revisions_by_id(page);
+
+        for rev_ptr in &analysis.ordered_revisions {
+            self.revision_rows
+                .append(page, revisions_by_id[&rev_ptr.id]);
+        }
+        for word in iterate_revision_tokens(analysis, &analysis.current_revision) {
+            let word_analysis = &analysis[word];
+            self.token_rows.append(
+                page,
+                word.unique_id(),
+                &word.value,
+                revisions_by_id[&word_analysis.origin_revision.id],
+                word_analysis.inbound.iter().map(|r| r.id),
+                word_analysis.outbound.iter().map(|r| r.id),
+            );
+        }
+
+        if self.revision_rows.len >= BATCH_SIZE {
+            self.revisions.write(&self.revision_rows.finish()?)?;
+        }
+        if self.token_rows.len >= BATCH_SIZE {
+            self.tokens.write(&self.token_rows.finish()?)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the remaining rows and the file footers, and returns the writers of the
+    /// `tokens` and `revisions` tables.
+    pub fn finish(mut self) -> Result<(W, W), ParquetError> {
+        if self.revision_rows.len > 0 {
+            self.revisions.write(&self.revision_rows.finish()?)?;
+        }
+        if self.token_rows.len > 0 {
+            self.tokens.write(&self.token_rows.finish()?)?;
+        }
+        Ok((self.tokens.into_inner()?, self.revisions.into_inner()?))
+    }
+}
+
+fn id_list_type() -> DataType {
+    DataType::List(Arc::new(Field::new("item", DataType::Int32, true)))
+}
+
+fn tokens_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("page_id", DataType::Int32, true),
+        Field::new("page_title", DataType::Utf8, false),
+        Field::new("namespace", DataType::Int32, false),
+        Field::new("token_id", DataType::UInt64, false),
+        Field::new("str", DataType::Utf8, false),
+        Field::new("o_rev_id", DataType::Int32, false),
+        Field::new("editor", DataType::Utf8, false),
+        Field::new("in", id_list_type(), false),
+        Field::new("out", id_list_type(), false),
+    ]))
+}
+
+fn revisions_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("page_id", DataType::Int32, true),
+        Field::new("page_title", DataType::Utf8, false),
+        Field::new("namespace", DataType::Int32, false),
+        Field::new("rev_id", DataType::Int32, false),
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            false,
+        ),
+        Field::new("editor", DataType::Utf8, false),
+    ]))
+}
+
+/// The columns of the `tokens` rows that were not written yet.
+#[derive(Default)]
+struct TokenRows {
+    len: usize,
+    page_id: Int32Builder,
+    page_title: StringBuilder,
+    namespace: Int32Builder,
+    token_id: UInt64Builder,
+    value: StringBuilder,
+    o_rev_id: Int32Builder,
+    editor: StringBuilder,
+    inbound: ListBuilder<Int32Builder>,
+    outbound: ListBuilder<Int32Builder>,
+}
+
+impl TokenRows {
+    fn append(
+        &mut self,
+        page: &Page,
+        token_id: usize,
+        value: &str,
+        origin_revision: &Revision,
+        inbound: impl IntoIterator<Item = i32>,
+        outbound: impl IntoIterator<Item = i32>,
+    ) {
+        self.len += 1;
+        self.page_id.append_option(page.id);
+        self.page_title.append_value(&page.title);
+        self.namespace.append_value(page.namespace);
+        self.token_id.append_value(token_id as u64);
+        self.value.append_value(value);
+        self.o_rev_id.append_value(origin_revision.id);
+        self.editor
+            .append_value(Editor(&origin_revision.contributor).to_string());
+        self.inbound.append_value(inbound.into_iter().map(Some));
+        self.outbound.append_value(outbound.into_iter().map(Some));
+    }
+
+    /// Takes the collected rows as a record batch.
+    fn finish(&mut self) -> Result<RecordBatch, ParquetError> {
+        self.len = 0;
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.page_id.finish()),
+            Arc::new(self.page_title.finish()),
+            Arc::new(self.namespace.finish()),
+            Arc::new(self.token_id.finish()),
+            Arc::new(self.value.finish()),
+            Arc::new(self.o_rev_id.finish()),
+            Arc::new(self.editor.finish()),
+            Arc::new(self.inbound.finish()),
+            Arc::new(self.outbound.finish()),
+        ];
+        Ok(RecordBatch::try_new(tokens_schema(), columns)?)
+    }
+}
+
+/// The columns of the `revisions` rows that were not written yet.
+struct RevisionRows {
+    len: usize,
+    page_id: Int32Builder,
+    page_title: StringBuilder,
+    namespace: Int32Builder,
+    rev_id: Int32Builder,
+    timestamp: TimestampMillisecondBuilder,
+    editor: StringBuilder,
+}
+
+impl Default for RevisionRows {
+    fn default() -> Self {
+        Self {
+            len: 0,
+            page_id: Int32Builder::new(),
+            page_title: StringBuilder::new(),
+            namespace: Int32Builder::new(),
+            rev_id: Int32Builder::new(),
+            timestamp: TimestampMillisecondBuilder::new().with_timezone("UTC"),
+            editor: StringBuilder::new(),
+        }
+    }
+}
+
+impl RevisionRows {
+    fn append(&mut self, page: &Page, revision: &Revision) {
+        self.len += 1;
+        self.page_id.append_option(page.id);
+        self.page_title.append_value(&page.title);
+        self.namespace.append_value(page.namespace);
+        self.rev_id.append_value(revision.id);
+        self.timestamp
+            .append_value(revision.timestamp.timestamp_millis());
+        self.editor
+            .append_value(Editor(&revision.contributor).to_string());
+    }
+
+    /// Takes the collected rows as a record batch.
+    fn finish(&mut self) -> Result<RecordBatch, ParquetError> {
+        self.len = 0;
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.page_id.finish()),
+            Arc::new(self.page_title.finish()),
+            Arc::new(self.namespace.finish()),
+            Arc::new(self.rev_id.finish()),
+            Arc::new(self.timestamp.finish()),
+            Arc::new(self.editor.finish()),
+        ];
+        Ok(RecordBatch::try_new(revisions_schema(), columns)?)
+    }
+}
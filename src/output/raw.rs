@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: MPL-2.0
+//! The records of `wikiwho-cli -f raw`: a serialized [`PageAnalysis`] together with what is
+//! needed to tie it back to its page.
+//!
+//! Each line of raw output is a [`RawRecord`]: the version of the crate that wrote it, the
+//! `dbname` of the dump, the [analysis options](AnalysisOptionsRecord), the
+//! [page metadata](PageMetadata) (title, namespace, id and the revisions without their text)
+//! and the analysis in the format of its `serde` implementation. [`read_raw`] reads them back
+//! as [`OwnedRawRecord`]s, so results can be post-processed without running the analysis
+//! again.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use wikiwho::output::raw::read_raw;
+//! use wikiwho::utils::iterate_revision_tokens;
+//!
+//! let reader = std::io::BufReader::new(std::fs::File::open("out.raw.jsonl").unwrap());
+//! for record in read_raw(reader) {
+//!     let record = record.unwrap();
+//!     let analysis = &record.analysis;
+//!     let tokens = iterate_revision_tokens(analysis, &analysis.current_revision).count();
+//!     println!("{} ({}): {tokens} tokens", record.page.title, record.dbname);
+//! }
+//! ```
+use std::io::Read;
+
+use chrono::{DateTime, Utc};
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+
+use super::{jsonl::JsonlReader, SCHEMA_VERSION};
+use crate::{
+    algorithm::{PageAnalysis, PageAnalysisOptions},
+    dump_parser::{Comment, Contributor, Page, Revision, Sha1Hash},
+};
+
+/// A raw record, borrowing the analysis.
+#[derive(Serialize, yoke::Yokeable)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "schema",
+    schemars(description = "The analysis of a page with its metadata.")
+)]
+pub struct RawRecord<'a> {
+    /// [`SCHEMA_VERSION`]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(description = "Version of the record format.")
+    )]
+    pub schema_version: u32,
+    /// Version of the crate that wrote the record.
+    pub crate_version: &'static str,
+    /// Database name of the dump, e.g. `enwiki`.
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub dbname: CompactString,
+    pub options: AnalysisOptionsRecord,
+    pub page: PageMetadata,
+    pub analysis: &'a PageAnalysis,
+}
+
+/// A raw record read back, see [`RawRecord`].
+#[derive(Deserialize)]
+pub struct OwnedRawRecord {
+    /// [`SCHEMA_VERSION`], 0 for records written before it existed.
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Version of the crate that wrote the record.
+    pub crate_version: String,
+    /// Database name of the dump, e.g. `enwiki`.
+    pub dbname: CompactString,
+    pub options: AnalysisOptionsRecord,
+    pub page: PageMetadata,
+    pub analysis: PageAnalysis,
+}
+
+/// The [`PageAnalysisOptions`] a record was analysed with, including the options of features
+/// that may not be enabled when reading it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AnalysisOptionsRecord {
+    /// Role of the analysed content slot.
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub slot: CompactString,
+    pub optimize_non_ascii: bool,
+    pub use_python_diff: bool,
+}
+
+/// A [`Page`] without the revision texts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PageMetadata {
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub title: CompactString,
+    pub namespace: i32,
+    pub id: Option<i32>,
+    /// All revisions of the page, including those detected as spam, in the input order.
+    pub revisions: Vec<RevisionMetadata>,
+}
+
+/// A [`Revision`] without its text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RevisionMetadata {
+    pub id: i32,
+    pub timestamp: DateTime<Utc>,
+    pub contributor: Contributor,
+    pub sha1: Option<Sha1Hash>,
+    pub comment: Option<Comment>,
+    pub minor: bool,
+}
+
+impl<'a> RawRecord<'a> {
+    /// Creates the record of `page` and its analysis.
+    pub fn new(
+        dbname: &str,
+        options: &PageAnalysisOptions,
+        page: &Page,
+        analysis: &'a PageAnalysis,
+    ) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION"),
+            dbname: dbname.into(),
+            options: options.into(),
+            page: page.into(),
+            analysis,
+        }
+    }
+}
+
+impl OwnedRawRecord {
+    /// Splits the record into the page metadata and the analysis.
+    pub fn into_parts(self) -> (PageMetadata, PageAnalysis) {
+        (self.page, self.analysis)
+    }
+}
+
+impl From<&PageAnalysisOptions> for AnalysisOptionsRecord {
+    fn from(options: &PageAnalysisOptions) -> Self {
+        Self {
+            slot: options.slot.clone(),
+            #[cfg(feature = "optimized-lowercase")]
+            optimize_non_ascii: options.optimize_non_ascii,
+            #[cfg(not(feature = "optimized-lowercase"))]
+            optimize_non_ascii: false,
+            #[cfg(feature = "python-diff")]
+            use_python_diff: options.use_python_diff,
+            #[cfg(not(feature = "python-diff"))]
+            use_python_diff: false,
+        }
+    }
+}
+
+impl From<&Page> for PageMetadata {
+    fn from(page: &Page) -> Self {
+        Self {
+            title: page.title.clone(),
+            namespace: page.namespace,
+            id: page.id,
+            revisions: page.revisions.iter().map(RevisionMetadata::from).collect(),
+        }
+    }
+}
+
+impl From<&Revision> for RevisionMetadata {
+    fn from(revision: &Revision) -> Self {
+        Self {
+            id: revision.id,
+            timestamp: revision.timestamp,
+            contributor: revision.contributor.clone(),
+            sha1: revision.sha1,
+            comment: revision.comment.clone(),
+            minor: revision.minor,
+        }
+    }
+}
+
+impl PageMetadata {
+    /// Returns the revision with the given id.
+    pub fn revision(&self, id: i32) -> Option<&RevisionMetadata> {
+        self.revisions.iter().find(|revision| revision.id == id)
+    }
+}
+
+/// Reads the records of `wikiwho-cli -f raw` output, one at a time.
+///
+/// `reader` should be buffered. Blank lines are skipped. After an error the iterator ends,
+/// since the position of the next record is unknown.
+pub fn read_raw<R: Read>(reader: R) -> JsonlReader<R, OwnedRawRecord> {
+    JsonlReader::new(reader)
+}
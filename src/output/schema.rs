@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: MPL-2.0
+//! [JSON Schemas](https://json-schema.org/) of the JSON output formats of `wikiwho-cli`,
+//! generated from the output types themselves.
+//!
+//! Every [`OutputSchema`] describes one `--format`: the schema of one line for the formats
+//! with one record per line (`jsonl`, `raw` and the `wikiwho-api:*` formats), the schema of
+//! the whole array for `json`. The schemas of the current version are shipped in the
+//! `schemas` directory of the repository (as [`OutputSchema::file_name`]) and printed by
+//! `wikiwho-cli schema FORMAT`.
+//!
+//! The `jsonl`, `json` and `raw` records carry a `schema_version`, which the schemas pin to
+//! [`SCHEMA_VERSION`]: records of another version don't validate, while fields added within
+//! a version do, since the schemas allow additional properties. The `wikiwho-api:*` responses
+//! follow the schema of the WikiWho web API and have no version field.
+//!
+//! # Example
+//!
+//! ```rust
+//! use wikiwho::output::schema::OutputSchema;
+//!
+//! let schema = OutputSchema::from_format("jsonl").unwrap().schema();
+//! println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+//! ```
+use schemars::{generate::SchemaSettings, JsonSchema, Schema};
+use serde_json::Value;
+
+use super::{
+    jsonl::PageOutput,
+    raw::RawRecord,
+    wikiwho_api::{AllContent, EditPersistence, RevContent, RevIds},
+    SCHEMA_VERSION,
+};
+
+/// A JSON output format of `wikiwho-cli`, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputSchema {
+    Jsonl,
+    Json,
+    Raw,
+    AllContent,
+    RevContent,
+    RevIds,
+    EditPersistence,
+}
+
+impl OutputSchema {
+    /// All JSON output formats.
+    pub const ALL: [OutputSchema; 7] = [
+        OutputSchema::Jsonl,
+        OutputSchema::Json,
+        OutputSchema::Raw,
+        OutputSchema::AllContent,
+        OutputSchema::RevContent,
+        OutputSchema::RevIds,
+        OutputSchema::EditPersistence,
+    ];
+
+    /// The format as passed to `wikiwho-cli --format`, e.g. `wikiwho-api:rev_ids`.
+    pub fn format(self) -> &'static str {
+        match self {
+            OutputSchema::Jsonl => "jsonl",
+            OutputSchema::Json => "json",
+            OutputSchema::Raw => "raw",
+            OutputSchema::AllContent => "wikiwho-api:all_content",
+            OutputSchema::RevContent => "wikiwho-api:rev_content",
+            OutputSchema::RevIds => "wikiwho-api:rev_ids",
+            OutputSchema::EditPersistence => "wikiwho-api:edit_persistence",
+        }
+    }
+
+    /// Looks up the schema of a `wikiwho-cli --format`.
+    pub fn from_format(format: &str) -> Option<Self> {
+        OutputSchema::ALL
+            .into_iter()
+            .find(|schema| schema.format() == format)
+    }
+
+    /// Name of the schema file, e.g. `wikiwho-api.rev_ids.schema.json`.
+    pub fn file_name(self) -> String {
+        format!("{}.schema.json", self.format().replace(':', "."))
+    }
+
+    /// Generates the schema.
+    pub fn schema(self) -> Schema {
+        let mut schema = match self {
+            OutputSchema::Jsonl => root_schema::<PageOutput>(),
+            OutputSchema::Json => root_schema::<Vec<PageOutput>>(),
+            OutputSchema::Raw => root_schema::<RawRecord>(),
+            OutputSchema::AllContent => root_schema::<AllContent>(),
+            OutputSchema::RevContent => root_schema::<RevContent>(),
+            OutputSchema::RevIds => root_schema::<RevIds>(),
+            OutputSchema::EditPersistence => root_schema::<EditPersistence>(),
+        };
+        schema.insert(
+            "title".into(),
+            format!("wikiwho-cli --format {}", self.format()).into(),
+        );
+
+        pin_schema_version(schema.as_object_mut().expect("object schema"));
+        if let Some(Value::Object(definitions)) = schema.get_mut("$defs") {
+            for definition in definitions.values_mut() {
+                if let Value::Object(definition) = definition {
+                    pin_schema_version(definition);
+                }
+            }
+        }
+        schema
+    }
+}
+
+/// The schema of `T` as it is serialized.
+fn root_schema<T: JsonSchema>() -> Schema {
+    SchemaSettings::draft2020_12()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<T>()
+}
+
+/// Restricts the `schema_version` property of an object schema, if it has one, to
+/// [`SCHEMA_VERSION`].
+fn pin_schema_version(schema: &mut serde_json::Map<String, Value>) {
+    if let Some(Value::Object(version)) = schema
+        .get_mut("properties")
+        .and_then(|properties| properties.get_mut("schema_version"))
+    {
+        version.insert("const".into(), SCHEMA_VERSION.into());
+    }
+}
@@ -0,0 +1,204 @@
+// SPDX-License-Identifier: MPL-2.0
+//! Analysis results as an [SQLite](https://sqlite.org/) database, for ad-hoc blame lookups
+//! with SQL.
+//!
+//! [`SqliteWriter`] writes pages into four normalised tables:
+//!
+//! - `pages(id, page_id, title, namespace)`: one row per page. `id` is the row id the other
+//!   tables refer to, `page_id` the page id from the dump (`NULL` if it has none),
+//! - `revisions(page, rev_id, timestamp, editor)`: one row per analysed (non-spam) revision,
+//!   the timestamp as `YYYY-MM-DDTHH:MM:SSZ`,
+//! - `tokens(id, page, position, token_id, str, o_rev_id)`: one row per token of the latest
+//!   revision of each page, `position` counting from 0 in reading order,
+//! - `token_events(token, kind, rev_id)`: the revisions a token was reintroduced in
+//!   (`kind = 'in'`) or removed in (`kind = 'out'`).
+//!
+//! Editors are written as the user id, or `0|<username>` for anonymous editors. Rows are
+//! inserted in transactions of about [`BATCH_SIZE`] rows; the indexes on `pages(title)`,
+//! `revisions(rev_id)`, `revisions(page)`, `tokens(page, position)` and
+//! `token_events(token)` are created by [`SqliteWriter::finish`], after all rows are in.
+//!
+//! Who introduced the tokens of a phrase in an article:
+//!
+//! ```sql
+//! SELECT t.position, t.str, r.rev_id, r.timestamp, r.editor
+//! FROM pages p
+//! JOIN tokens t ON t.page = p.id
+//! JOIN revisions r ON r.page = p.id AND r.rev_id = t.o_rev_id
+//! WHERE p.title = 'Example' AND t.str IN ('quick', 'fox')
+//! ORDER BY t.position;
+//! ```
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use wikiwho::algorithm::PageAnalysis;
+//! use wikiwho::dump_parser::DumpParser;
+//! use wikiwho::output::sqlite::SqliteWriter;
+//!
+//! let reader = std::io::BufReader::new(std::fs::File::open("dump.xml").unwrap());
+//! let mut parser = DumpParser::new(reader).unwrap();
+//! let mut writer = SqliteWriter::create("out.db").unwrap();
+//! while let Some(page) = parser.parse_page().unwrap() {
+//!     let analysis = PageAnalysis::analyse_page(&page.revisions).unwrap();
+//!     writer.write_page(&page, &analysis).unwrap();
+//! }
+//! writer.finish().unwrap();
+//! ```
+use std::path::Path;
+
+use rusqlite::{ffi, params};
+pub use rusqlite::{Connection, Error as SqliteError};
+
+use super::{revisions_by_id, Editor, TIMESTAMP_FORMAT};
+use crate::{algorithm::PageAnalysis, dump_parser::Page, utils::iterate_revision_tokens};
+
+/// Number of rows inserted per transaction.
+pub const BATCH_SIZE: usize = 64 * 1024;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS pages (
+    id INTEGER PRIMARY KEY,
+    page_id INTEGER,
+    title TEXT NOT NULL,
+    namespace INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS revisions (
+    page INTEGER NOT NULL REFERENCES pages(id),
+    rev_id INTEGER NOT NULL,
+    timestamp TEXT NOT NULL,
+    editor TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS tokens (
+    id INTEGER PRIMARY KEY,
+    page INTEGER NOT NULL REFERENCES pages(id),
+    position INTEGER NOT NULL,
+    token_id INTEGER NOT NULL,
+    str TEXT NOT NULL,
+    o_rev_id INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS token_events (
+    token INTEGER NOT NULL REFERENCES tokens(id),
+    kind TEXT NOT NULL CHECK (kind IN ('in', 'out')),
+    rev_id INTEGER NOT NULL
+);
+";
+
+const INDEXES: &str = "
+CREATE INDEX IF NOT EXISTS pages_title ON pages(title);
+CREATE INDEX IF NOT EXISTS revisions_rev_id ON revisions(rev_id);
+CREATE INDEX IF NOT EXISTS revisions_page ON revisions(page);
+CREATE INDEX IF NOT EXISTS tokens_page_position ON tokens(page, position);
+CREATE INDEX IF NOT EXISTS token_events_token ON token_events(token);
+";
+
+/// Writes pages into an SQLite database, see the [module documentation](self).
+///
+/// The rows of the last batch and the indexes are only written by
+/// [`finish`](SqliteWriter::finish).
+pub struct SqliteWriter {
+    connection: Connection,
+    /// Rows inserted since the current transaction began.
+    pending_rows: usize,
+}
+
+impl SqliteWriter {
+    /// Creates the database file at `path`.
+    ///
+    /// Fails if the file is already a database with tables, so that the pages of separate runs
+    /// don't end up mixed; [`SqliteWriter::new`] adds pages to the tables of an open database.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, SqliteError> {
+        let connection = Connection::open(path)?;
+        let tables: i64 =
+            connection.query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get(0))?;
+        if tables > 0 {
+            return Err(SqliteError::SqliteFailure(
+                ffi::Error::new(ffi::SQLITE_CANTOPEN),
+                Some("the database is not empty".into()),
+            ));
+        }
+        Self::new(connection)
+    }
+
+    /// Creates the tables if needed and starts writing to `connection`.
+    ///
+    /// Pages are added to the tables if they already exist.
+    pub fn new(connection: Connection) -> Result<Self, SqliteError> {
+        connection.execute_batch(SCHEMA)?;
+        connection.execute_batch("BEGIN")?;
+        Ok(Self {
+            connection,
+            pending_rows: 0,
+        })
+    }
+
+    /// Adds a page with its revisions and the tokens of its latest revision.
+    ///
+    /// `analysis` must be the analysis of `page`.
+    pub fn write_page(&mut self, page: &Page, analysis: &PageAnalysis) -> Result<(), SqliteError> {
+        let revisions_by_id = revisions_by_id(page);
+
+        self.connection
+            .prepare_cached("INSERT INTO pages (page_id, title, namespace) VALUES (?1, ?2, ?3)")?
+            .execute(params![page.id, page.title.as_str(), page.namespace])?;
+        let page_row = self.connection.last_insert_rowid();
+        let mut rows = 1;
+
+        let mut insert_revision = self.connection.prepare_cached(
+            "INSERT INTO revisions (page, rev_id, timestamp, editor) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for rev_ptr in &analysis.ordered_revisions {
+            let revision = revisions_by_id[&rev_ptr.id];
+            insert_revision.execute(params![
+                page_row,
+                revision.id,
+                revision.timestamp.format(TIMESTAMP_FORMAT).to_string(),
+                Editor(&revision.contributor).to_string(),
+            ])?;
+            rows += 1;
+        }
+
+        let mut insert_token = self.connection.prepare_cached(
+            "INSERT INTO tokens (page, position, token_id, str, o_rev_id)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        let mut insert_event = self
+            .connection
+            .prepare_cached("INSERT INTO token_events (token, kind, rev_id) VALUES (?1, ?2, ?3)")?;
+        for (position, word) in
+            iterate_revision_tokens(analysis, &analysis.current_revision).enumerate()
+        {
+            let word_analysis = &analysis[word];
+            let token_row = insert_token.insert(params![
+                page_row,
+                position as i64,
+                word.unique_id() as i64,
+                word.value.as_str(),
+                word_analysis.origin_revision.id,
+            ])?;
+            rows += 1;
+            for rev in &word_analysis.inbound {
+                insert_event.execute(params![token_row, "in", rev.id])?;
+                rows += 1;
+            }
+            for rev in &word_analysis.outbound {
+                insert_event.execute(params![token_row, "out", rev.id])?;
+                rows += 1;
+            }
+        }
+
+        self.pending_rows += rows;
+        if self.pending_rows >= BATCH_SIZE {
+            self.connection.execute_batch("COMMIT; BEGIN")?;
+            self.pending_rows = 0;
+        }
+        Ok(())
+    }
+
+    /// Commits the remaining rows, creates the indexes and returns the connection.
+    pub fn finish(self) -> Result<Connection, SqliteError> {
+        self.connection.execute_batch("COMMIT")?;
+        self.connection.execute_batch(INDEXES)?;
+        Ok(self.connection)
+    }
+}
@@ -0,0 +1,230 @@
+// SPDX-License-Identifier: MPL-2.0
+//! Token authorship as tab-separated values, one line per token, for R, spreadsheets and
+//! other tools that read flat tables.
+//!
+//! [`TsvWriter`] writes a header line with the column names and then one line per token of
+//! each selected revision, in reading order. The [columns](TsvColumn) are, by default in this
+//! order:
+//!
+//! | Column | Content |
+//! |---|---|
+//! | `page_title` | title of the page |
+//! | `namespace` | namespace id of the page |
+//! | `rev_id` | the selected revision the token is part of |
+//! | `position` | position of the token in that revision, starting at 0 |
+//! | `token_id` | id of the token, unique within the page |
+//! | `str` | the token |
+//! | `o_rev_id` | revision that added the token |
+//! | `o_timestamp` | timestamp of that revision, as `YYYY-MM-DDTHH:MM:SSZ` |
+//! | `editor` | editor of that revision: the user id, or `0|<username>` for anonymous editors |
+//! | `in_count` | number of times the token was reintroduced in the whole history |
+//! | `out_count` | number of times the token was removed in the whole history |
+//!
+//! The counts don't depend on `rev_id`: they include the revisions after the selected one.
+//!
+//! Tabs, line breaks and backslashes in a value are escaped as `\t`, `\n`, `\r` and `\\`, so
+//! every line is one row. Values are never quoted and `"` is written as is, e.g. as a token
+//! of its own, so readers must not treat it as a quote character (e.g.
+//! `readr::read_tsv(escape_backslash = TRUE, quote = "")` in R, or disabling text quoting when
+//! importing into a spreadsheet).
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use wikiwho::algorithm::PageAnalysis;
+//! use wikiwho::dump_parser::DumpParser;
+//! use wikiwho::output::tsv::{TsvColumn, TsvWriter};
+//! use wikiwho::output::RevisionSelection;
+//!
+//! let reader = std::io::BufReader::new(std::fs::File::open("dump.xml").unwrap());
+//! let mut parser = DumpParser::new(reader).unwrap();
+//! let out = std::io::BufWriter::new(std::fs::File::create("out.tsv").unwrap());
+//! let columns = vec![TsvColumn::PageTitle, TsvColumn::Token, TsvColumn::Editor];
+//! let mut writer = TsvWriter::new(out, columns).unwrap();
+//! while let Some(page) = parser.parse_page().unwrap() {
+//!     let analysis = PageAnalysis::analyse_page(&page.revisions).unwrap();
+//!     writer.write_page(&page, &analysis, &RevisionSelection::Latest).unwrap();
+//! }
+//! writer.finish().unwrap();
+//! ```
+use std::{
+    fmt::{self, Display},
+    io::{self, Write},
+    str::FromStr,
+};
+
+use super::{revisions_by_id, Editor, RevisionSelection, TIMESTAMP_FORMAT};
+use crate::{algorithm::PageAnalysis, dump_parser::Page, utils::iterate_revision_tokens};
+
+/// A column of the TSV output, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TsvColumn {
+    PageTitle,
+    Namespace,
+    RevId,
+    Position,
+    TokenId,
+    Token,
+    OriginRevId,
+    OriginTimestamp,
+    Editor,
+    InCount,
+    OutCount,
+}
+
+/// A column name that is not one of [`TsvColumn::ALL`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown column `{0}`")]
+pub struct UnknownColumn(pub String);
+
+impl TsvColumn {
+    /// All columns, in their default order.
+    pub const ALL: [TsvColumn; 11] = [
+        TsvColumn::PageTitle,
+        TsvColumn::Namespace,
+        TsvColumn::RevId,
+        TsvColumn::Position,
+        TsvColumn::TokenId,
+        TsvColumn::Token,
+        TsvColumn::OriginRevId,
+        TsvColumn::OriginTimestamp,
+        TsvColumn::Editor,
+        TsvColumn::InCount,
+        TsvColumn::OutCount,
+    ];
+
+    /// The name of the column in the header line.
+    pub fn name(self) -> &'static str {
+        match self {
+            TsvColumn::PageTitle => "page_title",
+            TsvColumn::Namespace => "namespace",
+            TsvColumn::RevId => "rev_id",
+            TsvColumn::Position => "position",
+            TsvColumn::TokenId => "token_id",
+            TsvColumn::Token => "str",
+            TsvColumn::OriginRevId => "o_rev_id",
+            TsvColumn::OriginTimestamp => "o_timestamp",
+            TsvColumn::Editor => "editor",
+            TsvColumn::InCount => "in_count",
+            TsvColumn::OutCount => "out_count",
+        }
+    }
+}
+
+impl Display for TsvColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for TsvColumn {
+    type Err = UnknownColumn;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TsvColumn::ALL
+            .into_iter()
+            .find(|column| column.name() == s)
+            .ok_or_else(|| UnknownColumn(s.to_string()))
+    }
+}
+
+/// Writes the tokens of pages as TSV, see the [module documentation](self).
+pub struct TsvWriter<W: Write> {
+    writer: W,
+    columns: Vec<TsvColumn>,
+}
+
+impl<W: Write> TsvWriter<W> {
+    /// Writes the header line of `columns` and returns the writer for the pages.
+    ///
+    /// `writer` should be buffered.
+    pub fn new(mut writer: W, columns: Vec<TsvColumn>) -> io::Result<Self> {
+        for (i, column) in columns.iter().enumerate() {
+            if i > 0 {
+                writer.write_all(b"\t")?;
+            }
+            writer.write_all(column.name().as_bytes())?;
+        }
+        writer.write_all(b"\n")?;
+        Ok(Self { writer, columns })
+    }
+
+    /// Writes the tokens of the selected revisions of `page`, `analysis` must be the analysis
+    /// of `page`. Selected revisions that were detected as spam are left out.
+    pub fn write_page(
+        &mut self,
+        page: &Page,
+        analysis: &PageAnalysis,
+        revisions: &RevisionSelection,
+    ) -> io::Result<()> {
+        let revisions_by_id = revisions_by_id(page);
+
+        let selected: Vec<_> = match revisions {
+            RevisionSelection::Latest => vec![&analysis.current_revision],
+            RevisionSelection::All | RevisionSelection::Ids(_) => analysis
+                .ordered_revisions
+                .iter()
+                .filter(|rev_ptr| revisions.contains(rev_ptr.id))
+                .collect(),
+        };
+
+        for rev_ptr in selected {
+            for (position, word_ptr) in iterate_revision_tokens(analysis, rev_ptr).enumerate() {
+                let word_analysis = &analysis[word_ptr];
+                let origin = revisions_by_id[&word_analysis.origin_revision.id];
+                for (i, column) in self.columns.iter().enumerate() {
+                    if i > 0 {
+                        self.writer.write_all(b"\t")?;
+                    }
+                    match column {
+                        TsvColumn::PageTitle => write_escaped(&mut self.writer, &page.title)?,
+                        TsvColumn::Namespace => write!(self.writer, "{}", page.namespace)?,
+                        TsvColumn::RevId => write!(self.writer, "{}", rev_ptr.id)?,
+                        TsvColumn::Position => write!(self.writer, "{position}")?,
+                        TsvColumn::TokenId => write!(self.writer, "{}", word_ptr.unique_id())?,
+                        TsvColumn::Token => write_escaped(&mut self.writer, &word_ptr.value)?,
+                        TsvColumn::OriginRevId => write!(self.writer, "{}", origin.id)?,
+                        TsvColumn::OriginTimestamp => {
+                            write!(self.writer, "{}", origin.timestamp.format(TIMESTAMP_FORMAT))?
+                        }
+                        TsvColumn::Editor => write_escaped(
+                            &mut self.writer,
+                            &Editor(&origin.contributor).to_string(),
+                        )?,
+                        TsvColumn::InCount => {
+                            write!(self.writer, "{}", word_analysis.inbound.len())?
+                        }
+                        TsvColumn::OutCount => {
+                            write!(self.writer, "{}", word_analysis.outbound.len())?
+                        }
+                    }
+                }
+                self.writer.write_all(b"\n")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Writes `value` with tabs, line breaks and backslashes escaped, and `"` as is.
+fn write_escaped<W: Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    let mut rest = value;
+    while let Some(i) = rest.find(['\t', '\n', '\r', '\\']) {
+        writer.write_all(&rest.as_bytes()[..i])?;
+        let escaped: &[u8] = match rest.as_bytes()[i] {
+            b'\t' => b"\\t",
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            _ => b"\\\\",
+        };
+        writer.write_all(escaped)?;
+        rest = &rest[i + 1..];
+    }
+    writer.write_all(rest.as_bytes())
+}
@@ -0,0 +1,408 @@
+// SPDX-License-Identifier: MPL-2.0
+//! Analysis results in the response schemas of the [WikiWho web API](https://wikiwho-api.wmcloud.org/),
+//! for code written against the API.
+//!
+//! Each type corresponds to one endpoint, with every optional field requested
+//! (`o_rev_id`, `editor`, `token_id`, `in`, `out`, `timestamp`):
+//!
+//! - [`AllContent`]: `all_content`, the tokens of the latest revision,
+//! - [`RevContent`]: `rev_content`, the tokens of selected revisions,
+//! - [`RevIds`]: `rev_ids`, the analysed revisions,
+//! - [`EditPersistence`]: `edit_persistence`, the monthly added, deleted and reintroduced
+//!   tokens of each editor and how long these edits survived.
+//!
+//! Like in the API, editors are written as the user id, or `0|<username>` for anonymous
+//! editors, and revisions detected as spam are left out.
+//!
+//! The types follow the response format documented by the API. They are not yet checked
+//! against responses saved from the service, so details may differ; in particular the
+//! survival counts of [`EditPersistence`] are this crate's reading of the documentation.
+//!
+//! The views borrow from the parsed [`Page`] and its [`PageAnalysis`], which must be the
+//! analysis of that page.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use wikiwho::algorithm::PageAnalysis;
+//! use wikiwho::dump_parser::DumpParser;
+//! use wikiwho::output::wikiwho_api::AllContent;
+//!
+//! let reader = std::io::BufReader::new(std::fs::File::open("dump.xml").unwrap());
+//! let mut parser = DumpParser::new(reader).unwrap();
+//! while let Some(page) = parser.parse_page().unwrap() {
+//!     let analysis = PageAnalysis::analyse_page(&page.revisions).unwrap();
+//!     let response = AllContent::new(&page, &analysis);
+//!     println!("{}", serde_json::to_string(&response).unwrap());
+//! }
+//! ```
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, TimeDelta};
+use serde::{ser::SerializeMap, Serialize, Serializer};
+
+use crate::{
+    algorithm::{PageAnalysis, RevisionPointer, WordPointer},
+    dump_parser::{Page, Revision},
+    utils::iterate_revision_tokens,
+};
+
+pub use super::Editor;
+use super::{revisions_by_id, TIMESTAMP_FORMAT};
+
+/// A token of [`AllContent`] or [`RevContent`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Token<'a> {
+    #[serde(rename = "str")]
+    pub value: &'a str,
+    /// The revision that first introduced the token.
+    pub o_rev_id: i32,
+    /// The editor of `o_rev_id`.
+    pub editor: Editor<'a>,
+    pub token_id: usize,
+    /// Revisions that re-inserted the token.
+    #[serde(rename = "in")]
+    pub inbound: Vec<i32>,
+    /// Revisions that removed the token.
+    #[serde(rename = "out")]
+    pub outbound: Vec<i32>,
+}
+
+/// Response of the `all_content` endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AllContent<'a> {
+    pub article_title: &'a str,
+    pub page_id: Option<i32>,
+    /// The tokens of the latest revision, in reading order.
+    pub all_tokens: Vec<Token<'a>>,
+    pub success: bool,
+    pub message: Option<String>,
+}
+
+/// Response of the `rev_content` endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RevContent<'a> {
+    pub article_title: &'a str,
+    pub page_id: Option<i32>,
+    pub revisions: Vec<RevContentRevision<'a>>,
+    pub success: bool,
+    pub message: Option<String>,
+}
+
+/// A revision of [`RevContent`], serialized as an object with the revision id as its only key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevContentRevision<'a> {
+    pub id: i32,
+    pub editor: Editor<'a>,
+    pub time: String,
+    /// The tokens of the revision, in reading order.
+    pub tokens: Vec<Token<'a>>,
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for RevContentRevision<'_> {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "RevContentRevision".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        let editor = generator.subschema_for::<Editor>();
+        let tokens = generator.subschema_for::<Vec<Token>>();
+        schemars::json_schema!({
+            "type": "object",
+            "description": "A revision, as an object with the revision id as its only key.",
+            "minProperties": 1,
+            "maxProperties": 1,
+            "propertyNames": { "pattern": "^-?[0-9]+$" },
+            "additionalProperties": {
+                "type": "object",
+                "properties": {
+                    "editor": editor,
+                    "time": { "type": "string" },
+                    "tokens": tokens,
+                },
+                "required": ["editor", "time", "tokens"],
+            },
+        })
+    }
+}
+
+impl Serialize for RevContentRevision<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Content<'b, 'a> {
+            editor: Editor<'a>,
+            time: &'b str,
+            tokens: &'b [Token<'a>],
+        }
+
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(
+            &self.id,
+            &Content {
+                editor: self.editor,
+                time: &self.time,
+                tokens: &self.tokens,
+            },
+        )?;
+        map.end()
+    }
+}
+
+/// Response of the `rev_ids` endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RevIds<'a> {
+    pub article_title: &'a str,
+    pub page_id: Option<i32>,
+    pub revisions: Vec<RevIdsRevision<'a>>,
+    pub success: bool,
+    pub message: Option<String>,
+}
+
+/// A revision of [`RevIds`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RevIdsRevision<'a> {
+    pub id: i32,
+    pub editor: Editor<'a>,
+    pub timestamp: String,
+}
+
+/// Response of the `edit_persistence` endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EditPersistence {
+    /// Sorted by month and editor.
+    pub editions: Vec<Edition>,
+    pub success: bool,
+    pub message: Option<String>,
+}
+
+/// The actions of one editor on a page in one month, see [`EditPersistence`].
+///
+/// Every token an editor adds, deletes or reintroduces counts as one action. An action
+/// survived 48 hours (`_surv_48h`) if no later revision undid it within 48 hours, and is
+/// persistent (`_persistent`) if it was not undone by the latest revision. Stopwords
+/// (`_stopword_count`) are counted as well as included in the totals. This is how this crate
+/// reads the API documentation; the service may count differently.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Edition {
+    /// The first day of the month, e.g. `2006-09-01`.
+    pub year_month: String,
+    pub page_id: Option<i32>,
+    pub editor_id: String,
+    pub adds: u64,
+    pub adds_surv_48h: u64,
+    pub adds_persistent: u64,
+    pub adds_stopword_count: u64,
+    pub dels: u64,
+    pub dels_surv_48h: u64,
+    pub dels_persistent: u64,
+    pub dels_stopword_count: u64,
+    pub reins: u64,
+    pub reins_surv_48h: u64,
+    pub reins_persistent: u64,
+    pub reins_stopword_count: u64,
+}
+
+#[derive(Clone, Copy)]
+enum Action {
+    Add,
+    Delete,
+    Reintroduce,
+}
+
+impl Edition {
+    fn count(&mut self, action: Action, surv_48h: bool, persistent: bool, stopword: bool) {
+        let (total, total_surv_48h, total_persistent, stopword_count) = match action {
+            Action::Add => (
+                &mut self.adds,
+                &mut self.adds_surv_48h,
+                &mut self.adds_persistent,
+                &mut self.adds_stopword_count,
+            ),
+            Action::Delete => (
+                &mut self.dels,
+                &mut self.dels_surv_48h,
+                &mut self.dels_persistent,
+                &mut self.dels_stopword_count,
+            ),
+            Action::Reintroduce => (
+                &mut self.reins,
+                &mut self.reins_surv_48h,
+                &mut self.reins_persistent,
+                &mut self.reins_stopword_count,
+            ),
+        };
+        *total += 1;
+        *total_surv_48h += u64::from(surv_48h);
+        *total_persistent += u64::from(persistent);
+        *stopword_count += u64::from(stopword);
+    }
+}
+
+/// The revisions of the page, to look up the metadata the analysis doesn't keep.
+struct Revisions<'a>(HashMap<i32, &'a Revision>);
+
+impl<'a> Revisions<'a> {
+    fn new(page: &'a Page) -> Self {
+        Self(revisions_by_id(page))
+    }
+
+    fn get(&self, revision: &RevisionPointer) -> &'a Revision {
+        self.0[&revision.id]
+    }
+
+    fn token(&self, analysis: &'a PageAnalysis, word: &'a WordPointer) -> Token<'a> {
+        let word_analysis = &analysis[word];
+        let origin_revision = self.get(&word_analysis.origin_revision);
+        Token {
+            value: &word.value,
+            o_rev_id: origin_revision.id,
+            editor: Editor(&origin_revision.contributor),
+            token_id: word.unique_id(),
+            inbound: word_analysis.inbound.iter().map(|r| r.id).collect(),
+            outbound: word_analysis.outbound.iter().map(|r| r.id).collect(),
+        }
+    }
+}
+
+impl<'a> AllContent<'a> {
+    pub fn new(page: &'a Page, analysis: &'a PageAnalysis) -> Self {
+        let revisions = Revisions::new(page);
+        Self {
+            article_title: &page.title,
+            page_id: page.id,
+            all_tokens: iterate_revision_tokens(analysis, &analysis.current_revision)
+                .map(|word| revisions.token(analysis, word))
+                .collect(),
+            success: true,
+            message: None,
+        }
+    }
+}
+
+impl<'a> RevContent<'a> {
+    /// Creates the response with the tokens of the given revisions of the analysis.
+    ///
+    /// The API returns the latest revision (`analysis.current_revision`), a single revision
+    /// or a range of `analysis.ordered_revisions`.
+    pub fn new(
+        page: &'a Page,
+        analysis: &'a PageAnalysis,
+        selected: impl IntoIterator<Item = &'a RevisionPointer>,
+    ) -> Self {
+        let revisions = Revisions::new(page);
+        Self {
+            article_title: &page.title,
+            page_id: page.id,
+            revisions: selected
+                .into_iter()
+                .map(|rev_ptr| {
+                    let revision = revisions.get(rev_ptr);
+                    RevContentRevision {
+                        id: revision.id,
+                        editor: Editor(&revision.contributor),
+                        time: revision.timestamp.format(TIMESTAMP_FORMAT).to_string(),
+                        tokens: iterate_revision_tokens(analysis, rev_ptr)
+                            .map(|word| revisions.token(analysis, word))
+                            .collect(),
+                    }
+                })
+                .collect(),
+            success: true,
+            message: None,
+        }
+    }
+}
+
+impl<'a> RevIds<'a> {
+    pub fn new(page: &'a Page, analysis: &PageAnalysis) -> Self {
+        let revisions = Revisions::new(page);
+        Self {
+            article_title: &page.title,
+            page_id: page.id,
+            revisions: analysis
+                .ordered_revisions
+                .iter()
+                .map(|rev_ptr| {
+                    let revision = revisions.get(rev_ptr);
+                    RevIdsRevision {
+                        id: revision.id,
+                        editor: Editor(&revision.contributor),
+                        timestamp: revision.timestamp.format(TIMESTAMP_FORMAT).to_string(),
+                    }
+                })
+                .collect(),
+            success: true,
+            message: None,
+        }
+    }
+}
+
+impl EditPersistence {
+    /// Counts the actions of every editor per month.
+    ///
+    /// `is_stopword` is called with the lowercased token to fill in the `_stopword_count`
+    /// fields, pass `|_| false` to leave them at zero.
+    pub fn new(page: &Page, analysis: &PageAnalysis, is_stopword: impl Fn(&str) -> bool) -> Self {
+        let revisions = Revisions::new(page);
+        // (year, month, editor) -> edition
+        let mut editions: BTreeMap<(i32, u32, String), Edition> = BTreeMap::new();
+
+        for word in &analysis.words {
+            let word_analysis = &analysis[word];
+            let stopword = is_stopword(&word.value);
+
+            // a token is added, then alternately deleted and reintroduced,
+            // each action is undone by the next one
+            let mut actions = vec![(Action::Add, &word_analysis.origin_revision)];
+            for (i, deleted) in word_analysis.outbound.iter().enumerate() {
+                actions.push((Action::Delete, deleted));
+                if let Some(reintroduced) = word_analysis.inbound.get(i) {
+                    actions.push((Action::Reintroduce, reintroduced));
+                }
+            }
+
+            for (i, (action, rev_ptr)) in actions.iter().enumerate() {
+                let revision = revisions.get(rev_ptr);
+                let undone_at = actions
+                    .get(i + 1)
+                    .map(|(_, next)| revisions.get(next).timestamp);
+                let surv_48h =
+                    undone_at.is_none_or(|t| t - revision.timestamp >= TimeDelta::hours(48));
+                let persistent = undone_at.is_none();
+
+                let key = (
+                    revision.timestamp.year(),
+                    revision.timestamp.month(),
+                    Editor(&revision.contributor).to_string(),
+                );
+                editions
+                    .entry(key)
+                    .or_default()
+                    .count(*action, surv_48h, persistent, stopword);
+            }
+        }
+
+        Self {
+            editions: editions
+                .into_iter()
+                .map(|((year, month, editor_id), edition)| Edition {
+                    year_month: format!("{year:04}-{month:02}-01"),
+                    page_id: page.id,
+                    editor_id,
+                    ..edition
+                })
+                .collect(),
+            success: true,
+            message: None,
+        }
+    }
+}
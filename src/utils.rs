@@ -22,12 +22,6 @@ impl Debug for DebugStringEllipsis<'_> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub(crate) enum RevisionHash {
-    Sha1(Sha1Hash),
-    Blake3(blake3::Hash),
-}
-
 /// Split the input text into paragraphs.
 ///
 /// # Arguments
@@ -178,7 +172,7 @@ pub fn split_into_tokens_naive(text: &str) -> Vec<Cow<'_, str>> {
 
 pub fn to_lowercase(
     input: &str,
-    #[allow(unused)] analysis_options: PageAnalysisOptions,
+    #[allow(unused)] analysis_options: &PageAnalysisOptions,
 ) -> (usize, String) {
     #[cfg(feature = "python-diff")]
     {
@@ -249,11 +243,10 @@ pub fn to_lowercase_opt(input: &str) -> (usize, String) {
     (char_count, result)
 }
 
-use std::{collections::HashMap, hash::Hash, sync::LazyLock};
+use std::{collections::HashMap, sync::LazyLock};
 
-use crate::{
-    algorithm::{ArcSubstring, PageAnalysis, PageAnalysisOptions, RevisionPointer, WordPointer},
-    dump_parser::Sha1Hash,
+use crate::algorithm::{
+    ArcSubstring, PageAnalysis, PageAnalysisOptions, RevisionPointer, WordPointer,
 };
 
 pub fn compute_avg_word_freq(token_list: &[Token], interner: &mut Interner<ArcSubstring>) -> f64 {
@@ -205,6 +205,7 @@ fn test_case_1() {
     // found by proptest
     Python::attach(|py| {
         let page = Page {
+            id: None,
             title: "Test".into(),
             namespace: 0,
             revisions: vec![
@@ -256,6 +257,7 @@ fn test_case_1() {
 fn test_case_2() {
     // found by proptest
     let page = Page {
+        id: None,
         title: "Test".into(),
         namespace: 0,
         revisions: vec![
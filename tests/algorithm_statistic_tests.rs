@@ -1,16 +1,16 @@
 // SPDX-License-Identifier: MPL-2.0
-#![cfg(feature = "serde")] // needed for JSON cache path
+#![cfg(all(feature = "serde", feature = "compression"))] // JSON cache path, compressed dumps
 
 use std::{
     collections::HashMap,
     fs::{self, File},
-    io::{BufReader, Read},
+    io::BufReader,
     path::{Path, PathBuf},
 };
 
 use wikiwho::{
     algorithm::{AnalysisError, PageAnalysis, PageAnalysisOptions},
-    dump_parser::{DumpParser, Page, ParsingError},
+    dump_parser::{open_decompressed, DumpParser, Page, ParsingError},
 };
 
 mod common;
@@ -119,22 +119,8 @@ fn find_xml_for_article(article: &str) -> Option<PathBuf> {
     None
 }
 
-fn open_maybe_compressed_reader(path: &Path) -> Result<Box<dyn Read>, std::io::Error> {
-    let file = File::open(path)?;
-    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-
-    match ext {
-        "bz2" => Ok(Box::new(bzip2::read::BzDecoder::new(file))),
-        "gz" => Ok(Box::new(flate2::read::GzDecoder::new(file))),
-        "zst" | "zstd" => Ok(Box::new(zstd::stream::read::Decoder::new(file)?)),
-        _ => Ok(Box::new(file)),
-    }
-}
-
 fn load_page_from_xml(path: &Path) -> Result<Page, ParsingError> {
-    let reader = open_maybe_compressed_reader(path)
-        .unwrap_or_else(|e| panic!("Could not open {}: {e}", path.display()));
-    let mut parser = DumpParser::new(BufReader::new(reader))
+    let mut parser = DumpParser::open(path)
         .unwrap_or_else(|e| panic!("Could not create DumpParser for {}: {e}", path.display()));
     Ok(parser
         .parse_page()?
@@ -176,8 +162,8 @@ fn cache_path_for_article(article: &str) -> PathBuf {
 }
 
 fn find_page_in_dump(dump_path: &Path, title: &str) -> Option<Page> {
-    let reader = open_maybe_compressed_reader(dump_path).ok()?;
-    common::find_page_by_title_and_ns(BufReader::new(reader), title, 0)
+    let reader = open_decompressed(dump_path).ok()?;
+    common::find_page_by_title_and_ns(reader, title, 0)
         .ok()
         .flatten()
 }
@@ -389,14 +375,15 @@ fn run_precision_test(options: PageAnalysisOptions) -> (usize, usize, usize) {
             article_entries.len()
         );
 
-        let analysis = match PageAnalysis::analyse_page_with_options(&page.revisions, options) {
-            Ok(a) => a,
-            Err(AnalysisError::NoValidRevisions) => {
-                eprintln!("  Skipping '{article}': no valid revisions");
-                continue;
-            }
-            Err(e) => panic!("Analysis failed for '{article}': {e}"),
-        };
+        let analysis =
+            match PageAnalysis::analyse_page_with_options(&page.revisions, options.clone()) {
+                Ok(a) => a,
+                Err(AnalysisError::NoValidRevisions) => {
+                    eprintln!("  Skipping '{article}': no valid revisions");
+                    continue;
+                }
+                Err(e) => panic!("Analysis failed for '{article}': {e}"),
+            };
 
         for entry in article_entries {
             match find_token_origin(&analysis, entry) {
@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: MPL-2.0
+#![cfg(feature = "serde")]
+
+use wikiwho::dump_parser::{
+    api_json::{self, ApiJsonError, ApiJsonReader},
+    Comment, Contributor, DumpParser, Sha1Hash, Text,
+};
+
+const FIXTURE: &str =
+    include_str!("fixtures/exact-regressions/Anontalkpagetext_shortened-manually.xml");
+
+#[test]
+fn continuation_files_match_dump() {
+    let mut parser = DumpParser::new(FIXTURE.as_bytes()).unwrap();
+    let expected = parser.parse_page().unwrap().unwrap();
+
+    let pages = api_json::read_files([
+        "tests/fixtures/api-json/Anontalkpagetext-1.json",
+        "tests/fixtures/api-json/Anontalkpagetext-2.json",
+    ])
+    .unwrap();
+    assert_eq!(pages, vec![expected.clone()]);
+
+    // the order of the parts doesn't matter, repeated revisions are dropped
+    let pages = api_json::read_files([
+        "tests/fixtures/api-json/Anontalkpagetext-2.json",
+        "tests/fixtures/api-json/Anontalkpagetext-1.json",
+        "tests/fixtures/api-json/Anontalkpagetext-2.json",
+    ])
+    .unwrap();
+    assert_eq!(pages, vec![expected]);
+}
+
+#[test]
+fn hidden_and_anonymous_revisions() {
+    let pages =
+        api_json::read_files(["tests/fixtures/api-json/hidden-formatversion-1.json"]).unwrap();
+    // the missing page and the invalid one, which has no namespace, are skipped
+    assert_eq!(pages.len(), 1);
+    let page = &pages[0];
+    assert_eq!(page.title, "Hidden history");
+    assert_eq!(page.namespace, 0);
+
+    let ids = page.revisions.iter().map(|r| r.id).collect::<Vec<_>>();
+    assert_eq!(ids, [101, 102, 103, 104]);
+    let [anon, user_hidden, text_hidden, normal] = page.revisions.as_slice() else {
+        unreachable!()
+    };
+
+    assert_eq!(
+        anon.contributor,
+        Contributor {
+            username: "192.0.2.1".into(),
+            id: None,
+            deleted: false,
+        }
+    );
+    assert_eq!(anon.comment, Some(Comment::Normal("first".into())));
+    assert_eq!(anon.sha1, Some(Sha1Hash::compute("abc")));
+
+    assert_eq!(user_hidden.contributor, Contributor::deleted());
+    assert_eq!(user_hidden.comment, Some(Comment::Deleted));
+    assert_eq!(user_hidden.text, Text::Normal(String::new()));
+
+    assert_eq!(text_hidden.text, Text::Deleted);
+    assert_eq!(text_hidden.sha1, None);
+    assert_eq!(text_hidden.comment, None);
+    assert!(text_hidden.minor);
+
+    assert_eq!(normal.contributor.id, Some(42));
+    assert_eq!(normal.text, Text::Normal("abc".into()));
+    assert!(!normal.minor);
+}
+
+#[test]
+fn api_errors_are_reported() {
+    let mut reader = ApiJsonReader::new();
+    let error = reader
+        .read_str(r#"{"error": {"code": "badvalue", "info": "Unrecognized value for parameter \"prop\"."}}"#)
+        .unwrap_err();
+    assert!(matches!(error, ApiJsonError::Api { code, .. } if code == "badvalue"));
+
+    let error = reader
+        .read_str(
+            r#"{"query": {"pages": [{"pageid": 1, "ns": 0, "title": "A",
+                "revisions": [{"revid": 5, "timestamp": "2020-01-01T00:00:00Z", "user": "X", "userid": 1}]}]}}"#,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        ApiJsonError::MissingField {
+            revision_id: 5,
+            field: "content"
+        }
+    ));
+}
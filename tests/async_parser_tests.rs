@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MPL-2.0
+#![cfg(feature = "async")]
+
+use tokio::io::BufReader;
+use wikiwho::dump_parser::{
+    AsyncDumpParser, DiagnosticKind, DumpParser, Page, ParserOptions, ParsingError,
+    ParsingErrorKind,
+};
+
+const FIXTURE: &str =
+    include_str!("fixtures/exact-regressions/Anontalkpagetext_shortened-manually.xml");
+
+/// Builds a dump with `num_pages` copies of the fixture page, each with a distinct title.
+fn build_dump(num_pages: usize) -> String {
+    let page_start = FIXTURE.find("  <page>").unwrap();
+    let page_end = FIXTURE.find("</page>").unwrap() + "</page>\n".len();
+    let (header, page) = (&FIXTURE[..page_start], &FIXTURE[page_start..page_end]);
+
+    let mut dump = String::from(header);
+    for i in 0..num_pages {
+        dump.push_str(&page.replace(
+            "MediaWiki:Anontalkpagetext",
+            &format!("MediaWiki:Anontalkpagetext {i}"),
+        ));
+    }
+    dump.push_str("</mediawiki>\n");
+    dump
+}
+
+fn parse_sequential(xml: &str) -> Result<Vec<Page>, ParsingError> {
+    let mut parser = DumpParser::new(xml.as_bytes())?;
+    let mut pages = Vec::new();
+    while let Some(page) = parser.parse_page()? {
+        pages.push(page);
+    }
+    Ok(pages)
+}
+
+#[tokio::test]
+async fn async_parser_matches_sequential_parser() {
+    let dump = build_dump(20);
+    let expected = parse_sequential(&dump).unwrap();
+
+    // small buffers split tags and page boundaries across reads
+    for capacity in [7, 64, 8 * 1024] {
+        let reader = BufReader::with_capacity(capacity, dump.as_bytes());
+        let mut parser = AsyncDumpParser::new(reader).await.unwrap();
+        assert_eq!(
+            parser.site_info(),
+            DumpParser::new(dump.as_bytes()).unwrap().site_info()
+        );
+
+        let mut actual = Vec::new();
+        while let Some(page) = parser.parse_page().await.unwrap() {
+            actual.push(page);
+        }
+        assert_eq!(actual, expected, "buffer capacity {capacity}");
+    }
+}
+
+#[tokio::test]
+async fn async_parser_reports_truncated_page() {
+    let mut dump = build_dump(2);
+    dump.truncate(dump.rfind("<revision>").unwrap());
+
+    let mut parser = AsyncDumpParser::new(dump.as_bytes()).await.unwrap();
+    assert!(parser.parse_page().await.unwrap().is_some());
+    let error = parser.parse_page().await.unwrap_err();
+    assert!(matches!(error.kind(), ParsingErrorKind::Eof));
+    assert_eq!(
+        error.location().page_title.as_deref(),
+        Some("Anontalkpagetext 1")
+    );
+    assert!(parser.parse_page().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn async_parser_reports_diagnostics_like_sequential_parser() {
+    let dump = build_dump(3).replacen(
+        "<timestamp>2007-05-23T15:43:05Z</timestamp>",
+        "<timestamp>yesterday</timestamp>",
+        2,
+    );
+
+    let mut sequential = DumpParser::new(dump.as_bytes()).unwrap();
+    let reader = BufReader::with_capacity(100, dump.as_bytes());
+    let mut parser = AsyncDumpParser::new(reader).await.unwrap();
+    while let Some(page) = parser.parse_page().await.unwrap() {
+        assert_eq!(Some(page), sequential.parse_page().unwrap());
+        assert_eq!(parser.diagnostics(), sequential.diagnostics());
+    }
+    assert!(sequential.parse_page().unwrap().is_none());
+
+    // in strict mode the broken pages are skipped
+    let options = ParserOptions::new().strict(true);
+    let mut parser = AsyncDumpParser::with_options(dump.as_bytes(), options)
+        .await
+        .unwrap();
+    for _ in 0..2 {
+        let error = parser.parse_page().await.unwrap_err();
+        assert!(error.is_recoverable());
+        assert!(matches!(
+            error.kind(),
+            ParsingErrorKind::Malformed(DiagnosticKind::InvalidTimestamp(_))
+        ));
+    }
+    let page = parser.parse_page().await.unwrap().unwrap();
+    assert_eq!(page.title, "Anontalkpagetext 2");
+    assert!(parser.parse_page().await.unwrap().is_none());
+}
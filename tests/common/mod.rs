@@ -18,7 +18,8 @@ use std::{
     io::{BufRead, BufReader, Read},
 };
 
-use wikiwho::dump_parser::{self, Contributor, Page, Revision, Text};
+use wikiwho::algorithm::PageAnalysis;
+use wikiwho::dump_parser::{self, Contributor, DumpParser, Page, Revision, Text};
 
 #[cfg(feature = "python-diff")]
 use pyo3::prelude::*;
@@ -63,6 +64,17 @@ pub(crate) fn bincode_deserialize<T: serde::de::DeserializeOwned>(
     bincode::serde::decode_from_slice(bytes, bincode::config::standard()).map(|(value, _)| value)
 }
 
+/// The dump of the made-up page `Example`, also the source of the WikiWho API fixtures.
+pub const EXAMPLE_DUMP: &str = include_str!("../fixtures/wikiwho-api/Example.xml");
+
+/// Parses [`EXAMPLE_DUMP`] and analyses its page.
+pub fn parse_example() -> (Page, PageAnalysis) {
+    let mut parser = DumpParser::new(EXAMPLE_DUMP.as_bytes()).unwrap();
+    let page = parser.parse_page().unwrap().unwrap();
+    let analysis = PageAnalysis::analyse_page(&page.revisions).unwrap();
+    (page, analysis)
+}
+
 pub fn dummy_revision() -> Revision {
     Revision {
         id: 0,
@@ -71,10 +83,14 @@ pub fn dummy_revision() -> Revision {
         contributor: Contributor {
             id: None,
             username: "Dummy".into(),
+            deleted: false,
         },
         comment: None,
         sha1: None,
         minor: false,
+        model: None,
+        format: None,
+        slots: Vec::new(),
     }
 }
 
@@ -716,13 +732,21 @@ pub mod input_structs {
     impl PyRevision {
         pub fn from_revision(rev: &Revision) -> Self {
             let (text, deleted) = match &rev.text {
-                Text::Normal(s) => (Some(s.clone()), PyDeleted(false)),
+                Text::Normal(_) | Text::Shared(_) => {
+                    (Some(rev.text.as_str().to_owned()), PyDeleted(false))
+                }
                 Text::Deleted => (None, PyDeleted(true)),
+                // like mwxml for a stub dump
+                Text::Stub { .. } => (None, PyDeleted(false)),
             };
             Self {
                 id: rev.id,
                 text,
-                comment: rev.comment.as_ref().map(|s| s.to_string()),
+                comment: rev
+                    .comment
+                    .as_ref()
+                    .and_then(|comment| comment.as_visible())
+                    .map(str::to_string),
                 sha1: rev.sha1.as_ref().map(|s| {
                     std::str::from_utf8(&s.0)
                         .expect("sha1 to be base36 encoded")
@@ -800,12 +824,13 @@ pub mod proptest_support {
     use proptest::prelude::*;
     use proptest::strategy::Strategy;
 
-    use wikiwho::dump_parser::{Contributor, Page, Revision, Sha1Hash, Text};
+    use wikiwho::dump_parser::{Comment, Contributor, Page, Revision, Sha1Hash, Text};
 
-    pub fn maybe_comment() -> impl Strategy<Value = Option<CompactString>> {
+    pub fn maybe_comment() -> impl Strategy<Value = Option<Comment>> {
         prop_oneof![
             7 => Just(None),
-            1 => any::<String>().prop_map(CompactString::from).prop_map(Some)
+            1 => Just(Some(Comment::Deleted)),
+            2 => any::<String>().prop_map(CompactString::from).prop_map(Comment::Normal).prop_map(Some)
         ]
     }
 
@@ -818,8 +843,11 @@ pub mod proptest_support {
 
     pub fn sha1(text: &Text) -> impl Strategy<Value = Sha1Hash> {
         match text {
-            Text::Deleted => Just(Sha1Hash(*b"verycoolhashofdeletedtext123456")),
-            Text::Normal(text) => {
+            Text::Deleted | Text::Stub { .. } => {
+                Just(Sha1Hash(*b"verycoolhashofdeletedtext123456"))
+            }
+            Text::Normal(_) | Text::Shared(_) => {
+                let text = text.as_str();
                 // Just use any hash function here, only needs to make sure the same text always has the same hash
                 // Collisions are not a concern since we have "few" revisions in our tests
                 let hash = blake3::Hasher::new().update(text.as_bytes()).finalize();
@@ -848,11 +876,15 @@ pub mod proptest_support {
                 contributor: Contributor { /* ignored in algorithm */
                     id: None,
                     username: "".into(),
+                    deleted: false,
                 },
                 text,
                 sha1,
                 comment,
-                minor
+                minor,
+                model: None,
+                format: None,
+                slots: Vec::new(),
             }
         }
     }
@@ -877,6 +909,7 @@ pub mod proptest_support {
                 (revisions in correct_revision_vec(has_hash, text_strategy.clone(), max_revisions))
         -> Page {
             Page {
+                id: None,
                 title: "Pagetitle".into(), /* ignored in algorithm */
                 namespace: 0, /* ignored in algorithm */
                 revisions
@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: MPL-2.0
+#![cfg(feature = "compression")]
+
+use std::{
+    fs,
+    io::{self, BufReader, Write},
+    path::PathBuf,
+};
+
+use wikiwho::dump_parser::{Compression, DumpParser, Page, ParsingErrorKind};
+
+const FIXTURE: &str =
+    include_str!("fixtures/exact-regressions/Anontalkpagetext_shortened-manually.xml");
+
+/// Builds a dump with `num_pages` copies of the fixture page, split into the header, one part
+/// per page and the footer like the streams of a Wikimedia multistream dump.
+fn build_dump_parts(num_pages: usize) -> Vec<String> {
+    let page_start = FIXTURE.find("  <page>").unwrap();
+    let page_end = FIXTURE.find("</page>").unwrap() + "</page>\n".len();
+    let (header, page) = (&FIXTURE[..page_start], &FIXTURE[page_start..page_end]);
+
+    let mut parts = vec![header.to_owned()];
+    for i in 0..num_pages {
+        parts.push(page.replace(
+            "MediaWiki:Anontalkpagetext",
+            &format!("MediaWiki:Anontalkpagetext {i}"),
+        ));
+    }
+    parts.push("</mediawiki>\n".to_owned());
+    parts
+}
+
+fn parse_all<R: io::BufRead>(mut parser: DumpParser<R>) -> Vec<Page> {
+    std::iter::from_fn(|| parser.parse_page().unwrap()).collect()
+}
+
+/// Compresses every part separately and concatenates the results.
+fn compress_parts(compression: Compression, parts: &[String]) -> Vec<u8> {
+    let mut output = Vec::new();
+    for part in parts {
+        let part = part.as_bytes();
+        let compressed = match compression {
+            Compression::None => part.to_vec(),
+            Compression::Bzip2 => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::fast());
+                encoder.write_all(part).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+                encoder.write_all(part).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Zstd => zstd::encode_all(part, 1).unwrap(),
+            Compression::Xz => {
+                let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 1);
+                encoder.write_all(part).unwrap();
+                encoder.finish().unwrap()
+            }
+            _ => unreachable!("not a stream format"),
+        };
+        output.extend(compressed);
+    }
+    output
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name)
+}
+
+const STREAM_FORMATS: [Compression; 5] = [
+    Compression::None,
+    Compression::Bzip2,
+    Compression::Gzip,
+    Compression::Zstd,
+    Compression::Xz,
+];
+
+#[test]
+fn detects_compression_from_magic_bytes() {
+    let parts = build_dump_parts(0);
+    for compression in STREAM_FORMATS {
+        let data = compress_parts(compression, &parts);
+        assert_eq!(Compression::detect(&data), compression);
+    }
+    assert_eq!(
+        Compression::detect(b"7z\xbc\xaf\x27\x1c\x00\x04"),
+        Compression::SevenZip
+    );
+    assert_eq!(Compression::detect(b"BZh"), Compression::None);
+    assert_eq!(Compression::detect(b""), Compression::None);
+}
+
+#[test]
+fn open_ignores_file_extension() {
+    let parts = build_dump_parts(5);
+    let expected = parse_all(DumpParser::new(parts.concat().as_bytes()).unwrap());
+    assert_eq!(expected.len(), 5);
+
+    for compression in STREAM_FORMATS {
+        // every part is a separate stream, like in a multistream dump
+        let path = temp_path(&format!("compression-{compression:?}.xml.gz"));
+        fs::write(&path, compress_parts(compression, &parts)).unwrap();
+
+        let parser = DumpParser::open(&path).unwrap();
+        assert_eq!(parse_all(parser), expected, "{compression:?}");
+    }
+}
+
+#[test]
+fn open_reads_first_file_of_7z_archive() {
+    let parts = build_dump_parts(5);
+    let expected = parse_all(DumpParser::new(parts.concat().as_bytes()).unwrap());
+
+    let xml_path = temp_path("compression-7z-source.xml");
+    fs::write(&xml_path, parts.concat()).unwrap();
+    let path = temp_path("compression-7z.xml.bz2");
+    sevenz_rust::compress_to_path(&xml_path, &path).unwrap();
+
+    let parser = DumpParser::open(&path).unwrap();
+    assert_eq!(parse_all(parser), expected);
+
+    // a 7z archive can't be read from a stream
+    let error = DumpParser::from_reader_autodetect(BufReader::new(fs::File::open(&path).unwrap()))
+        .unwrap_err();
+    assert!(
+        matches!(
+            error.kind(),
+            ParsingErrorKind::XmlError(quick_xml::Error::Io(e))
+                if e.kind() == io::ErrorKind::Unsupported
+        ),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn from_reader_autodetect_handles_short_reads() {
+    let parts = build_dump_parts(3);
+    let expected = parse_all(DumpParser::new(parts.concat().as_bytes()).unwrap());
+
+    for compression in STREAM_FORMATS {
+        let data = compress_parts(compression, &parts);
+        // a tiny buffer splits the magic bytes across reads
+        let reader = BufReader::with_capacity(2, io::Cursor::new(data));
+        let parser = DumpParser::from_reader_autodetect(reader).unwrap();
+        assert_eq!(parse_all(parser), expected, "{compression:?}");
+    }
+}
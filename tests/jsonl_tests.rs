@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: MPL-2.0
+#![cfg(feature = "serde")]
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+use wikiwho::algorithm::PageAnalysis;
+use wikiwho::dump_parser::{Comment, Text};
+use wikiwho::output::jsonl::{
+    read_jsonl, read_jsonl_str, PageOutput, RevisionFields, RevisionSelection,
+};
+
+mod common;
+
+use common::parse_example;
+
+fn write_jsonl(records: &[PageOutput]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for record in records {
+        serde_json::to_writer(&mut out, record).unwrap();
+        out.push(b'\n');
+    }
+    out
+}
+
+#[test]
+fn read_written_records() {
+    let (page, analysis) = parse_example();
+    let latest = PageOutput::new(&page, &analysis, &RevisionSelection::Latest);
+    let selected = PageOutput::new(
+        &page,
+        &analysis,
+        &RevisionSelection::Ids(BTreeSet::from([101, 103])),
+    );
+    let mut jsonl = write_jsonl(&[latest, selected]);
+    jsonl.extend(b"\n");
+
+    let records: Vec<_> = read_jsonl(jsonl.as_slice())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(records.len(), 2);
+
+    let latest = &records[0];
+    assert_eq!(latest.article_title, "Example");
+    assert_eq!(latest.namespace, 0);
+    let ids: Vec<_> = latest.revisions.iter().map(|rev| rev.id).collect();
+    assert_eq!(ids, [100, 101, 102, 103]);
+    assert_eq!(latest.revisions[1].editor, "0|192.0.2.1");
+    assert_eq!(latest.revisions[0].timestamp, page.revisions[0].timestamp);
+    assert!(latest.revision_tokens.is_none());
+    let expected: Vec<_> =
+        wikiwho::utils::iterate_revision_tokens(&analysis, &analysis.current_revision)
+            .map(|word| word.value.to_string())
+            .collect();
+    let values: Vec<_> = latest.all_tokens.iter().map(|t| t.value.clone()).collect();
+    assert_eq!(values, expected);
+    let brown = &latest.all_tokens[2];
+    assert_eq!((brown.o_rev_id, brown.editor.as_str()), (100, "11"));
+    assert_eq!(
+        (&brown.inbound[..], &brown.outbound[..]),
+        (&[102][..], &[101][..])
+    );
+
+    let selected = records[1].revision_tokens.as_ref().unwrap();
+    let ids: Vec<_> = selected.iter().map(|rev| rev.id).collect();
+    assert_eq!(ids, [101, 103]);
+    for rev in selected {
+        for token_id in &rev.tokens {
+            assert!(records[1]
+                .all_tokens
+                .iter()
+                .any(|token| token.token_id == *token_id));
+        }
+    }
+
+    // owned records write the same JSON structure back
+    let rewritten: serde_json::Value = serde_json::to_value(&records[0]).unwrap();
+    let original: serde_json::Value =
+        serde_json::from_slice(jsonl.split(|&b| b == b'\n').next().unwrap()).unwrap();
+    assert_eq!(rewritten["all_tokens"], original["all_tokens"]);
+    assert_eq!(
+        rewritten["revisions"][1]["editor"],
+        original["revisions"][1]["editor"]
+    );
+}
+
+#[test]
+fn owned_and_borrowed_records_agree() {
+    let (mut page, analysis) = parse_example();
+    page.title = "\"Example\"".into();
+    let records = [
+        PageOutput::with_revision_fields(
+            &page,
+            &analysis,
+            &RevisionSelection::All,
+            RevisionFields::ALL,
+        ),
+        PageOutput::new(&page, &analysis, &RevisionSelection::Latest),
+    ];
+    let jsonl = String::from_utf8(write_jsonl(&records)).unwrap();
+
+    let owned: Vec<_> = read_jsonl(jsonl.as_bytes())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let borrowed: Vec<_> = read_jsonl_str(&jsonl).collect::<Result<_, _>>().unwrap();
+    assert_eq!(borrowed.len(), 2);
+    for (owned, borrowed) in owned.iter().zip(&borrowed) {
+        assert_eq!(
+            serde_json::to_value(owned).unwrap(),
+            serde_json::to_value(borrowed).unwrap()
+        );
+    }
+
+    // escaped strings can't be borrowed
+    assert_eq!(borrowed[0].article_title, "\"Example\"");
+    assert!(matches!(borrowed[0].article_title, Cow::Owned(_)));
+    let token = &borrowed[0].all_tokens[0];
+    assert!(matches!(token.value, Cow::Borrowed(_)));
+    assert!(matches!(token.editor, Cow::Borrowed(_)));
+    assert_eq!(token.value, owned[0].all_tokens[0].value);
+}
+
+#[test]
+fn stops_after_error() {
+    let (page, analysis) = parse_example();
+    let record = PageOutput::new(&page, &analysis, &RevisionSelection::Latest);
+    let mut jsonl = b"{\"article_title\": 1}\n".to_vec();
+    jsonl.extend(write_jsonl(&[record]));
+
+    let mut records = read_jsonl(jsonl.as_slice());
+    assert!(records.next().unwrap().is_err());
+    assert!(records.next().is_none());
+}
+
+#[test]
+fn revision_fields() {
+    let (mut page, _) = parse_example();
+    page.revisions[1].comment = Some(Comment::Normal("red".into()));
+    page.revisions[1].minor = true;
+    page.revisions[2].comment = Some(Comment::Deleted);
+    let analysis = PageAnalysis::analyse_page(&page.revisions).unwrap();
+    // texts dropped after the analysis keep their size as a stub
+    page.revisions[0].text = Text::Stub {
+        bytes: Some(26),
+        location: None,
+    };
+
+    let default = PageOutput::new(&page, &analysis, &RevisionSelection::Latest);
+    let revision = serde_json::to_value(&default.revisions[0]).unwrap();
+    assert_eq!(revision.as_object().unwrap().len(), 3);
+
+    let record = PageOutput::with_revision_fields(
+        &page,
+        &analysis,
+        &RevisionSelection::Latest,
+        RevisionFields::ALL,
+    );
+    let mut jsonl = serde_json::to_vec(&record).unwrap();
+    jsonl.push(b'\n');
+    let record = read_jsonl(jsonl.as_slice()).next().unwrap().unwrap();
+
+    let fields: Vec<_> = record
+        .revisions
+        .iter()
+        .map(|rev| {
+            (
+                rev.comment.as_deref(),
+                rev.minor,
+                rev.size,
+                rev.token_count,
+                rev.spam,
+            )
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            (None, Some(false), Some(26), Some(6), Some(false)),
+            (Some("red"), Some(true), Some(24), Some(6), Some(false)),
+            (None, Some(false), Some(26), Some(6), Some(false)),
+            (None, Some(false), Some(44), Some(10), Some(false)),
+        ]
+    );
+    let changes: Vec<_> = record
+        .revisions
+        .iter()
+        .map(|rev| (rev.added, rev.removed, rev.reintroduced))
+        .collect();
+    // "brown" is replaced by "red" in 101 and reintroduced in 102
+    assert_eq!(
+        changes,
+        [
+            (Some(6), Some(0), Some(0)),
+            (Some(1), Some(1), Some(0)),
+            (Some(0), Some(1), Some(1)),
+            (Some(4), Some(0), Some(0)),
+        ]
+    );
+    let added: usize = record.revisions.iter().filter_map(|rev| rev.added).sum();
+    assert_eq!(added, analysis.words.len());
+}
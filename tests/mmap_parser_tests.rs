@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: MPL-2.0
+#![cfg(feature = "mmap")]
+
+use std::{fs, sync::Arc};
+
+use wikiwho::{
+    algorithm::PageAnalysis,
+    dump_parser::{
+        DiagnosticKind, DumpParser, MmapDumpParser, Page, ParserOptions, ParsingErrorKind, Text,
+    },
+    utils::iterate_revision_tokens,
+};
+
+const FIXTURE: &str =
+    include_str!("fixtures/exact-regressions/Anontalkpagetext_shortened-manually.xml");
+
+/// Builds a dump with `num_pages` copies of the fixture page, each with a distinct title.
+fn build_dump(num_pages: usize) -> String {
+    let page_start = FIXTURE.find("  <page>").unwrap();
+    let page_end = FIXTURE.find("</page>").unwrap() + "</page>\n".len();
+    let (header, page) = (&FIXTURE[..page_start], &FIXTURE[page_start..page_end]);
+
+    let mut dump = String::from(header);
+    for i in 0..num_pages {
+        dump.push_str(&page.replace(
+            "MediaWiki:Anontalkpagetext",
+            &format!("MediaWiki:Anontalkpagetext {i}"),
+        ));
+    }
+    dump.push_str("</mediawiki>\n");
+    dump
+}
+
+fn parse_all(mut next: impl FnMut() -> Option<Page>) -> Vec<Page> {
+    std::iter::from_fn(&mut next).collect()
+}
+
+#[test]
+fn mmap_parser_matches_buffered_parser() {
+    let dump = build_dump(5);
+    let mut buffered = DumpParser::new(dump.as_bytes()).unwrap();
+    let expected = parse_all(|| buffered.parse_page().unwrap());
+
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("mmap_parser_dump.xml");
+    fs::write(&path, &dump).unwrap();
+    let mut parser = MmapDumpParser::open(&path).unwrap();
+    assert_eq!(parser.site_info(), buffered.site_info());
+    let actual = parse_all(|| parser.parse_page().unwrap());
+    assert_eq!(actual, expected);
+    assert_eq!(parser.bytes_consumed(), buffered.bytes_consumed());
+    assert!(parser.parse_page().unwrap().is_none());
+}
+
+/// Builds a dump with one page of plain revision texts and one text with entities.
+fn build_plain_dump() -> String {
+    let page_start = FIXTURE.find("  <page>").unwrap();
+    let revision = |id: i32, text: &str| {
+        format!(
+            "    <revision>\n      <id>{id}</id>\n      <timestamp>2020-01-0{id}T00:00:00Z</timestamp>\n      \
+             <contributor>\n        <username>Editor</username>\n        <id>1</id>\n      \
+             </contributor>\n      <text xml:space=\"preserve\">{text}</text>\n    </revision>\n"
+        )
+    };
+    let mut dump = String::from(&FIXTURE[..page_start]);
+    dump.push_str("  <page>\n    <title>Plain</title>\n    <ns>0</ns>\n    <id>1</id>\n");
+    dump.push_str(&revision(1, "Some plain text."));
+    dump.push_str(&revision(2, "Some plain text, and more."));
+    dump.push_str(&revision(3, "Some &lt;b&gt;bold&lt;/b&gt; text, and more."));
+    dump.push_str("  </page>\n</mediawiki>\n");
+    dump
+}
+
+#[test]
+fn mmap_parser_shares_text_without_entities() {
+    let dump = build_plain_dump();
+    let mut parser =
+        MmapDumpParser::from_buffer(Arc::new(dump.clone()), ParserOptions::default()).unwrap();
+    let page = parser.parse_page().unwrap().unwrap();
+    drop(parser);
+
+    // shared texts outlive the parser and compare equal to owned texts
+    let mut buffered = DumpParser::new(dump.as_bytes()).unwrap();
+    let expected = buffered.parse_page().unwrap().unwrap();
+    assert_eq!(page, expected);
+
+    let shared: Vec<_> = page
+        .revisions
+        .iter()
+        .map(|r| matches!(r.text, Text::Shared(_)))
+        .collect();
+    assert_eq!(shared, [true, true, false]);
+    assert_eq!(
+        page.revisions[2].text.as_str(),
+        "Some <b>bold</b> text, and more."
+    );
+
+    // the analysis doesn't care where the text came from
+    let tokens = |analysis: &PageAnalysis| {
+        iterate_revision_tokens(analysis, &analysis.current_revision)
+            .map(|token| (token.value.to_string(), analysis[token].origin_revision.id))
+            .collect::<Vec<_>>()
+    };
+    let analysis = PageAnalysis::analyse_page(&page.revisions).unwrap();
+    let expected = PageAnalysis::analyse_page(&expected.revisions).unwrap();
+    assert_eq!(tokens(&analysis), tokens(&expected));
+}
+
+#[test]
+fn mmap_parser_reports_errors_like_buffered_parser() {
+    let dump = build_dump(3).replacen(
+        "<timestamp>2007-05-23T15:43:05Z</timestamp>",
+        "<timestamp>yesterday</timestamp>",
+        2,
+    );
+
+    let mut buffered = DumpParser::new(dump.as_bytes()).unwrap();
+    let mut parser =
+        MmapDumpParser::from_buffer(Arc::new(dump.clone()), ParserOptions::default()).unwrap();
+    while let Some(page) = parser.parse_page().unwrap() {
+        assert_eq!(Some(page), buffered.parse_page().unwrap());
+        assert_eq!(parser.diagnostics(), buffered.diagnostics());
+    }
+    assert!(buffered.parse_page().unwrap().is_none());
+
+    // in strict mode the broken pages are skipped
+    let options = ParserOptions::new().strict(true);
+    let mut parser = MmapDumpParser::from_buffer(Arc::new(dump), options).unwrap();
+    for _ in 0..2 {
+        let error = parser.parse_page().unwrap_err();
+        assert!(error.is_recoverable());
+        assert!(matches!(
+            error.kind(),
+            ParsingErrorKind::Malformed(DiagnosticKind::InvalidTimestamp(_))
+        ));
+    }
+    let page = parser.parse_page().unwrap().unwrap();
+    assert_eq!(page.title, "Anontalkpagetext 2");
+    assert!(parser.parse_page().unwrap().is_none());
+
+    // truncated input
+    let mut dump = build_dump(2);
+    dump.truncate(dump.rfind("<revision>").unwrap());
+    let mut parser = MmapDumpParser::from_buffer(Arc::new(dump), ParserOptions::default()).unwrap();
+    assert!(parser.parse_page().unwrap().is_some());
+    let error = parser.parse_page().unwrap_err();
+    assert!(matches!(error.kind(), ParsingErrorKind::Eof));
+    assert!(parser.parse_page().unwrap().is_none());
+}
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: MPL-2.0
+#![cfg(feature = "arrow")]
+
+use std::{fs::File, path::Path};
+
+use arrow_array::{
+    cast::AsArray,
+    types::{Int32Type, TimestampMillisecondType, UInt64Type},
+    RecordBatch,
+};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+use wikiwho::output::parquet::{ParquetWriter, REVISIONS_FILE, TOKENS_FILE};
+
+mod common;
+
+use common::parse_example;
+
+/// Reads a table as a single batch, together with its number of row groups.
+fn read_table(path: &Path) -> (RecordBatch, usize) {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap()).unwrap();
+    let row_groups = builder.metadata().num_row_groups();
+    let mut reader = builder.with_batch_size(usize::MAX).build().unwrap();
+    (reader.next().unwrap().unwrap(), row_groups)
+}
+
+#[test]
+fn tables_round_trip() {
+    let (page, analysis) = parse_example();
+    let dir = std::env::temp_dir().join(format!("wikiwho_parquet_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    // one row group of revisions per page
+    let mut writer = ParquetWriter::with_row_group_size(
+        File::create(dir.join(TOKENS_FILE)).unwrap(),
+        File::create(dir.join(REVISIONS_FILE)).unwrap(),
+        analysis.ordered_revisions.len(),
+    )
+    .unwrap();
+    writer.write_page(&page, &analysis).unwrap();
+    writer.write_page(&page, &analysis).unwrap();
+    writer.finish().unwrap();
+
+    let (revisions, row_groups) = read_table(&dir.join(REVISIONS_FILE));
+    assert_eq!(row_groups, 2);
+    assert_eq!(revisions.num_rows(), 8);
+    let rev_ids: Vec<_> = revisions["rev_id"].as_primitive::<Int32Type>().values()[..4].to_vec();
+    assert_eq!(rev_ids, [100, 101, 102, 103]);
+    let editors: Vec<_> = revisions["editor"]
+        .as_string::<i32>()
+        .iter()
+        .take(4)
+        .collect();
+    assert_eq!(
+        editors,
+        [Some("11"), Some("0|192.0.2.1"), Some("11"), Some("12")]
+    );
+    assert_eq!(
+        revisions["timestamp"]
+            .as_primitive::<TimestampMillisecondType>()
+            .value(0),
+        page.revisions[0].timestamp.timestamp_millis()
+    );
+    assert_eq!(
+        revisions["page_id"].as_primitive::<Int32Type>().value(0),
+        4242
+    );
+
+    let (tokens, _) = read_table(&dir.join(TOKENS_FILE));
+    let expected =
+        wikiwho::utils::iterate_revision_tokens(&analysis, &analysis.current_revision).count();
+    assert_eq!(tokens.num_rows(), 2 * expected);
+    let values: Vec<_> = tokens["str"].as_string::<i32>().iter().take(3).collect();
+    assert_eq!(values, [Some("the"), Some("quick"), Some("brown")]);
+    assert_eq!(tokens["token_id"].as_primitive::<UInt64Type>().value(2), 2);
+    assert_eq!(tokens["o_rev_id"].as_primitive::<Int32Type>().value(2), 100);
+    let inbound = tokens["in"].as_list::<i32>().value(2);
+    let outbound = tokens["out"].as_list::<i32>().value(2);
+    assert_eq!(inbound.as_primitive::<Int32Type>().values(), &[102]);
+    assert_eq!(outbound.as_primitive::<Int32Type>().values(), &[101]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
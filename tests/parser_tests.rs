@@ -1,7 +1,820 @@
 // SPDX-License-Identifier: MPL-2.0
 //! Parser integration tests.
 //!
-//! Intentionally empty for now — implementing these is tracked in
-//! <https://github.com/Schuwi/wikiwho_rs/issues/6> (e.g. parsing the full bundled
-//! Wiktionary dump in `strict` mode). A plain `cargo test` reports this binary as
-//! `running 0 tests`; that is expected until the suite is populated or removed there.
+//! These run on small XML documents built from the tracked fixtures in `tests/fixtures`.
+//! Parsing the full bundled Wiktionary dump (e.g. in strict mode) is still tracked in
+//! <https://github.com/Schuwi/wikiwho_rs/issues/6>.
+use std::{
+    fs, io,
+    io::Cursor,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use wikiwho::algorithm::{AnalysisError, PageAnalysis, PageAnalysisOptions};
+use wikiwho::dump_parser::{
+    stats::DumpStats, Case, Comment, Contributor, DiagnosticKind, DumpParser, DumpWriter,
+    Namespace, Page, ParallelDumpReader, ParserOptions, ParsingError, ParsingErrorKind, Revision,
+    RevisionAnomaly, RevisionTextProvider, SchemaVersion, Sha1Hash, SiteInfo, Slot, Text,
+    MAIN_SLOT,
+};
+use wikiwho::utils::iterate_revision_tokens;
+
+const FIXTURE: &str =
+    include_str!("fixtures/exact-regressions/Anontalkpagetext_shortened-manually.xml");
+
+/// Splits the fixture into the part before the first `<page>` and the page itself.
+fn fixture_header_and_page() -> (&'static str, &'static str) {
+    let page_start = FIXTURE.find("  <page>").unwrap();
+    let page_end = FIXTURE.find("</page>").unwrap() + "</page>\n".len();
+    (&FIXTURE[..page_start], &FIXTURE[page_start..page_end])
+}
+
+/// Builds a dump with `num_pages` copies of the fixture page, each with a distinct title.
+fn build_dump(num_pages: usize) -> String {
+    let (header, page) = fixture_header_and_page();
+    let mut dump = String::from(header);
+    for i in 0..num_pages {
+        dump.push_str(&page.replace(
+            "MediaWiki:Anontalkpagetext",
+            &format!("MediaWiki:Anontalkpagetext {i}"),
+        ));
+    }
+    dump.push_str("</mediawiki>\n");
+    dump
+}
+
+fn parse_sequential(xml: &str) -> Result<Vec<Page>, ParsingError> {
+    let mut parser = DumpParser::new(xml.as_bytes())?;
+    let mut pages = Vec::new();
+    while let Some(page) = parser.parse_page()? {
+        pages.push(page);
+    }
+    Ok(pages)
+}
+
+#[test]
+fn parallel_reader_matches_sequential_parser() {
+    // large enough to be split into several chunks
+    let dump = build_dump(3000);
+
+    let expected = parse_sequential(&dump).unwrap();
+    assert_eq!(expected.len(), 3000);
+
+    let reader = ParallelDumpReader::new(Cursor::new(dump.into_bytes()), 4).unwrap();
+    assert_eq!(reader.site_info().dbname, "dewiktionary");
+    let actual = reader.collect::<Result<Vec<_>, _>>().unwrap();
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn parallel_reader_reports_truncated_page() {
+    let mut dump = build_dump(2);
+    dump.truncate(dump.rfind("<revision>").unwrap());
+
+    let mut reader = ParallelDumpReader::new(Cursor::new(dump.into_bytes()), 2).unwrap();
+    assert!(reader.parse_page().unwrap().is_some());
+    let error = reader.parse_page().unwrap_err();
+    assert!(matches!(error.kind(), ParsingErrorKind::Eof));
+    assert_eq!(
+        error.location().page_title.as_deref(),
+        Some("Anontalkpagetext 1")
+    );
+    assert!(reader.parse_page().unwrap().is_none());
+}
+
+#[test]
+fn diagnostics_carry_location() {
+    let dump = build_dump(2).replace(
+        "<timestamp>2007-05-23T15:43:05Z</timestamp>",
+        "<timestamp>yesterday</timestamp>",
+    );
+
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let options = ParserOptions::new().on_diagnostic({
+        let reported = reported.clone();
+        move |diagnostic| reported.lock().unwrap().push(diagnostic.clone())
+    });
+    let mut parser = DumpParser::with_options(dump.as_bytes(), options).unwrap();
+
+    let page = parser.parse_page().unwrap().unwrap();
+    // the revision with the invalid timestamp is dropped
+    assert_eq!(page.revisions.len(), 1);
+
+    let diagnostics = parser.diagnostics().to_vec();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(
+        diagnostics[0].kind,
+        DiagnosticKind::InvalidTimestamp("yesterday".into())
+    );
+    assert_eq!(
+        diagnostics[1].kind,
+        DiagnosticKind::MissingField("timestamp")
+    );
+    for diagnostic in &diagnostics {
+        assert_eq!(
+            diagnostic.location.page_title.as_deref(),
+            Some("Anontalkpagetext 0")
+        );
+        assert_eq!(diagnostic.location.revision_id, Some(552578));
+    }
+    let position = diagnostics[0].location.position as usize;
+    assert!(dump[..position].ends_with("<timestamp>yesterday"));
+
+    parser.parse_page().unwrap().unwrap();
+    assert_eq!(parser.diagnostics().len(), 2);
+    assert_eq!(reported.lock().unwrap().len(), 4);
+
+    // the parallel reader reports the same locations
+    let mut reader = ParallelDumpReader::new(Cursor::new(dump.clone().into_bytes()), 2).unwrap();
+    reader.parse_page().unwrap().unwrap();
+    assert_eq!(reader.diagnostics(), diagnostics.as_slice());
+}
+
+/// Builds a dump of three pages where the middle one is modified by `break_page`.
+fn build_dump_with_broken_page(break_page: impl Fn(&str) -> String) -> String {
+    let (header, page) = fixture_header_and_page();
+    let mut dump = String::from(header);
+    for i in 0..3 {
+        let page = page.replace(
+            "MediaWiki:Anontalkpagetext",
+            &format!("MediaWiki:Anontalkpagetext {i}"),
+        );
+        if i == 1 {
+            dump.push_str(&break_page(&page));
+        } else {
+            dump.push_str(&page);
+        }
+    }
+    dump.push_str("</mediawiki>\n");
+    dump
+}
+
+fn duplicate_revision_id(page: &str) -> String {
+    page.replace("<id>552578</id>", "<id>401685</id>")
+}
+
+#[test]
+fn strict_mode_reports_duplicate_revision_ids() {
+    let dump = build_dump_with_broken_page(duplicate_revision_id);
+
+    // lenient: the revision is kept and a diagnostic is reported
+    let mut parser = DumpParser::new(dump.as_bytes()).unwrap();
+    parser.parse_page().unwrap().unwrap();
+    let page = parser.parse_page().unwrap().unwrap();
+    assert_eq!(page.revisions.len(), 2);
+    assert_eq!(
+        parser.diagnostics()[0].kind,
+        DiagnosticKind::DuplicateRevisionId(401685)
+    );
+
+    // strict: the page is an error, parsing continues with the next page
+    let options = ParserOptions::new().strict(true);
+    let mut parser = DumpParser::with_options(dump.as_bytes(), options.clone()).unwrap();
+    let mut reader =
+        ParallelDumpReader::with_options(Cursor::new(dump.clone().into_bytes()), 2, options)
+            .unwrap();
+    for next_page in [&mut || parser.parse_page(), &mut || reader.parse_page()]
+        as [&mut dyn FnMut() -> Result<Option<Page>, ParsingError>; 2]
+    {
+        assert_eq!(next_page().unwrap().unwrap().title, "Anontalkpagetext 0");
+        let error = next_page().unwrap_err();
+        assert!(error.is_recoverable());
+        assert!(matches!(
+            error.kind(),
+            ParsingErrorKind::Malformed(DiagnosticKind::DuplicateRevisionId(401685))
+        ));
+        assert_eq!(
+            error.location().page_title.as_deref(),
+            Some("Anontalkpagetext 1")
+        );
+        assert_eq!(next_page().unwrap().unwrap().title, "Anontalkpagetext 2");
+        assert!(next_page().unwrap().is_none());
+    }
+}
+
+#[test]
+fn normalize_revisions_restores_chronological_order() {
+    let page = parse_sequential(&build_dump(1)).unwrap().remove(0);
+    let [first, second] = [page.revisions[0].clone(), page.revisions[1].clone()];
+    let tokens = |analysis: &PageAnalysis| {
+        iterate_revision_tokens(analysis, &analysis.current_revision)
+            .map(|token| (token.value.to_string(), analysis[token].origin_revision.id))
+            .collect::<Vec<_>>()
+    };
+    let expected = PageAnalysis::analyse_page(&page.revisions).unwrap();
+
+    // two overlapping parts glued together in the wrong order
+    let mut glued = Page {
+        revisions: vec![second.clone(), first.clone(), second.clone()],
+        ..page.clone()
+    };
+    assert_eq!(
+        PageAnalysis::analyse_page(&glued.revisions).err(),
+        Some(AnalysisError::RevisionsOutOfOrder {
+            revision_id: first.id,
+            previous_revision_id: second.id
+        })
+    );
+    assert_eq!(
+        glued.normalize_revisions(),
+        [
+            RevisionAnomaly::OutOfOrder(first.id),
+            RevisionAnomaly::Duplicate(second.id)
+        ]
+    );
+    assert_eq!(glued, page);
+    assert!(glued.normalize_revisions().is_empty());
+    let analysis = PageAnalysis::analyse_page(&glued.revisions).unwrap();
+    assert_eq!(tokens(&analysis), tokens(&expected));
+
+    // of revisions with the same id and different content, the last one is kept
+    let mut conflicting = page.clone();
+    let edited = Revision {
+        minor: !second.minor,
+        ..second.clone()
+    };
+    conflicting.revisions.push(edited.clone());
+    assert_eq!(
+        PageAnalysis::analyse_page(&conflicting.revisions).err(),
+        Some(AnalysisError::DuplicateRevisionId(second.id))
+    );
+    assert_eq!(
+        conflicting.normalize_revisions(),
+        [RevisionAnomaly::ConflictingId(second.id)]
+    );
+    assert_eq!(conflicting.revisions, [first.clone(), edited]);
+    let analysis = PageAnalysis::analyse_page(&conflicting.revisions).unwrap();
+    assert_eq!(tokens(&analysis), tokens(&expected));
+
+    // in dumps (ordered by id) timestamps can go backwards, e.g. after a history merge
+    let mut merged = page.clone();
+    merged.revisions[1].timestamp = first.timestamp - chrono::TimeDelta::days(1);
+    assert!(PageAnalysis::analyse_page(&merged.revisions).is_ok());
+    assert_eq!(
+        merged.normalize_revisions(),
+        [RevisionAnomaly::OutOfOrder(second.id)]
+    );
+    assert_eq!(merged.revisions[0].id, second.id);
+}
+
+#[test]
+fn strict_mode_rejects_non_utf8_tags() {
+    let dump = build_dump_with_broken_page(|page| {
+        page.replace("<minor />", "<minor /><invalid\u{1}>x</invalid\u{1}>")
+    })
+    .into_bytes();
+    // replace the marker with a byte that is not valid UTF-8
+    let dump = dump
+        .into_iter()
+        .map(|b| if b == 1 { 0xff } else { b })
+        .collect::<Vec<_>>();
+
+    let mut parser = DumpParser::new(dump.as_slice()).unwrap();
+    parser.parse_page().unwrap().unwrap();
+    parser.parse_page().unwrap().unwrap();
+    assert!(matches!(
+        parser.diagnostics()[0].kind,
+        DiagnosticKind::NonUtf8Tag(_)
+    ));
+
+    let mut parser =
+        DumpParser::with_options(dump.as_slice(), ParserOptions::new().strict(true)).unwrap();
+    parser.parse_page().unwrap().unwrap();
+    let error = parser.parse_page().unwrap_err();
+    assert!(matches!(
+        error.kind(),
+        ParsingErrorKind::Malformed(DiagnosticKind::NonUtf8Tag(_))
+    ));
+    assert_eq!(
+        parser.parse_page().unwrap().unwrap().title,
+        "Anontalkpagetext 2"
+    );
+}
+
+#[test]
+fn strict_mode_recovers_at_mismatched_page_end() {
+    // the closing page tag closes the unclosed revision instead
+    let dump = build_dump_with_broken_page(|page| {
+        let last_revision_end = page.rfind("</revision>").unwrap();
+        page[..last_revision_end].to_string() + &page[last_revision_end + "</revision>".len()..]
+    });
+
+    let mut parser =
+        DumpParser::with_options(dump.as_bytes(), ParserOptions::new().strict(true)).unwrap();
+    parser.parse_page().unwrap().unwrap();
+    let error = parser.parse_page().unwrap_err();
+    assert!(matches!(
+        error.kind(),
+        ParsingErrorKind::Malformed(DiagnosticKind::MismatchedTags { .. })
+    ));
+    assert_eq!(
+        parser.parse_page().unwrap().unwrap().title,
+        "Anontalkpagetext 2"
+    );
+    assert!(parser.parse_page().unwrap().is_none());
+}
+
+fn write_dump(site_info: &SiteInfo, pages: &[Page]) -> String {
+    let mut writer = DumpWriter::new(Vec::new(), site_info).unwrap();
+    for page in pages {
+        writer.write_page(page).unwrap();
+    }
+    String::from_utf8(writer.finish().unwrap()).unwrap()
+}
+
+#[test]
+fn parser_resolves_entity_references() {
+    let pages = parse_sequential(FIXTURE).unwrap();
+    let text = pages[0].revisions[0].text.as_str();
+    assert!(text.starts_with("<br />\n----\n''Dies ist die Diskussionsseite"));
+    assert!(text.contains(r#"<span class="plainlinks">"#));
+    assert!(text.contains("whois?lg=DE&query={{PAGENAME}}"));
+}
+
+#[test]
+fn dump_writer_round_trips_fixture() {
+    let parser = DumpParser::new(FIXTURE.as_bytes()).unwrap();
+    let site_info = parser.site_info().clone();
+    let pages = parse_sequential(FIXTURE).unwrap();
+
+    let written = write_dump(&site_info, &pages);
+    assert!(written.contains("<title>MediaWiki:Anontalkpagetext</title>"));
+
+    let parser = DumpParser::new(written.as_bytes()).unwrap();
+    assert_eq!(parser.site_info(), &site_info);
+    assert_eq!(parse_sequential(&written).unwrap(), pages);
+}
+
+#[test]
+fn dump_writer_round_trips_edge_cases() {
+    let site_info = DumpParser::new(FIXTURE.as_bytes())
+        .unwrap()
+        .site_info()
+        .clone();
+    let mut page = parse_sequential(FIXTURE).unwrap().remove(0);
+    page.title = "A & B <c> \"d\"".into();
+
+    let mut revision = page.revisions[1].clone();
+    revision.id += 1;
+    revision.contributor = Contributor {
+        username: "2001:db8::1".into(),
+        id: None,
+        deleted: false,
+    };
+    revision.comment = Some(Comment::Normal("revert <b>vandalism</b> & 'stuff'".into()));
+    revision.minor = false;
+    revision.sha1 = None;
+    revision.text = Text::Deleted;
+    page.revisions.push(revision.clone());
+
+    revision.id += 1;
+    revision.contributor.username = "Unregistered name".into();
+    revision.text = Text::Normal(String::new());
+    page.revisions.push(revision.clone());
+
+    revision.id += 1;
+    revision.text = Text::Normal("tab\there, ümlaut, ]]> and &amp; literally".into());
+    page.revisions.push(revision.clone());
+
+    revision.id += 1;
+    revision.contributor = Contributor::deleted();
+    revision.comment = Some(Comment::Deleted);
+    page.revisions.push(revision);
+
+    let mut main_page = page.clone();
+    main_page.namespace = 0;
+    main_page.title = "Main page".into();
+
+    let pages = vec![page, main_page];
+    let written = write_dump(&site_info, &pages);
+    assert!(written.contains("<ip>2001:db8::1</ip>"));
+    assert!(written.contains("<title>Main page</title>"));
+    assert_eq!(parse_sequential(&written).unwrap(), pages);
+}
+
+#[test]
+fn parser_keeps_revisions_with_deleted_contributor_and_comment() {
+    let (header, page) = fixture_header_and_page();
+    let page = page
+        .replacen(
+            "<contributor>\n        <username>Pajz</username>\n        <id>1390</id>\n      </contributor>",
+            r#"<contributor deleted="deleted" />"#,
+            1,
+        )
+        .replace("<minor />", r#"<minor />\n      <comment deleted="deleted" />"#);
+    let dump = format!("{header}{page}</mediawiki>\n");
+
+    let mut parser = DumpParser::new(dump.as_bytes()).unwrap();
+    let page = parser.parse_page().unwrap().unwrap();
+    assert!(parser.diagnostics().is_empty());
+
+    let [first, second] = page.revisions.as_slice() else {
+        panic!("expected two revisions, got {:?}", page.revisions);
+    };
+    assert_eq!(first.contributor, Contributor::deleted());
+    assert_eq!(first.comment, None);
+    assert!(!second.contributor.deleted);
+    assert_eq!(second.comment, Some(Comment::Deleted));
+    assert_eq!(second.comment.as_ref().unwrap().as_visible(), None);
+}
+
+const MEDIAINFO: [&str; 2] = [
+    r#"{"labels":{"en":"sunset"}}"#,
+    r#"{"labels":{"en":"sunset over the sea"}}"#,
+];
+
+/// The fixture with a `mediainfo` slot of [`MEDIAINFO`] added to both revisions.
+fn mcr_dump() -> String {
+    let (header, page) = fixture_header_and_page();
+    let mediainfo = MEDIAINFO;
+    let mut revisions = page.split("<sha1>");
+    let mut page = revisions.next().unwrap().to_string();
+    for (content, rest) in mediainfo.iter().zip(revisions) {
+        page.push_str("<sha1>");
+        let (sha1, rest) = rest.split_once('\n').unwrap();
+        page.push_str(&format!(
+            "{sha1}\n      <content>\n        <role>mediainfo</role>\n        \
+             <origin>1</origin>\n        <model>wikibase-mediainfo</model>\n        \
+             <format>application/json</format>\n        <text bytes=\"{}\" sha1=\"{}\" \
+             xml:space=\"preserve\">{}</text>\n      </content>\n{rest}",
+            content.len(),
+            std::str::from_utf8(&Sha1Hash::compute(content).0).unwrap(),
+            content.replace('"', "&quot;"),
+        ));
+    }
+    format!("{header}{page}</mediawiki>\n")
+}
+
+#[test]
+fn mcr_slots_are_parsed_written_and_analysed() {
+    let mediainfo = MEDIAINFO;
+    let dump = mcr_dump();
+
+    let pages = parse_sequential(&dump).unwrap();
+    let revision = &pages[0].revisions[1];
+    assert_eq!(revision.model.as_deref(), Some("wikitext"));
+    assert_eq!(revision.format.as_deref(), Some("text/x-wiki"));
+    assert_eq!(
+        revision.slots,
+        [Slot {
+            role: "mediainfo".into(),
+            model: Some("wikibase-mediainfo".into()),
+            format: Some("application/json".into()),
+            text: Text::Normal(mediainfo[1].into()),
+            sha1: Some(Sha1Hash::compute(mediainfo[1])),
+        }]
+    );
+    assert_eq!(revision.slot_text(MAIN_SLOT), Some(&revision.text));
+    assert_eq!(
+        revision.slot_text("mediainfo"),
+        Some(&revision.slots[0].text)
+    );
+    assert_eq!(revision.slot_text("unknown"), None);
+
+    let site_info = DumpParser::new(dump.as_bytes())
+        .unwrap()
+        .site_info()
+        .clone();
+    assert_eq!(
+        parse_sequential(&write_dump(&site_info, &pages)).unwrap(),
+        pages
+    );
+
+    let tokens = |options| {
+        let analysis =
+            PageAnalysis::analyse_page_with_options(&pages[0].revisions, options).unwrap();
+        iterate_revision_tokens(&analysis, &analysis.current_revision)
+            .map(|token| token.value.to_string())
+            .collect::<Vec<_>>()
+    };
+    let main_tokens = tokens(PageAnalysisOptions::new());
+    assert!(main_tokens.contains(&"diskussionsseite".to_string()));
+    let mediainfo_tokens = tokens(PageAnalysisOptions::new().slot("mediainfo"));
+    assert!(mediainfo_tokens.contains(&"sea".to_string()));
+    assert!(!mediainfo_tokens.contains(&"diskussionsseite".to_string()));
+}
+
+#[test]
+fn verify_sha1_skips_revisions_with_several_slots() {
+    // the hash of a revision with several slots is not the hash of its main text
+    let dump = mcr_dump().replace(
+        "<sha1>alhx7nh8t09lxgevm6bjec4uwki82jt</sha1>",
+        "<sha1>0123456789abcdefghijklmnopqrstu</sha1>",
+    );
+
+    let options = ParserOptions::new().strict(true).verify_sha1(true);
+    let mut parser = DumpParser::with_options(dump.as_bytes(), options).unwrap();
+    let page = parser.parse_page().unwrap().unwrap();
+
+    assert!(parser.diagnostics().is_empty());
+    assert_eq!(
+        page.revisions[1].sha1,
+        Some(Sha1Hash(*b"0123456789abcdefghijklmnopqrstu"))
+    );
+}
+
+#[test]
+fn analysis_hashes_the_main_text_of_revisions_with_several_slots() {
+    let long = (0..300).map(|n| format!("word{n} ")).collect::<String>();
+    let revision = |id: i32, text: &str, comment: Option<&str>| Revision {
+        id,
+        timestamp: chrono::DateTime::from_timestamp(1_600_000_000 + i64::from(id), 0).unwrap(),
+        contributor: Contributor {
+            username: format!("User{id}").into(),
+            id: Some(id),
+            deleted: false,
+        },
+        text: Text::Normal(text.into()),
+        // like in a dump, the hash of the revision combines the hashes of all slots
+        sha1: Some(Sha1Hash::compute(&format!("combined {id}"))),
+        comment: comment.map(|comment| Comment::Normal(comment.into())),
+        minor: comment.is_some(),
+        model: None,
+        format: None,
+        slots: vec![Slot {
+            role: "mediainfo".into(),
+            model: None,
+            format: None,
+            text: Text::Normal(format!("caption {id}")),
+            sha1: None,
+        }],
+    };
+    let revisions = [
+        revision(1, &long, None),
+        // removes most of the text, detected as spam
+        revision(2, "Short.", None),
+        revision(3, &long, None),
+        // the same main text as the spam revision, but another caption; as a minor edit with
+        // a comment it is only recognised as spam by the hash of its main text
+        revision(4, "Short.", Some("tidy up")),
+    ];
+
+    let analysis = PageAnalysis::analyse_page(&revisions).unwrap();
+    assert_eq!(analysis.spam_ids, [2, 4]);
+}
+
+/// A content store with one zstd compressed file per revision text.
+struct ZstdTextStore(PathBuf);
+
+impl RevisionTextProvider for ZstdTextStore {
+    type Error = io::Error;
+
+    fn revision_text(&mut self, revision: &Revision, role: &str) -> io::Result<Option<String>> {
+        let path = self.0.join(format!("{}-{role}.zst", revision.id));
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = zstd::decode_all(fs::File::open(path)?)?;
+        String::from_utf8(text)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[test]
+fn stub_dump_is_joined_with_stored_texts() {
+    let full = parse_sequential(FIXTURE).unwrap();
+    let site_info = DumpParser::new(FIXTURE.as_bytes())
+        .unwrap()
+        .site_info()
+        .clone();
+
+    let store = Path::new(env!("CARGO_TARGET_TMPDIR")).join("stub-dump-texts");
+    fs::create_dir_all(&store).unwrap();
+    let mut stubs = full.clone();
+    for revision in &mut stubs[0].revisions {
+        let stub = Text::Stub {
+            bytes: Some(revision.text.len() as u64),
+            location: Some(format!("tt:{}", revision.id).into()),
+        };
+        let text = std::mem::replace(&mut revision.text, stub);
+        fs::write(
+            store.join(format!("{}-{MAIN_SLOT}.zst", revision.id)),
+            zstd::encode_all(text.as_str().as_bytes(), 3).unwrap(),
+        )
+        .unwrap();
+    }
+    let stub_dump = write_dump(&site_info, &stubs);
+
+    // without stub mode, the texts are empty
+    let page = parse_sequential(&stub_dump).unwrap().remove(0);
+    assert!(page
+        .revisions
+        .iter()
+        .all(|r| r.text == Text::Normal(String::new())));
+
+    let options = ParserOptions::new().stub(true);
+    let mut parser = DumpParser::with_options(stub_dump.as_bytes(), options).unwrap();
+    let page = parser.parse_page().unwrap().unwrap();
+    assert_eq!(page, stubs[0]);
+    // the analysis skips revisions without text
+    assert!(matches!(
+        PageAnalysis::analyse_page(&page.revisions),
+        Err(AnalysisError::NoValidRevisions)
+    ));
+
+    let tokens = |analysis: &PageAnalysis| {
+        iterate_revision_tokens(analysis, &analysis.current_revision)
+            .map(|token| (token.value.to_string(), analysis[token].origin_revision.id))
+            .collect::<Vec<_>>()
+    };
+    let expected = PageAnalysis::analyse_page(&full[0].revisions).unwrap();
+
+    let mut provider = ZstdTextStore(store.clone());
+    let mut revisions = provider.fill_texts(page.revisions.clone());
+    let analysis = PageAnalysis::analyse_page(&mut revisions).unwrap();
+    revisions.finish().unwrap();
+    assert_eq!(tokens(&analysis), tokens(&expected));
+    assert_eq!(analysis.spam_ids, expected.spam_ids);
+
+    // a broken text ends the iteration with an error
+    let last_id = page.revisions.last().unwrap().id;
+    fs::write(store.join(format!("{last_id}-{MAIN_SLOT}.zst")), "not zstd").unwrap();
+    let mut revisions = provider.fill_texts(page.revisions);
+    assert_eq!(revisions.by_ref().count(), stubs[0].revisions.len() - 1);
+    assert!(revisions.finish().is_err());
+}
+
+#[test]
+fn skip_text_keeps_sizes_without_decoding_text() {
+    let full = parse_sequential(FIXTURE).unwrap();
+    let options = ParserOptions::new().skip_text(true);
+    let mut parser = DumpParser::with_options(FIXTURE.as_bytes(), options).unwrap();
+    let page = parser.parse_page().unwrap().unwrap();
+    assert!(parser.parse_page().unwrap().is_none());
+
+    let texts: Vec<_> = page.revisions.iter().map(|r| r.text.clone()).collect();
+    assert_eq!(
+        texts,
+        [
+            Text::Stub {
+                bytes: Some(1204),
+                location: None
+            },
+            Text::Stub {
+                bytes: Some(724),
+                location: None
+            },
+        ]
+    );
+    // everything but the text is parsed as usual
+    for (revision, expected) in page.revisions.iter().zip(&full[0].revisions) {
+        assert_eq!(revision.id, expected.id);
+        assert_eq!(revision.timestamp, expected.timestamp);
+        assert_eq!(revision.contributor, expected.contributor);
+        assert_eq!(revision.sha1, expected.sha1);
+    }
+}
+
+#[test]
+fn dump_stats_count_pages_revisions_and_sizes() {
+    let dump = build_dump(3);
+    let options = ParserOptions::new().skip_text(true);
+    let mut parser = DumpParser::with_options(dump.as_bytes(), options).unwrap();
+    let stats = DumpStats::from_parser(&mut parser, 2).unwrap();
+
+    assert_eq!(stats.pages, 3);
+    assert_eq!(stats.revisions, 6);
+    assert_eq!(stats.text_bytes, 3 * (1204 + 724));
+    assert_eq!(stats.anonymous_revisions, 0);
+    assert_eq!(stats.anonymous_fraction(), 0.0);
+    assert_eq!(stats.deleted_texts, 0);
+    assert_eq!(stats.max_revisions_per_page, 2);
+    assert_eq!(stats.mean_revisions_per_page(), 2.0);
+    assert_eq!(
+        stats.revisions_per_page.into_iter().collect::<Vec<_>>(),
+        [(2, 3)]
+    );
+    let namespace = &stats.namespaces[&8];
+    assert_eq!((namespace.pages, namespace.revisions), (3, 6));
+
+    // ties keep the first pages
+    let largest: Vec<_> = stats
+        .largest_pages
+        .iter()
+        .map(|p| p.title.as_str())
+        .collect();
+    assert_eq!(largest, ["Anontalkpagetext 0", "Anontalkpagetext 1"]);
+
+    // an anonymous edit and a larger page
+    let mut pages = parse_sequential(&dump).unwrap();
+    pages[2].revisions[0].contributor = Contributor {
+        username: "127.0.0.1".into(),
+        id: None,
+        deleted: false,
+    };
+    pages[2].revisions[0].text = Text::Normal("x".repeat(10_000));
+    let mut stats = DumpStats::new(2);
+    for page in &pages {
+        stats.add_page(page);
+    }
+    assert_eq!(stats.anonymous_revisions, 1);
+    assert_eq!(stats.anonymous_fraction(), 1.0 / 6.0);
+    assert_eq!(stats.largest_pages[0].title, "Anontalkpagetext 2");
+    assert_eq!(stats.largest_pages[0].text_bytes, 10_000 + 724);
+}
+
+#[test]
+fn sha1_is_computed_like_mediawiki() {
+    let pages = parse_sequential(FIXTURE).unwrap();
+    for revision in &pages[0].revisions {
+        assert_eq!(
+            Some(Sha1Hash::compute(revision.text.as_str())),
+            revision.sha1
+        );
+    }
+    assert_eq!(
+        Sha1Hash::compute(""),
+        Sha1Hash(*b"phoiac9h4m842xq45sp7s6u21eteeq1")
+    );
+}
+
+#[test]
+fn verify_sha1_reports_mismatches_and_fills_in_missing_hashes() {
+    let dump = FIXTURE
+        .replace("Amerika", "America")
+        .replace("<sha1>alhx7nh8t09lxgevm6bjec4uwki82jt</sha1>", "");
+
+    let mut parser = DumpParser::new(dump.as_bytes()).unwrap();
+    let unverified = parser.parse_page().unwrap().unwrap();
+    assert!(parser.diagnostics().is_empty());
+    assert_eq!(unverified.revisions[1].sha1, None);
+
+    let options = ParserOptions::new().verify_sha1(true);
+    let mut parser = DumpParser::with_options(dump.as_bytes(), options).unwrap();
+    let page = parser.parse_page().unwrap().unwrap();
+
+    assert_eq!(parser.diagnostics().len(), 1);
+    let DiagnosticKind::Sha1Mismatch { expected, actual } = &parser.diagnostics()[0].kind else {
+        panic!("unexpected diagnostic {:?}", parser.diagnostics()[0]);
+    };
+    assert_eq!(expected, "rgmjxuqqcwfffxegauvoyhw080d7txm");
+    assert_eq!(parser.diagnostics()[0].location.revision_id, Some(401685));
+
+    let computed = Sha1Hash::compute(page.revisions[0].text.as_str());
+    assert_eq!(actual.as_bytes(), computed.0);
+    assert_eq!(page.revisions[0].sha1, Some(computed));
+    assert_eq!(
+        page.revisions[1].sha1,
+        Some(Sha1Hash(*b"alhx7nh8t09lxgevm6bjec4uwki82jt"))
+    );
+}
+
+#[test]
+fn site_info_is_complete() {
+    let parser = DumpParser::new(FIXTURE.as_bytes()).unwrap();
+    let site_info = parser.site_info();
+    assert!(parser.diagnostics().is_empty());
+
+    assert_eq!(
+        site_info.schema_version,
+        Some(SchemaVersion {
+            major: 0,
+            minor: 11
+        })
+    );
+    assert_eq!(site_info.sitename, "Wiktionary");
+    assert_eq!(site_info.dbname, "dewiktionary");
+    assert_eq!(
+        site_info.base,
+        "https://de.wiktionary.org/wiki/Wiktionary:Hauptseite"
+    );
+    assert_eq!(site_info.generator, "MediaWiki 1.43.0-wmf.20");
+    assert_eq!(site_info.case, Some(Case::CaseSensitive));
+
+    assert_eq!(site_info.namespaces.len(), 32);
+    assert!(site_info.namespaces[&0].is_default());
+    assert_eq!(site_info.namespaces[&0].case, Some(Case::CaseSensitive));
+    assert_eq!(
+        site_info.namespaces[&3],
+        Namespace {
+            name: "Benutzer Diskussion".into(),
+            case: Some(Case::FirstLetter),
+        }
+    );
+}
+
+#[test]
+fn site_info_reports_invalid_values() {
+    let dump = FIXTURE
+        .replacen(r#"version="0.11""#, r#"version="eleven""#, 1)
+        .replacen("<case>case-sensitive</case>", "<case>upside-down</case>", 1);
+    let parser = DumpParser::new(dump.as_bytes()).unwrap();
+
+    assert_eq!(parser.site_info().schema_version, None);
+    assert_eq!(parser.site_info().case, None);
+    let kinds = parser
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| diagnostic.kind.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            DiagnosticKind::InvalidSchemaVersion("eleven".into()),
+            DiagnosticKind::InvalidCase("upside-down".into()),
+        ]
+    );
+}
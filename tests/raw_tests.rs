@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: MPL-2.0
+#![cfg(feature = "serde")]
+
+use wikiwho::algorithm::{PageAnalysis, PageAnalysisOptions};
+use wikiwho::dump_parser::{DumpParser, Page};
+use wikiwho::output::raw::{read_raw, RawRecord};
+use wikiwho::utils::iterate_revision_tokens;
+
+mod common;
+
+use common::parse_example;
+
+fn parse_fixture() -> (String, Page, PageAnalysis) {
+    let parser = DumpParser::new(common::EXAMPLE_DUMP.as_bytes()).unwrap();
+    let (page, analysis) = parse_example();
+    (parser.site_info().dbname.to_string(), page, analysis)
+}
+
+fn token_values(analysis: &PageAnalysis) -> Vec<String> {
+    iterate_revision_tokens(analysis, &analysis.current_revision)
+        .map(|word| word.value.to_string())
+        .collect()
+}
+
+#[test]
+fn read_written_records() {
+    let (dbname, page, analysis) = parse_fixture();
+    let options = PageAnalysisOptions::new();
+    let record = RawRecord::new(&dbname, &options, &page, &analysis);
+    let mut raw = serde_json::to_vec(&record).unwrap();
+    raw.extend(b"\n\n");
+
+    let records: Vec<_> = read_raw(raw.as_slice()).collect::<Result<_, _>>().unwrap();
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.crate_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(record.dbname, "enwiki");
+    assert_eq!(record.options.slot, "main");
+
+    let (metadata, reloaded) = records.into_iter().next().unwrap().into_parts();
+    assert_eq!(metadata.title, page.title);
+    assert_eq!(metadata.namespace, page.namespace);
+    assert_eq!(metadata.id, Some(4242));
+    let ids: Vec<_> = metadata.revisions.iter().map(|rev| rev.id).collect();
+    assert_eq!(ids, [100, 101, 102, 103]);
+    let revision = metadata.revision(101).unwrap();
+    assert_eq!(revision.contributor, page.revisions[1].contributor);
+    assert_eq!(revision.timestamp, page.revisions[1].timestamp);
+
+    assert_eq!(token_values(&reloaded), token_values(&analysis));
+    assert_eq!(reloaded.spam_ids, analysis.spam_ids);
+    // the origin of every token can be looked up in the page metadata
+    for word in iterate_revision_tokens(&reloaded, &reloaded.current_revision) {
+        let origin = reloaded[word].origin_revision.id;
+        assert!(metadata.revision(origin).is_some());
+    }
+}
+
+#[test]
+fn stops_after_error() {
+    let (dbname, page, analysis) = parse_fixture();
+    let record = RawRecord::new(&dbname, &PageAnalysisOptions::new(), &page, &analysis);
+    let mut raw = b"{\"crate_version\": 1}\n".to_vec();
+    raw.extend(serde_json::to_vec(&record).unwrap());
+
+    let mut records = read_raw(raw.as_slice());
+    assert!(records.next().unwrap().is_err());
+    assert!(records.next().is_none());
+}
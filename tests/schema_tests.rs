@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: MPL-2.0
+#![cfg(feature = "schema")]
+
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+use wikiwho::algorithm::PageAnalysisOptions;
+use wikiwho::output::jsonl::{PageOutput, RevisionFields, RevisionSelection};
+use wikiwho::output::raw::RawRecord;
+use wikiwho::output::schema::OutputSchema;
+use wikiwho::output::wikiwho_api::{AllContent, EditPersistence, RevContent, RevIds};
+
+mod common;
+
+use common::parse_example;
+
+fn validator(schema: OutputSchema) -> jsonschema::Validator {
+    jsonschema::validator_for(&serde_json::to_value(schema.schema()).unwrap()).unwrap()
+}
+
+fn assert_valid(schema: OutputSchema, record: &impl serde::Serialize) {
+    let record = serde_json::to_value(record).unwrap();
+    let errors: Vec<_> = validator(schema)
+        .iter_errors(&record)
+        .map(|error| format!("{} at {}", error, error.instance_path()))
+        .collect();
+    assert!(errors.is_empty(), "{}: {errors:#?}", schema.format());
+}
+
+/// Set `UPDATE_SCHEMAS=1` to write the generated schemas to `schemas/`.
+#[test]
+fn shipped_schemas_are_up_to_date() {
+    for schema in OutputSchema::ALL {
+        let path = format!(
+            "{}/schemas/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            schema.file_name()
+        );
+        let generated = serde_json::to_value(schema.schema()).unwrap();
+        if std::env::var_os("UPDATE_SCHEMAS").is_some() {
+            // the same as `wikiwho-cli schema`
+            let json = serde_json::to_string_pretty(&schema.schema()).unwrap();
+            std::fs::write(&path, json + "\n").unwrap();
+            continue;
+        }
+        let shipped: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            shipped, generated,
+            "{path} is outdated, run the tests with UPDATE_SCHEMAS=1"
+        );
+    }
+}
+
+#[test]
+fn records_validate() {
+    let (page, analysis) = parse_example();
+    let latest = PageOutput::new(&page, &analysis, &RevisionSelection::Latest);
+    let selected = PageOutput::new(
+        &page,
+        &analysis,
+        &RevisionSelection::Ids(BTreeSet::from([101, 103])),
+    );
+    let with_fields = PageOutput::with_revision_fields(
+        &page,
+        &analysis,
+        &RevisionSelection::Latest,
+        RevisionFields::ALL,
+    );
+    assert_valid(OutputSchema::Jsonl, &with_fields);
+    assert_valid(OutputSchema::Jsonl, &latest);
+    assert_valid(OutputSchema::Jsonl, &selected);
+    assert_valid(OutputSchema::Json, &[latest, selected]);
+
+    let options = PageAnalysisOptions::new();
+    assert_valid(
+        OutputSchema::Raw,
+        &RawRecord::new("enwiki", &options, &page, &analysis),
+    );
+
+    assert_valid(OutputSchema::AllContent, &AllContent::new(&page, &analysis));
+    assert_valid(
+        OutputSchema::RevContent,
+        &RevContent::new(&page, &analysis, &analysis.ordered_revisions),
+    );
+    assert_valid(OutputSchema::RevIds, &RevIds::new(&page, &analysis));
+    assert_valid(
+        OutputSchema::EditPersistence,
+        &EditPersistence::new(&page, &analysis, |_| false),
+    );
+}
+
+#[test]
+fn other_schema_versions_are_rejected() {
+    let (page, analysis) = parse_example();
+    let mut record = serde_json::to_value(PageOutput::new(
+        &page,
+        &analysis,
+        &RevisionSelection::Latest,
+    ))
+    .unwrap();
+    let validator = validator(OutputSchema::Jsonl);
+
+    // fields added within a version are accepted
+    record["added_later"] = Value::Bool(true);
+    assert!(validator.is_valid(&record));
+
+    record["schema_version"] = (wikiwho::output::SCHEMA_VERSION + 1).into();
+    assert!(!validator.is_valid(&record));
+    record.as_object_mut().unwrap().remove("schema_version");
+    assert!(!validator.is_valid(&record));
+}
+
+#[test]
+fn formats_round_trip() {
+    for schema in OutputSchema::ALL {
+        assert_eq!(OutputSchema::from_format(schema.format()), Some(schema));
+    }
+    assert_eq!(OutputSchema::from_format("tsv"), None);
+}
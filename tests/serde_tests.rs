@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: MPL-2.0
+#![cfg(feature = "serde")]
+
+use wikiwho::dump_parser::{stats::DumpStats, Comment, Contributor, DumpParser, Page, Text};
+
+mod common;
+
+fn bincode_round_trip(page: &Page) -> Page {
+    let bytes = bincode::serde::encode_to_vec(page, bincode::config::standard()).unwrap();
+    bincode::serde::decode_from_slice(&bytes, bincode::config::standard())
+        .unwrap()
+        .0
+}
+
+#[test]
+fn pages_round_trip() {
+    let mut page = DumpParser::new(common::EXAMPLE_DUMP.as_bytes())
+        .unwrap()
+        .parse_page()
+        .unwrap()
+        .unwrap();
+    page.revisions[0].contributor = Contributor::deleted();
+    page.revisions[0].comment = Some(Comment::Deleted);
+    page.revisions[1].comment = Some(Comment::Normal("Deleted".into()));
+    page.revisions[2].text = Text::Deleted;
+    page.revisions[3].text = Text::Stub {
+        bytes: Some(44),
+        location: Some("tt:103".into()),
+    };
+
+    let json: Page = serde_json::from_str(&serde_json::to_string(&page).unwrap()).unwrap();
+    assert_eq!(json, page);
+    assert_eq!(bincode_round_trip(&page), page);
+}
+
+/// Pages serialized before deleted contributors and comments were modelled have no
+/// `deleted` field and bare string comments.
+#[test]
+fn old_pages_deserialize() {
+    let page: Page = serde_json::from_str(include_str!(
+        "fixtures/exact-regressions/Anontalkpagetext_shortened.json"
+    ))
+    .unwrap();
+    assert_eq!(page.revisions.len(), 2);
+    assert_eq!(page.revisions[0].contributor.username, "Pajz");
+    assert!(!page.revisions[0].contributor.deleted);
+
+    let contributor: Contributor =
+        serde_json::from_str(r#"{"username": "127.0.0.1", "id": null}"#).unwrap();
+    assert!(!contributor.deleted);
+    let comment: Comment = serde_json::from_str(r#""fixed typo""#).unwrap();
+    assert_eq!(comment, Comment::Normal("fixed typo".into()));
+    let comment: Comment = serde_json::from_str(r#"{"Normal": "fixed typo"}"#).unwrap();
+    assert_eq!(comment, Comment::Normal("fixed typo".into()));
+    let comment: Comment = serde_json::from_str(r#""Deleted""#).unwrap();
+    assert_eq!(comment, Comment::Deleted);
+}
+
+#[test]
+fn dump_stats_keep_tracking_largest_pages() {
+    let mut page = DumpParser::new(common::EXAMPLE_DUMP.as_bytes())
+        .unwrap()
+        .parse_page()
+        .unwrap()
+        .unwrap();
+    let mut stats = DumpStats::new(1);
+    stats.add_page(&page);
+
+    let mut stats: DumpStats =
+        serde_json::from_str(&serde_json::to_string(&stats).unwrap()).unwrap();
+    page.title = "Larger".into();
+    page.revisions[0].text = Text::Normal("x".repeat(10_000));
+    stats.add_page(&page);
+    let largest: Vec<_> = stats
+        .largest_pages
+        .iter()
+        .map(|p| p.title.as_str())
+        .collect();
+    assert_eq!(largest, ["Larger"]);
+}
@@ -1,8 +1,8 @@
 // SPDX-License-Identifier: MPL-2.0
 //! Visible "this suite was skipped" notices for the feature-gated integration tests.
 //!
-//! `algorithm_exact_tests`, `algorithm_statistic_tests`, and `utils_comparisons`
-//! are gated behind cargo features at the *file* level (e.g.
+//! `algorithm_exact_tests`, `algorithm_statistic_tests`, `utils_comparisons`,
+//! `api_json_tests`, `serde_tests`, `jsonl_tests`, `raw_tests`, `wikiwho_api_tests`, `parquet_tests`, `sqlite_tests`, `schema_tests`, `async_parser_tests`, `mmap_parser_tests` and `compression_tests` are gated behind cargo features at the *file* level (e.g.
 //! `#![cfg(all(feature = "python-diff", feature = "serde"))]`). Without those features
 //! each one compiles to an empty test binary that prints `running 0 tests` — which is
 //! indistinguishable from a passing run, and has fooled contributors into thinking a
@@ -25,12 +25,13 @@ fn skipped_algorithm_exact_tests_enable_python_diff_and_serde() {
     );
 }
 
-/// `tests/algorithm_statistic_tests.rs` — gold-standard accuracy (uses the serde JSON cache).
-#[cfg(not(feature = "serde"))]
+/// `tests/algorithm_statistic_tests.rs` — gold-standard accuracy (uses the serde JSON cache
+/// and reads compressed dump shards).
+#[cfg(not(all(feature = "serde", feature = "compression")))]
 #[test]
-fn skipped_algorithm_statistic_tests_enable_serde() {
+fn skipped_algorithm_statistic_tests_enable_serde_and_compression() {
     eprintln!(
-        "SKIP: algorithm_statistic_tests compiled empty — run with `--features serde` \
+        "SKIP: algorithm_statistic_tests compiled empty — run with `--features serde,compression` \
          to check gold-standard accuracy (see CONTRIBUTING.md)."
     );
 }
@@ -44,3 +45,113 @@ fn skipped_utils_comparisons_enable_python_diff() {
          to check Rust-vs-Python tokenizer parity (see CONTRIBUTING.md)."
     );
 }
+
+/// `tests/async_parser_tests.rs` — `AsyncDumpParser` against the blocking parser.
+#[cfg(not(feature = "async"))]
+#[test]
+fn skipped_async_parser_tests_enable_async() {
+    eprintln!(
+        "SKIP: async_parser_tests compiled empty — run with `--features async` \
+         to check the async dump parser (see CONTRIBUTING.md)."
+    );
+}
+
+/// `tests/api_json_tests.rs` — reading MediaWiki Action API responses (`dump_parser::api_json`).
+#[cfg(not(feature = "serde"))]
+#[test]
+fn skipped_api_json_tests_enable_serde() {
+    eprintln!(
+        "SKIP: api_json_tests compiled empty — run with `--features serde` \
+         to check the API JSON reader (see CONTRIBUTING.md)."
+    );
+}
+
+/// `tests/serde_tests.rs` — serializing and deserializing `Page`s.
+#[cfg(not(feature = "serde"))]
+#[test]
+fn skipped_serde_tests_enable_serde() {
+    eprintln!(
+        "SKIP: serde_tests compiled empty — run with `--features serde` \
+         to check (de)serializing pages (see CONTRIBUTING.md)."
+    );
+}
+
+/// `tests/jsonl_tests.rs` — the `wikiwho-cli` page records (`output::jsonl`).
+#[cfg(not(feature = "serde"))]
+#[test]
+fn skipped_jsonl_tests_enable_serde() {
+    eprintln!(
+        "SKIP: jsonl_tests compiled empty — run with `--features serde` \
+         to check reading the CLI records (see CONTRIBUTING.md)."
+    );
+}
+
+/// `tests/raw_tests.rs` — the `wikiwho-cli -f raw` records (`output::raw`).
+#[cfg(not(feature = "serde"))]
+#[test]
+fn skipped_raw_tests_enable_serde() {
+    eprintln!(
+        "SKIP: raw_tests compiled empty — run with `--features serde` \
+         to check reloading raw records (see CONTRIBUTING.md)."
+    );
+}
+
+/// `tests/schema_tests.rs` — JSON Schemas of the CLI output (`output::schema`).
+#[cfg(not(feature = "schema"))]
+#[test]
+fn skipped_schema_tests_enable_schema() {
+    eprintln!(
+        "SKIP: schema_tests compiled empty — run with `--features schema` \
+         to validate the output against the shipped schemas (see CONTRIBUTING.md)."
+    );
+}
+
+/// `tests/wikiwho_api_tests.rs` — WikiWho API response schemas (`output::wikiwho_api`).
+#[cfg(not(feature = "serde"))]
+#[test]
+fn skipped_wikiwho_api_tests_enable_serde() {
+    eprintln!(
+        "SKIP: wikiwho_api_tests compiled empty — run with `--features serde` \
+         to check the WikiWho API output schemas (see CONTRIBUTING.md)."
+    );
+}
+
+/// `tests/parquet_tests.rs` — Parquet tables (`output::parquet`).
+#[cfg(not(feature = "arrow"))]
+#[test]
+fn skipped_parquet_tests_enable_arrow() {
+    eprintln!(
+        "SKIP: parquet_tests compiled empty — run with `--features arrow` \
+         to check the Parquet output (see CONTRIBUTING.md)."
+    );
+}
+
+/// `tests/sqlite_tests.rs` — SQLite tables (`output::sqlite`).
+#[cfg(not(feature = "sqlite"))]
+#[test]
+fn skipped_sqlite_tests_enable_sqlite() {
+    eprintln!(
+        "SKIP: sqlite_tests compiled empty — run with `--features sqlite` \
+         to check the SQLite output (see CONTRIBUTING.md)."
+    );
+}
+
+/// `tests/mmap_parser_tests.rs` — `MmapDumpParser` against the buffered parser.
+#[cfg(not(feature = "mmap"))]
+#[test]
+fn skipped_mmap_parser_tests_enable_mmap() {
+    eprintln!(
+        "SKIP: mmap_parser_tests compiled empty — run with `--features mmap` \
+         to check the memory-mapped dump parser (see CONTRIBUTING.md)."
+    );
+}
+
+/// `tests/compression_tests.rs` — `DumpParser::open` and compression detection.
+#[cfg(not(feature = "compression"))]
+#[test]
+fn skipped_compression_tests_enable_compression() {
+    eprintln!(
+        "SKIP: compression_tests compiled empty — run with `--features compression` \
+         to check reading compressed dumps (see CONTRIBUTING.md)."
+    );
+}
@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: MPL-2.0
+#![cfg(feature = "sqlite")]
+
+use wikiwho::output::sqlite::{Connection, SqliteWriter};
+
+mod common;
+
+use common::parse_example;
+
+fn count(connection: &Connection, table: &str) -> i64 {
+    connection
+        .query_row(&format!("SELECT count(*) FROM {table}"), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+}
+
+#[test]
+fn tables_and_blame_query() {
+    let (page, analysis) = parse_example();
+    let mut writer = SqliteWriter::new(Connection::open_in_memory().unwrap()).unwrap();
+    writer.write_page(&page, &analysis).unwrap();
+    let mut other = page.clone();
+    other.title = "Other".into();
+    writer.write_page(&other, &analysis).unwrap();
+    let connection = writer.finish().unwrap();
+
+    let tokens =
+        wikiwho::utils::iterate_revision_tokens(&analysis, &analysis.current_revision).count();
+    assert_eq!(count(&connection, "pages"), 2);
+    assert_eq!(count(&connection, "revisions"), 8);
+    assert_eq!(count(&connection, "tokens"), 2 * tokens as i64);
+
+    let revisions: Vec<(i32, String, String)> = connection
+        .prepare("SELECT rev_id, timestamp, editor FROM revisions WHERE page = 1 ORDER BY rev_id")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(revisions.len(), 4);
+    assert_eq!(
+        revisions[1],
+        (101, "2020-01-01T12:00:00Z".into(), "0|192.0.2.1".into())
+    );
+
+    // who introduced "brown" in the latest revision of "Example"
+    let (position, rev_id, editor): (i64, i32, String) = connection
+        .query_row(
+            "SELECT t.position, r.rev_id, r.editor
+             FROM pages p
+             JOIN tokens t ON t.page = p.id
+             JOIN revisions r ON r.page = p.id AND r.rev_id = t.o_rev_id
+             WHERE p.title = 'Example' AND t.str = 'brown'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!((position, rev_id, editor.as_str()), (2, 100, "11"));
+
+    let events: Vec<(String, i32)> = connection
+        .prepare(
+            "SELECT e.kind, e.rev_id FROM token_events e JOIN tokens t ON t.id = e.token
+             WHERE t.page = 1 AND t.position = 2 ORDER BY e.kind",
+        )
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(events, [("in".into(), 102), ("out".into(), 101)]);
+
+    let page_id: Option<i32> = connection
+        .query_row(
+            "SELECT page_id FROM pages WHERE title = 'Example'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(page_id, Some(4242));
+}
+
+#[test]
+fn create_refuses_existing_databases() {
+    let (page, analysis) = parse_example();
+    let path = std::env::temp_dir().join(format!("wikiwho_sqlite_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut writer = SqliteWriter::create(&path).unwrap();
+    writer.write_page(&page, &analysis).unwrap();
+    writer.finish().unwrap();
+
+    let error = SqliteWriter::create(&path).err().unwrap();
+    assert!(error.to_string().contains("not empty"), "{error}");
+
+    // appending needs an explicit connection
+    let mut writer = SqliteWriter::new(Connection::open(&path).unwrap()).unwrap();
+    writer.write_page(&page, &analysis).unwrap();
+    let connection = writer.finish().unwrap();
+    assert_eq!(count(&connection, "pages"), 2);
+
+    drop(connection);
+    std::fs::remove_file(&path).unwrap();
+}
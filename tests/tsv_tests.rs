@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: MPL-2.0
+use std::collections::BTreeSet;
+
+use wikiwho::algorithm::PageAnalysis;
+use wikiwho::dump_parser::{Page, Text};
+use wikiwho::output::tsv::{TsvColumn, TsvWriter, UnknownColumn};
+use wikiwho::output::RevisionSelection;
+
+mod common;
+
+use common::parse_example;
+
+fn write_tsv(
+    page: &Page,
+    analysis: &PageAnalysis,
+    columns: Vec<TsvColumn>,
+    revisions: &RevisionSelection,
+) -> String {
+    let mut writer = TsvWriter::new(Vec::new(), columns).unwrap();
+    writer.write_page(page, analysis, revisions).unwrap();
+    String::from_utf8(writer.finish().unwrap()).unwrap()
+}
+
+#[test]
+fn latest_revision_with_all_columns() {
+    let (page, analysis) = parse_example();
+    let tsv = write_tsv(
+        &page,
+        &analysis,
+        TsvColumn::ALL.to_vec(),
+        &RevisionSelection::Latest,
+    );
+    let lines: Vec<_> = tsv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "page_title\tnamespace\trev_id\tposition\ttoken_id\tstr\to_rev_id\to_timestamp\teditor\tin_count\tout_count"
+    );
+    let tokens = wikiwho::utils::iterate_revision_tokens(&analysis, &analysis.current_revision);
+    assert_eq!(lines.len(), 1 + tokens.count());
+
+    let mut brown: Vec<_> = lines[3].split('\t').collect();
+    let token_id = brown.remove(4);
+    assert!(token_id.parse::<usize>().is_ok());
+    assert_eq!(
+        brown,
+        [
+            "Example",
+            "0",
+            "103",
+            "2",
+            "brown",
+            "100",
+            "2020-01-01T00:00:00Z",
+            "11",
+            "1",
+            "1"
+        ]
+    );
+    let over: Vec<_> = lines[6].split('\t').collect();
+    assert_eq!((over[5], over[6], over[8]), ("over", "103", "12"));
+}
+
+#[test]
+fn selected_columns_and_revisions() {
+    let (page, analysis) = parse_example();
+    let columns = vec![TsvColumn::RevId, TsvColumn::Token, TsvColumn::Editor];
+    let revisions = RevisionSelection::Ids(BTreeSet::from([101, 103]));
+    let tsv = write_tsv(&page, &analysis, columns, &revisions);
+    let lines: Vec<_> = tsv.lines().collect();
+    assert_eq!(lines[0], "rev_id\tstr\teditor");
+    assert_eq!(lines[3], "101\tred\t0|192.0.2.1");
+    let rev_ids: BTreeSet<_> = lines[1..].iter().map(|line| &line[..3]).collect();
+    assert_eq!(rev_ids, BTreeSet::from(["101", "103"]));
+}
+
+#[test]
+fn escapes_separators() {
+    let (mut page, analysis) = parse_example();
+    page.title = "A\tB\nC\\D".into();
+    let tsv = write_tsv(
+        &page,
+        &analysis,
+        vec![TsvColumn::PageTitle, TsvColumn::Position],
+        &RevisionSelection::Latest,
+    );
+    assert_eq!(tsv.lines().nth(1).unwrap(), "A\\tB\\nC\\\\D\t0");
+
+    // `"` is a token of its own and written unquoted
+    let Text::Normal(text) = &mut page.revisions.last_mut().unwrap().text else {
+        unreachable!()
+    };
+    text.push_str(" He said \"hi\".");
+    let analysis = PageAnalysis::analyse_page(&page.revisions).unwrap();
+    let tsv = write_tsv(
+        &page,
+        &analysis,
+        vec![TsvColumn::Position, TsvColumn::Token],
+        &RevisionSelection::Latest,
+    );
+    let tokens: Vec<_> = tsv
+        .lines()
+        .skip(1)
+        .map(|line| line.split('\t').nth(1).unwrap())
+        .collect();
+    assert!(
+        tokens.ends_with(&["said", "\"", "hi", "\"", "."]),
+        "{tokens:?}"
+    );
+}
+
+#[test]
+fn column_names() {
+    for column in TsvColumn::ALL {
+        assert_eq!(column.name().parse::<TsvColumn>(), Ok(column));
+    }
+    assert_eq!(
+        "author".parse::<TsvColumn>(),
+        Err(UnknownColumn("author".into()))
+    );
+}
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MPL-2.0
+#![cfg(feature = "serde")]
+
+use wikiwho::output::wikiwho_api::{AllContent, EditPersistence, RevContent, RevIds};
+
+mod common;
+
+use common::parse_example;
+
+fn assert_matches_response(actual: &impl serde::Serialize, endpoint: &str) {
+    let path = format!("tests/fixtures/wikiwho-api/{endpoint}.json");
+    let expected: serde_json::Value =
+        serde_json::from_reader(std::fs::File::open(path).unwrap()).unwrap();
+    assert_eq!(
+        serde_json::to_value(actual).unwrap(),
+        expected,
+        "{endpoint}"
+    );
+}
+
+#[test]
+fn responses_match_api_schemas() {
+    let (page, analysis) = parse_example();
+    assert_eq!(page.id, Some(4242));
+
+    assert_matches_response(&AllContent::new(&page, &analysis), "all_content");
+    assert_matches_response(
+        &RevContent::new(&page, &analysis, &analysis.ordered_revisions),
+        "rev_content",
+    );
+    assert_matches_response(&RevIds::new(&page, &analysis), "rev_ids");
+    assert_matches_response(
+        &EditPersistence::new(&page, &analysis, |_| false),
+        "edit_persistence",
+    );
+}
+
+#[test]
+fn rev_content_of_latest_revision_matches_all_content() {
+    let (page, analysis) = parse_example();
+    let all_content = AllContent::new(&page, &analysis);
+    let rev_content = RevContent::new(&page, &analysis, [&analysis.current_revision]);
+
+    assert_eq!(rev_content.revisions.len(), 1);
+    assert_eq!(rev_content.revisions[0].id, 103);
+    assert_eq!(rev_content.revisions[0].tokens, all_content.all_tokens);
+}
+
+#[test]
+fn edit_persistence_counts_stopwords() {
+    let (page, analysis) = parse_example();
+    let response = EditPersistence::new(&page, &analysis, |word| word == "the");
+
+    let stopwords: Vec<_> = response
+        .editions
+        .iter()
+        .map(|edition| (edition.editor_id.as_str(), edition.adds_stopword_count))
+        .collect();
+    assert_eq!(stopwords, [("0|192.0.2.1", 0), ("11", 1), ("12", 1)]);
+    // stopwords are included in the totals
+    assert_eq!(response.editions[1].adds, 6);
+}